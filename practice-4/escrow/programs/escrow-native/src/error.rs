@@ -0,0 +1,153 @@
+use solana_program::{entrypoint::ProgramResult, program_error::ProgramError};
+
+// The codes the Anchor build returns for the same failures, so clients decode
+// errors from either build with the escrow IDL.  Anchor's own errors keep
+// their framework numbers, the escrow's are `6000` plus their position in
+// `escrow::error::ErrorCode`.  `UnsupportedByNativeBuild` is this build's
+// own, and kept well clear of the escrow's range so no error added there
+// ever takes its code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowError {
+    InstructionMissing = 100,
+    InstructionFallbackNotFound = 101,
+    InstructionDidNotDeserialize = 102,
+    ConstraintHasOne = 2001,
+    ConstraintSeeds = 2006,
+    ConstraintTokenMint = 2014,
+    ConstraintTokenOwner = 2015,
+    AccountDiscriminatorNotFound = 3001,
+    AccountDiscriminatorMismatch = 3002,
+    AccountDidNotDeserialize = 3003,
+    AccountDidNotSerialize = 3004,
+    AccountNotEnoughKeys = 3005,
+    AccountOwnedByWrongProgram = 3007,
+    InvalidProgramId = 3008,
+    AccountNotSigner = 3010,
+    AccountNotInitialized = 3012,
+    InvalidAmount = 6000,
+    MathOverflow = 6001,
+    PaymentMintNotAccepted = 6008,
+    InvalidRentReceiver = 6012,
+    InvalidVault = 6013,
+    IndexPageFull = 6016,
+    InvalidIndexPage = 6017,
+    InvalidReferralFee = 6031,
+    OutdatedOffer = 6053,
+    UnsupportedByNativeBuild = 9000,
+}
+
+impl From<EscrowError> for ProgramError {
+    fn from(error: EscrowError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
+pub fn require(condition: bool, error: EscrowError) -> ProgramResult {
+    if condition {
+        Ok(())
+    } else {
+        Err(error.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::error::ErrorCode as AnchorErrorCode;
+    use escrow::error::ErrorCode;
+
+    use super::EscrowError;
+
+    #[test]
+    fn codes_match_the_anchor_build() {
+        let codes = [
+            (
+                EscrowError::InstructionMissing,
+                AnchorErrorCode::InstructionMissing as u32,
+            ),
+            (
+                EscrowError::InstructionFallbackNotFound,
+                AnchorErrorCode::InstructionFallbackNotFound as u32,
+            ),
+            (
+                EscrowError::InstructionDidNotDeserialize,
+                AnchorErrorCode::InstructionDidNotDeserialize as u32,
+            ),
+            (
+                EscrowError::ConstraintHasOne,
+                AnchorErrorCode::ConstraintHasOne as u32,
+            ),
+            (
+                EscrowError::ConstraintSeeds,
+                AnchorErrorCode::ConstraintSeeds as u32,
+            ),
+            (
+                EscrowError::ConstraintTokenMint,
+                AnchorErrorCode::ConstraintTokenMint as u32,
+            ),
+            (
+                EscrowError::ConstraintTokenOwner,
+                AnchorErrorCode::ConstraintTokenOwner as u32,
+            ),
+            (
+                EscrowError::AccountDiscriminatorNotFound,
+                AnchorErrorCode::AccountDiscriminatorNotFound as u32,
+            ),
+            (
+                EscrowError::AccountDiscriminatorMismatch,
+                AnchorErrorCode::AccountDiscriminatorMismatch as u32,
+            ),
+            (
+                EscrowError::AccountDidNotDeserialize,
+                AnchorErrorCode::AccountDidNotDeserialize as u32,
+            ),
+            (
+                EscrowError::AccountDidNotSerialize,
+                AnchorErrorCode::AccountDidNotSerialize as u32,
+            ),
+            (
+                EscrowError::AccountNotEnoughKeys,
+                AnchorErrorCode::AccountNotEnoughKeys as u32,
+            ),
+            (
+                EscrowError::AccountOwnedByWrongProgram,
+                AnchorErrorCode::AccountOwnedByWrongProgram as u32,
+            ),
+            (
+                EscrowError::InvalidProgramId,
+                AnchorErrorCode::InvalidProgramId as u32,
+            ),
+            (
+                EscrowError::AccountNotSigner,
+                AnchorErrorCode::AccountNotSigner as u32,
+            ),
+            (
+                EscrowError::AccountNotInitialized,
+                AnchorErrorCode::AccountNotInitialized as u32,
+            ),
+            (EscrowError::InvalidAmount, ErrorCode::InvalidAmount.into()),
+            (EscrowError::MathOverflow, ErrorCode::MathOverflow.into()),
+            (
+                EscrowError::PaymentMintNotAccepted,
+                ErrorCode::PaymentMintNotAccepted.into(),
+            ),
+            (
+                EscrowError::InvalidRentReceiver,
+                ErrorCode::InvalidRentReceiver.into(),
+            ),
+            (EscrowError::InvalidVault, ErrorCode::InvalidVault.into()),
+            (EscrowError::IndexPageFull, ErrorCode::IndexPageFull.into()),
+            (
+                EscrowError::InvalidIndexPage,
+                ErrorCode::InvalidIndexPage.into(),
+            ),
+            (
+                EscrowError::InvalidReferralFee,
+                ErrorCode::InvalidReferralFee.into(),
+            ),
+            (EscrowError::OutdatedOffer, ErrorCode::OutdatedOffer.into()),
+        ];
+        for (native, anchor) in codes {
+            assert_eq!(native as u32, anchor, "{native:?}");
+        }
+    }
+}
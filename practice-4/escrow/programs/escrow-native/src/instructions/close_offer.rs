@@ -0,0 +1,106 @@
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use super::{check_rent_receiver, check_signer, next_account};
+use crate::{
+    error::{require, EscrowError},
+    state::{AnchorAccount, MarketIndexPage, MarketStats, Offer, Reputation, OFFER_VERSION},
+    system::{check_system_program, close_program_account},
+    token::{
+        associated_token_address, check_token_program, close_account, mint_decimals,
+        transfer_checked, TokenAccount,
+    },
+};
+
+pub const DISCRIMINATOR: [u8; 8] = [191, 72, 67, 35, 239, 209, 97, 132];
+
+pub fn close_offer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+    let maker = next_account(accounts)?;
+    let offer = next_account(accounts)?;
+    let vault = next_account(accounts)?;
+    let token_mint_a = next_account(accounts)?;
+    let token_mint_b = next_account(accounts)?;
+    let maker_token_account_a = next_account(accounts)?;
+    let rent_receiver = next_account(accounts)?;
+    let market_stats = next_account(accounts)?;
+    let maker_reputation = next_account(accounts)?;
+    let market_index_page = next_account(accounts)?;
+    let token_program = next_account(accounts)?;
+    let system_program = next_account(accounts)?;
+
+    check_signer(maker)?;
+    check_token_program(token_program)?;
+    check_system_program(system_program)?;
+
+    let offer_data = Offer::load(offer, program_id)?;
+    require(
+        offer_data.maker == *maker.key
+            && offer_data.token_mint_a == *token_mint_a.key
+            && offer_data.token_mint_b == *token_mint_b.key,
+        EscrowError::ConstraintHasOne,
+    )?;
+    require(
+        offer_data.version == OFFER_VERSION,
+        EscrowError::OutdatedOffer,
+    )?;
+    let decimals = mint_decimals(token_mint_a, token_program)?;
+    mint_decimals(token_mint_b, token_program)?;
+
+    require(
+        *vault.key == associated_token_address(offer.key, token_mint_a.key, token_program.key),
+        EscrowError::InvalidVault,
+    )?;
+    let vault_amount = TokenAccount::load(vault, token_program)?.amount;
+
+    let destination = TokenAccount::load(maker_token_account_a, token_program)?;
+    require(
+        destination.mint == *token_mint_a.key,
+        EscrowError::ConstraintTokenMint,
+    )?;
+    require(
+        destination.owner == *maker.key,
+        EscrowError::ConstraintTokenOwner,
+    )?;
+    check_rent_receiver(rent_receiver, maker, program_id)?;
+
+    let mut stats = MarketStats::load(market_stats, program_id)?;
+    require(
+        stats.token_mint_a == offer_data.token_mint_a
+            && stats.token_mint_b == offer_data.token_mint_b,
+        EscrowError::ConstraintSeeds,
+    )?;
+    let mut record = Reputation::load(maker_reputation, program_id)?;
+    require(record.wallet == *maker.key, EscrowError::ConstraintSeeds)?;
+    let mut page = MarketIndexPage::load(market_index_page, program_id)?;
+    require(
+        page.is_page_of(
+            &offer_data.token_mint_a,
+            &offer_data.token_mint_b,
+            offer_data.index_page,
+        ),
+        EscrowError::ConstraintSeeds,
+    )?;
+
+    let id = offer_data.id.to_le_bytes();
+    let bump = [offer_data.bump];
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"offer", maker.key.as_ref(), &id, &bump]];
+    transfer_checked(
+        token_program,
+        vault,
+        token_mint_a,
+        maker_token_account_a,
+        offer,
+        vault_amount,
+        decimals,
+        &signer_seeds,
+    )?;
+    close_account(token_program, vault, maker, offer, &signer_seeds)?;
+
+    stats.record_close();
+    stats.store(market_stats)?;
+    record.record_cancellation();
+    record.store(maker_reputation)?;
+    page.remove(offer.key);
+    page.store(market_index_page)?;
+    close_program_account(offer, maker)
+}
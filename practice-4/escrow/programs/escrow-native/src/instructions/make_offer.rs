@@ -0,0 +1,196 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use super::{check_signer, is_omitted, next_account};
+use crate::{
+    error::{require, EscrowError},
+    state::{
+        decode_error, AnchorAccount, MarketIndexPage, MarketStats, NoOptions, Offer, PlainTerms,
+        Reputation, MAX_BPS, OFFER_VERSION,
+    },
+    system::{check_system_program, PdaCreator},
+    token::{
+        check_token_program, mint_decimals, transfer_checked, AssociatedAccount,
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+    },
+};
+
+pub const DISCRIMINATOR: [u8; 8] = [214, 98, 97, 35, 59, 12, 44, 178];
+
+#[derive(BorshDeserialize)]
+pub struct MakeOfferArgs {
+    pub id: u64,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub terms: PlainTerms,
+    pub index_page: u32,
+}
+
+pub fn make_offer(program_id: &Pubkey, accounts: &[AccountInfo], mut args: &[u8]) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+    let maker = next_account(accounts)?;
+    let token_mint_a = next_account(accounts)?;
+    let token_mint_b = next_account(accounts)?;
+    let maker_token_account_a = next_account(accounts)?;
+    let maker_token_account_b = next_account(accounts)?;
+    let offer = next_account(accounts)?;
+    let vault = next_account(accounts)?;
+    let market_stats = next_account(accounts)?;
+    let maker_reputation = next_account(accounts)?;
+    let market_index_page = next_account(accounts)?;
+    let previous_index_page = next_account(accounts)?;
+    let associated_token_program = next_account(accounts)?;
+    let token_program = next_account(accounts)?;
+    let system_program = next_account(accounts)?;
+    let args = MakeOfferArgs::deserialize(&mut args)
+        .map_err(|error| decode_error(error, EscrowError::InstructionDidNotDeserialize))?;
+
+    check_signer(maker)?;
+    require(
+        *associated_token_program.key == ASSOCIATED_TOKEN_PROGRAM_ID,
+        EscrowError::InvalidProgramId,
+    )?;
+    check_token_program(token_program)?;
+    check_system_program(system_program)?;
+    let decimals = mint_decimals(token_mint_a, token_program)?;
+    mint_decimals(token_mint_b, token_program)?;
+
+    require(args.token_a_offered_amount > 0, EscrowError::InvalidAmount)?;
+    require(
+        args.terms.max_referral_bps <= MAX_BPS,
+        EscrowError::InvalidReferralFee,
+    )?;
+
+    let associated_account = |account, wallet, mint| AssociatedAccount {
+        payer: maker,
+        account,
+        wallet,
+        mint,
+        system_program,
+        token_program,
+        associated_token_program,
+    };
+    // Created here, at the maker's expense, so takers do not pay its rent.
+    associated_account(maker_token_account_b, maker, token_mint_b).create_if_needed()?;
+
+    let pdas = PdaCreator {
+        program_id,
+        payer: maker,
+        system_program,
+    };
+    let bump = pdas.create::<Offer>(
+        offer,
+        &[b"offer", maker.key.as_ref(), &args.id.to_le_bytes()],
+    )?;
+    associated_account(vault, offer, token_mint_a).create()?;
+
+    let mut stats = pdas.load_or_create(
+        market_stats,
+        &[
+            b"market_stats",
+            token_mint_a.key.as_ref(),
+            token_mint_b.key.as_ref(),
+        ],
+        |bump| Ok(MarketStats::new(*token_mint_a.key, *token_mint_b.key, bump)),
+    )?;
+    require(
+        stats.token_mint_a == *token_mint_a.key && stats.token_mint_b == *token_mint_b.key,
+        EscrowError::ConstraintSeeds,
+    )?;
+
+    let reputation = pdas.load_or_create(
+        maker_reputation,
+        &[b"reputation", maker.key.as_ref()],
+        |bump| {
+            Ok(Reputation::new(
+                *maker.key,
+                bump,
+                Clock::get()?.unix_timestamp,
+            ))
+        },
+    )?;
+    require(
+        reputation.wallet == *maker.key,
+        EscrowError::ConstraintSeeds,
+    )?;
+
+    let index_page = args.index_page;
+    let mut page = pdas.load_or_create(
+        market_index_page,
+        &[
+            b"market_index",
+            token_mint_a.key.as_ref(),
+            token_mint_b.key.as_ref(),
+            &index_page.to_le_bytes(),
+        ],
+        |bump| {
+            // Pages are created in order, each linked from the one before.
+            if index_page > 0 {
+                require(
+                    !is_omitted(previous_index_page, program_id),
+                    EscrowError::InvalidIndexPage,
+                )?;
+                let mut previous_page = MarketIndexPage::load(previous_index_page, program_id)?;
+                require(
+                    previous_page.is_page_of(token_mint_a.key, token_mint_b.key, index_page - 1),
+                    EscrowError::ConstraintSeeds,
+                )?;
+                require(
+                    previous_page.next_page.is_none(),
+                    EscrowError::InvalidIndexPage,
+                )?;
+                previous_page.next_page = Some(index_page);
+                previous_page.store(previous_index_page)?;
+            }
+            Ok(MarketIndexPage::new(
+                *token_mint_a.key,
+                *token_mint_b.key,
+                index_page,
+                bump,
+            ))
+        },
+    )?;
+    require(
+        page.is_page_of(token_mint_a.key, token_mint_b.key, index_page),
+        EscrowError::ConstraintSeeds,
+    )?;
+
+    transfer_checked(
+        token_program,
+        maker_token_account_a,
+        token_mint_a,
+        vault,
+        maker,
+        args.token_a_offered_amount,
+        decimals,
+        &[],
+    )?;
+
+    Offer {
+        id: args.id,
+        maker: *maker.key,
+        token_mint_a: *token_mint_a.key,
+        token_mint_b: *token_mint_b.key,
+        token_b_wanted_amount: args.token_b_wanted_amount,
+        bump,
+        version: OFFER_VERSION,
+        token_a_offered_amount: args.token_a_offered_amount,
+        options_before_index: NoOptions,
+        index_page,
+        options_before_referral: NoOptions,
+        max_referral_bps: args.terms.max_referral_bps,
+        options_after_referral: NoOptions,
+        delegate: None,
+        options_after_delegate: NoOptions,
+    }
+    .store(offer)?;
+
+    stats.record_open();
+    stats.store(market_stats)?;
+    reputation.store(maker_reputation)?;
+    page.insert(*offer.key)?;
+    page.store(market_index_page)
+}
@@ -0,0 +1,46 @@
+// One module per instruction, each with its Anchor discriminator,
+// `sha256("global:<name>")[..8]`.
+
+pub mod close_offer;
+pub mod make_offer;
+pub mod take_offer;
+
+use std::slice::Iter;
+
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::{require, EscrowError};
+
+// Accounts come in the order of the Anchor build's `Accounts` struct.
+pub(crate) fn next_account<'a, 'info>(
+    accounts: &mut Iter<'a, AccountInfo<'info>>,
+) -> Result<&'a AccountInfo<'info>, ProgramError> {
+    accounts
+        .next()
+        .ok_or(EscrowError::AccountNotEnoughKeys.into())
+}
+
+// Anchor clients pass the program id for optional accounts left out.
+pub(crate) fn is_omitted(account: &AccountInfo, program_id: &Pubkey) -> bool {
+    account.key == program_id
+}
+
+pub(crate) fn check_signer(account: &AccountInfo) -> ProgramResult {
+    require(account.is_signer, EscrowError::AccountNotSigner)
+}
+
+// The maker is the only rent receiver of a plain offer, and may also be
+// passed as the optional `rent_receiver`.
+pub(crate) fn check_rent_receiver(
+    rent_receiver: &AccountInfo,
+    maker: &AccountInfo,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    require(
+        is_omitted(rent_receiver, program_id) || rent_receiver.key == maker.key,
+        EscrowError::InvalidRentReceiver,
+    )
+}
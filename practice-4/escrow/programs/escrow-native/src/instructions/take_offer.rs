@@ -0,0 +1,217 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use super::{check_rent_receiver, check_signer, is_omitted, next_account};
+use crate::{
+    error::{require, EscrowError},
+    state::{AnchorAccount, MarketIndexPage, MarketStats, Offer, Reputation, OFFER_VERSION},
+    system::{check_system_program, close_program_account, PdaCreator},
+    token::{
+        associated_token_address, check_token_program, close_account, mint_decimals,
+        transfer_checked, AssociatedAccount, TokenAccount, ASSOCIATED_TOKEN_PROGRAM_ID,
+    },
+};
+
+pub const DISCRIMINATOR: [u8; 8] = [128, 156, 242, 207, 237, 192, 103, 240];
+
+pub fn take_offer(program_id: &Pubkey, accounts: &[AccountInfo], mut args: &[u8]) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+    let taker = next_account(accounts)?;
+    let maker = next_account(accounts)?;
+    let token_mint_a = next_account(accounts)?;
+    let token_mint_b = next_account(accounts)?;
+    let taker_token_account_a = next_account(accounts)?;
+    let taker_token_account_b = next_account(accounts)?;
+    let maker_token_account_b = next_account(accounts)?;
+    let offer = next_account(accounts)?;
+    let vault = next_account(accounts)?;
+    let taker_fill = next_account(accounts)?;
+    let rent_receiver = next_account(accounts)?;
+    let market_stats = next_account(accounts)?;
+    let taker_reputation = next_account(accounts)?;
+    let maker_reputation = next_account(accounts)?;
+    let market_index_page = next_account(accounts)?;
+    let vesting = next_account(accounts)?;
+    let vesting_vault = next_account(accounts)?;
+    let referrer = next_account(accounts)?;
+    let referrer_token_account = next_account(accounts)?;
+    // `token_a_metadata`, `price_feed` and the five receipt accounts, which
+    // plain offers never read, in either build.
+    accounts.nth(6).ok_or(EscrowError::AccountNotEnoughKeys)?;
+    let associated_token_program = next_account(accounts)?;
+    let token_program = next_account(accounts)?;
+    let system_program = next_account(accounts)?;
+    // The merkle proof after it is only read for allowlisted offers.
+    let token_a_amount =
+        u64::deserialize(&mut args).map_err(|_| EscrowError::InstructionDidNotDeserialize)?;
+
+    check_signer(taker)?;
+    require(
+        *associated_token_program.key == ASSOCIATED_TOKEN_PROGRAM_ID,
+        EscrowError::InvalidProgramId,
+    )?;
+    check_token_program(token_program)?;
+    check_system_program(system_program)?;
+    // The Anchor build would create or pay into these even for plain offers.
+    require(
+        [
+            taker_fill,
+            vesting,
+            vesting_vault,
+            referrer,
+            referrer_token_account,
+        ]
+        .iter()
+        .all(|account| is_omitted(account, program_id)),
+        EscrowError::UnsupportedByNativeBuild,
+    )?;
+
+    // Offers are only ever created at their PDA, so an account of the type
+    // needs no address check.  Signing for the vault with the offer's seeds
+    // below would fail for any other account anyway.
+    let offer_data = Offer::load(offer, program_id)?;
+    require(
+        offer_data.maker == *maker.key && offer_data.token_mint_a == *token_mint_a.key,
+        EscrowError::ConstraintHasOne,
+    )?;
+    require(
+        offer_data.version == OFFER_VERSION,
+        EscrowError::OutdatedOffer,
+    )?;
+    require(
+        offer_data.token_mint_b == *token_mint_b.key,
+        EscrowError::PaymentMintNotAccepted,
+    )?;
+    let decimals_a = mint_decimals(token_mint_a, token_program)?;
+    let decimals_b = mint_decimals(token_mint_b, token_program)?;
+
+    // Any other token account the offer owns could be drained to close the
+    // offer with its vault still full.
+    require(
+        *vault.key == associated_token_address(offer.key, token_mint_a.key, token_program.key),
+        EscrowError::InvalidVault,
+    )?;
+    let vault_amount = TokenAccount::load(vault, token_program)?.amount;
+    require(
+        token_a_amount > 0 && token_a_amount <= vault_amount,
+        EscrowError::InvalidAmount,
+    )?;
+
+    AssociatedAccount {
+        payer: taker,
+        account: taker_token_account_a,
+        wallet: taker,
+        mint: token_mint_a,
+        system_program,
+        token_program,
+        associated_token_program,
+    }
+    .create_if_needed()?;
+
+    // Never created here, so takers do not pay the maker's rent.
+    let destination = TokenAccount::load(maker_token_account_b, token_program)?;
+    require(
+        destination.mint == *token_mint_b.key,
+        EscrowError::ConstraintTokenMint,
+    )?;
+    require(
+        destination.owner == *maker.key,
+        EscrowError::ConstraintTokenOwner,
+    )?;
+
+    let mut stats = MarketStats::load(market_stats, program_id)?;
+    require(
+        stats.token_mint_a == offer_data.token_mint_a
+            && stats.token_mint_b == offer_data.token_mint_b,
+        EscrowError::ConstraintSeeds,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let pdas = PdaCreator {
+        program_id,
+        payer: taker,
+        system_program,
+    };
+    let mut taker_record = pdas.load_or_create(
+        taker_reputation,
+        &[b"reputation", taker.key.as_ref()],
+        |bump| Ok(Reputation::new(*taker.key, bump, now)),
+    )?;
+    require(
+        taker_record.wallet == *taker.key,
+        EscrowError::ConstraintSeeds,
+    )?;
+    let mut maker_record = Reputation::load(maker_reputation, program_id)?;
+    require(
+        maker_record.wallet == *maker.key,
+        EscrowError::ConstraintSeeds,
+    )?;
+
+    let mut page = MarketIndexPage::load(market_index_page, program_id)?;
+    require(
+        page.is_page_of(
+            &offer_data.token_mint_a,
+            &offer_data.token_mint_b,
+            offer_data.index_page,
+        ),
+        EscrowError::ConstraintSeeds,
+    )?;
+
+    let token_b_amount = offer_data.token_b_amount_for(token_a_amount)?;
+    transfer_checked(
+        token_program,
+        taker_token_account_b,
+        token_mint_b,
+        maker_token_account_b,
+        taker,
+        token_b_amount,
+        decimals_b,
+        &[],
+    )?;
+
+    let offer_filled = token_a_amount == vault_amount;
+    stats.record_fill(token_a_amount, token_b_amount, now);
+    if offer_filled {
+        stats.record_close();
+        page.remove(offer.key);
+    }
+    stats.store(market_stats)?;
+    page.store(market_index_page)?;
+    // Taking one's own offer builds no track record on either side, but a
+    // freshly created record still has to be written.
+    let self_take = taker.key == maker.key;
+    if !self_take {
+        taker_record.record_take(token_a_amount, decimals_a);
+    }
+    taker_record.store(taker_reputation)?;
+    if !self_take {
+        maker_record.record_sale(token_a_amount);
+        maker_record.store(maker_reputation)?;
+    }
+
+    let id = offer_data.id.to_le_bytes();
+    let bump = [offer_data.bump];
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"offer", maker.key.as_ref(), &id, &bump]];
+    transfer_checked(
+        token_program,
+        vault,
+        token_mint_a,
+        taker_token_account_a,
+        offer,
+        token_a_amount,
+        decimals_a,
+        &signer_seeds,
+    )?;
+
+    // A partial take leaves the offer open for the rest of the vault.
+    if !offer_filled {
+        return Ok(());
+    }
+
+    check_rent_receiver(rent_receiver, maker, program_id)?;
+    close_account(token_program, vault, maker, offer, &signer_seeds)?;
+    close_program_account(offer, maker)
+}
@@ -0,0 +1,56 @@
+//! `make_offer`, `take_offer` and `close_offer` of the escrow program without
+//! the Anchor runtime, for makers and takers who want the cheapest swaps.
+//!
+//! Instructions and accounts are byte for byte those of the Anchor build: the
+//! same discriminators, argument encoding and account order, and the same
+//! `Offer`, `MarketStats`, `Reputation` and `MarketIndexPage` layouts, so
+//! clients generated from the escrow IDL work against either build.  Each
+//! build only owns the accounts made at its own program id, so offers stay
+//! with the build they were made on.  Optional accounts are passed as the
+//! program id, as Anchor clients do.  Errors carry the Anchor build's codes.
+//!
+//! Only plain offers are handled: every `OfferTerms` option off, apart from
+//! the referral cap, and no referrer, vesting or allowlist accounts on take.
+//! Anything else fails with `UnsupportedByNativeBuild`, a code outside the
+//! Anchor build's range, and needs the Anchor build.
+//!
+//! The savings come from what Anchor does on every call and this build
+//! skips: instruction name logs, deserializing accounts the instruction never
+//! reads, and re-deriving the address of accounts whose discriminator and
+//! fields already pin them to their seeds.  Writability is left to the
+//! runtime, which rejects changes to read-only accounts anyway.
+
+pub mod error;
+pub mod instructions;
+pub mod state;
+pub mod system;
+pub mod token;
+
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use error::EscrowError;
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (discriminator, args) = data
+        .split_first_chunk::<8>()
+        .ok_or(EscrowError::InstructionMissing)?;
+    match *discriminator {
+        instructions::make_offer::DISCRIMINATOR => {
+            instructions::make_offer::make_offer(program_id, accounts, args)
+        }
+        instructions::take_offer::DISCRIMINATOR => {
+            instructions::take_offer::take_offer(program_id, accounts, args)
+        }
+        instructions::close_offer::DISCRIMINATOR => {
+            instructions::close_offer::close_offer(program_id, accounts)
+        }
+        _ => Err(EscrowError::InstructionFallbackNotFound.into()),
+    }
+}
@@ -0,0 +1,297 @@
+// The Anchor build's accounts, field for field.  Anchor stores an 8 byte
+// discriminator, `sha256("account:<Name>")[..8]`, followed by the Borsh
+// encoding of the account, and leaves the rest of the account zeroed.
+
+use borsh::{
+    io::{Error, ErrorKind, Read, Result as IoResult, Write},
+    BorshDeserialize, BorshSerialize,
+};
+use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::error::{require, EscrowError};
+
+pub const ANCHOR_DISCRIMINATOR: usize = 8;
+
+pub const OFFER_VERSION: u8 = 2;
+
+pub const MAX_BPS: u16 = 10_000;
+
+pub const OFFERS_PER_INDEX_PAGE: usize = 32;
+
+pub const PRICE_SCALE: u64 = 1_000_000_000;
+
+pub trait AnchorAccount: BorshSerialize + BorshDeserialize {
+    const DISCRIMINATOR: [u8; 8];
+    // Account size, discriminator included, as `ANCHOR_DISCRIMINATOR +
+    // INIT_SPACE` in the Anchor build.
+    const SPACE: usize;
+
+    // Checks the owner and discriminator, as `Account<T>` does.
+    fn load(account: &AccountInfo, program_id: &Pubkey) -> Result<Self, ProgramError> {
+        require(
+            account.owner == program_id,
+            EscrowError::AccountOwnedByWrongProgram,
+        )?;
+        let data = account.try_borrow_data()?;
+        let (discriminator, mut fields) = data
+            .split_first_chunk::<ANCHOR_DISCRIMINATOR>()
+            .ok_or(EscrowError::AccountDiscriminatorNotFound)?;
+        require(
+            *discriminator == Self::DISCRIMINATOR,
+            EscrowError::AccountDiscriminatorMismatch,
+        )?;
+        Self::deserialize(&mut fields)
+            .map_err(|error| decode_error(error, EscrowError::AccountDidNotDeserialize))
+    }
+
+    fn store(&self, account: &AccountInfo) -> Result<(), ProgramError> {
+        let mut data = account.try_borrow_mut_data()?;
+        let (discriminator, mut fields) = data
+            .split_first_chunk_mut::<ANCHOR_DISCRIMINATOR>()
+            .ok_or(EscrowError::AccountDidNotSerialize)?;
+        *discriminator = Self::DISCRIMINATOR;
+        self.serialize(&mut fields)
+            .map_err(|_| EscrowError::AccountDidNotSerialize.into())
+    }
+}
+
+// Terms this build cannot honour decode as `Unsupported`, anything else that
+// fails to decode as `fallback`.
+pub fn decode_error(error: Error, fallback: EscrowError) -> ProgramError {
+    match error.kind() {
+        ErrorKind::Unsupported => EscrowError::UnsupportedByNativeBuild.into(),
+        _ => fallback.into(),
+    }
+}
+
+// `N` bytes of options switched off.  Borsh writes `None` and `false` as a
+// single zero byte and an empty `Vec` as a zero length, so anything else is
+// an option this build cannot honour.
+#[derive(Clone, Copy, Default)]
+pub struct NoOptions<const N: usize>;
+
+impl<const N: usize> BorshSerialize for NoOptions<N> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        writer.write_all(&[0; N])
+    }
+}
+
+impl<const N: usize> BorshDeserialize for NoOptions<N> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        let options = <[u8; N]>::deserialize_reader(reader)?;
+        if options != [0; N] {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "offer terms need the Anchor build",
+            ));
+        }
+        Ok(Self)
+    }
+}
+
+// `OfferTerms` with every option switched off, the only terms this build
+// handles.  The referral cap is kept as given: it does nothing without a
+// referrer, and this build takes no referrers.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct PlainTerms {
+    // `allowlist`, `payment_options`, `rent_receiver`,
+    // `min_taker_reputation`, `tranches` and `vesting`.
+    pub options_before_referral: NoOptions<9>,
+    pub max_referral_bps: u16,
+    // `pay_royalties`, `price_trigger` and `issue_receipts`.
+    pub options_after_referral: NoOptions<3>,
+}
+
+// The offer stores its terms flat, in the order they were added, so the
+// switched off options sit around `index_page` and `delegate`.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Offer {
+    pub id: u64,
+    pub maker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_b_wanted_amount: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub token_a_offered_amount: u64,
+    // `allowlist`, `payment_options`, `rent_receiver` and
+    // `min_taker_reputation`.
+    pub options_before_index: NoOptions<7>,
+    pub index_page: u32,
+    // `tranches` and `vesting`.
+    pub options_before_referral: NoOptions<2>,
+    pub max_referral_bps: u16,
+    // `pay_royalties` and `price_trigger`.
+    pub options_after_referral: NoOptions<2>,
+    pub delegate: Option<Pubkey>,
+    // `issue_receipts`.
+    pub options_after_delegate: NoOptions<1>,
+}
+
+impl AnchorAccount for Offer {
+    const DISCRIMINATOR: [u8; 8] = [215, 88, 60, 71, 170, 162, 73, 229];
+    // 122 bytes of fixed fields, 352 for the terms at their largest, then
+    // `index_page`, `delegate` and 106 reserved bytes.
+    const SPACE: usize = ANCHOR_DISCRIMINATOR + 122 + 352 + 4 + 33 + 106;
+}
+
+impl Offer {
+    // Amount of token B the taker pays for `token_a_amount`, rounded up so
+    // partial fills never undercut the maker's price.
+    pub fn token_b_amount_for(&self, token_a_amount: u64) -> Result<u64, ProgramError> {
+        let numerator = (token_a_amount as u128)
+            .checked_mul(self.token_b_wanted_amount as u128)
+            .ok_or(EscrowError::MathOverflow)?;
+        let denominator = self.token_a_offered_amount as u128;
+        require(denominator > 0, EscrowError::InvalidAmount)?;
+
+        let amount = numerator
+            .checked_add(denominator - 1)
+            .ok_or(EscrowError::MathOverflow)?
+            / denominator;
+        u64::try_from(amount).map_err(|_| EscrowError::MathOverflow.into())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MarketStats {
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub open_offers: u64,
+    pub volume_a: u128,
+    pub volume_b: u128,
+    pub last_price: u64,
+    pub last_trade_timestamp: i64,
+    pub bump: u8,
+}
+
+impl AnchorAccount for MarketStats {
+    const DISCRIMINATOR: [u8; 8] = [240, 45, 182, 233, 92, 118, 209, 83];
+    const SPACE: usize = ANCHOR_DISCRIMINATOR + 32 * 2 + 8 + 16 * 2 + 8 + 8 + 1;
+}
+
+impl MarketStats {
+    pub fn new(token_mint_a: Pubkey, token_mint_b: Pubkey, bump: u8) -> Self {
+        Self {
+            token_mint_a,
+            token_mint_b,
+            open_offers: 0,
+            volume_a: 0,
+            volume_b: 0,
+            last_price: 0,
+            last_trade_timestamp: 0,
+            bump,
+        }
+    }
+
+    pub fn record_open(&mut self) {
+        self.open_offers = self.open_offers.saturating_add(1);
+    }
+
+    pub fn record_close(&mut self) {
+        self.open_offers = self.open_offers.saturating_sub(1);
+    }
+
+    pub fn record_fill(&mut self, token_a_amount: u64, token_b_amount: u64, now: i64) {
+        self.volume_a = self.volume_a.saturating_add(token_a_amount as u128);
+        self.volume_b = self.volume_b.saturating_add(token_b_amount as u128);
+        let price = token_b_amount as u128 * PRICE_SCALE as u128 / token_a_amount as u128;
+        self.last_price = u64::try_from(price).unwrap_or(u64::MAX);
+        self.last_trade_timestamp = now;
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Reputation {
+    pub wallet: Pubkey,
+    pub takes: u64,
+    pub sales: u64,
+    pub cancellations: u64,
+    pub volume: u128,
+    pub first_seen: i64,
+    pub bump: u8,
+}
+
+impl AnchorAccount for Reputation {
+    const DISCRIMINATOR: [u8; 8] = [55, 148, 90, 71, 68, 183, 193, 28];
+    const SPACE: usize = ANCHOR_DISCRIMINATOR + 32 + 8 * 3 + 16 + 8 + 1;
+}
+
+impl Reputation {
+    pub fn new(wallet: Pubkey, bump: u8, now: i64) -> Self {
+        Self {
+            wallet,
+            takes: 0,
+            sales: 0,
+            cancellations: 0,
+            volume: 0,
+            first_seen: now,
+            bump,
+        }
+    }
+
+    pub fn record_take(&mut self, token_a_amount: u64, token_a_decimals: u8) {
+        if token_a_amount >= 10u64.saturating_pow(token_a_decimals as u32) {
+            self.takes = self.takes.saturating_add(1);
+        }
+        self.volume = self.volume.saturating_add(token_a_amount as u128);
+    }
+
+    pub fn record_sale(&mut self, token_a_amount: u64) {
+        self.sales = self.sales.saturating_add(1);
+        self.volume = self.volume.saturating_add(token_a_amount as u128);
+    }
+
+    pub fn record_cancellation(&mut self) {
+        self.cancellations = self.cancellations.saturating_add(1);
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MarketIndexPage {
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub page: u32,
+    pub next_page: Option<u32>,
+    pub offers: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl AnchorAccount for MarketIndexPage {
+    const DISCRIMINATOR: [u8; 8] = [140, 101, 50, 124, 128, 82, 161, 83];
+    const SPACE: usize = ANCHOR_DISCRIMINATOR + 32 * 2 + 4 + 5 + 4 + 32 * OFFERS_PER_INDEX_PAGE + 1;
+}
+
+impl MarketIndexPage {
+    pub fn new(token_mint_a: Pubkey, token_mint_b: Pubkey, page: u32, bump: u8) -> Self {
+        Self {
+            token_mint_a,
+            token_mint_b,
+            page,
+            next_page: None,
+            offers: Vec::new(),
+            bump,
+        }
+    }
+
+    pub fn is_page_of(&self, token_mint_a: &Pubkey, token_mint_b: &Pubkey, page: u32) -> bool {
+        self.token_mint_a == *token_mint_a
+            && self.token_mint_b == *token_mint_b
+            && self.page == page
+    }
+
+    pub fn insert(&mut self, offer: Pubkey) -> Result<(), ProgramError> {
+        require(
+            self.offers.len() < OFFERS_PER_INDEX_PAGE,
+            EscrowError::IndexPageFull,
+        )?;
+        self.offers.push(offer);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, offer: &Pubkey) {
+        if let Some(position) = self.offers.iter().position(|key| key == offer) {
+            self.offers.swap_remove(position);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use solana_system_interface::{
+    instruction::{allocate, assign, create_account, transfer},
+    program::ID as SYSTEM_PROGRAM_ID,
+};
+
+use crate::{
+    error::{require, EscrowError},
+    state::AnchorAccount,
+};
+
+pub fn check_system_program(system_program: &AccountInfo) -> ProgramResult {
+    require(
+        *system_program.key == SYSTEM_PROGRAM_ID,
+        EscrowError::InvalidProgramId,
+    )
+}
+
+// Creates a rent-exempt `T` at the PDA of `seeds`, at `payer`'s expense.
+pub struct PdaCreator<'a, 'info> {
+    pub program_id: &'a Pubkey,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> PdaCreator<'_, 'info> {
+    // Derives the bump, checks `account` is the PDA and creates it.
+    pub fn create<T: AnchorAccount>(
+        &self,
+        account: &AccountInfo<'info>,
+        seeds: &[&[u8]],
+    ) -> Result<u8, ProgramError> {
+        let (address, bump) = Pubkey::find_program_address(seeds, self.program_id);
+        require(*account.key == address, EscrowError::ConstraintSeeds)?;
+
+        let bump_seed = [bump];
+        let mut signer_seeds = seeds.to_vec();
+        signer_seeds.push(&bump_seed);
+        let signer_seeds = [signer_seeds.as_slice()];
+
+        let space = T::SPACE;
+        let rent = Rent::get()?.minimum_balance(space);
+        let system_program = self.system_program.clone();
+        if account.lamports() == 0 {
+            invoke_signed(
+                &create_account(
+                    self.payer.key,
+                    account.key,
+                    rent,
+                    space as u64,
+                    self.program_id,
+                ),
+                &[self.payer.clone(), account.clone(), system_program],
+                &signer_seeds,
+            )?;
+            return Ok(bump);
+        }
+
+        // `create_account` refuses addresses that already hold lamports, which
+        // anyone can send, so those are topped up and claimed instead.
+        let shortfall = rent.saturating_sub(account.lamports());
+        if shortfall > 0 {
+            invoke(
+                &transfer(self.payer.key, account.key, shortfall),
+                &[self.payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        invoke_signed(
+            &allocate(account.key, space as u64),
+            &[account.clone(), system_program.clone()],
+            &signer_seeds,
+        )?;
+        invoke_signed(
+            &assign(account.key, self.program_id),
+            &[account.clone(), system_program],
+            &signer_seeds,
+        )?;
+        Ok(bump)
+    }
+
+    // Anchor's `init_if_needed`: creates the account with `new(bump)` while
+    // the system program owns it, and loads it otherwise.  Existing accounts
+    // are only type-checked, so callers check the fields that tie them to
+    // `seeds`, which is cheaper than deriving the address again.
+    pub fn load_or_create<T: AnchorAccount>(
+        &self,
+        account: &AccountInfo<'info>,
+        seeds: &[&[u8]],
+        new: impl FnOnce(u8) -> Result<T, ProgramError>,
+    ) -> Result<T, ProgramError> {
+        if *account.owner != SYSTEM_PROGRAM_ID {
+            return T::load(account, self.program_id);
+        }
+        let bump = self.create::<T>(account, seeds)?;
+        new(bump)
+    }
+}
+
+// Anchor's `close`: hands the lamports to `destination` and gives the
+// account back to the system program.
+pub fn close_program_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let lamports = destination
+        .lamports()
+        .checked_add(account.lamports())
+        .ok_or(EscrowError::MathOverflow)?;
+    **destination.try_borrow_mut_lamports()? = lamports;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&SYSTEM_PROGRAM_ID);
+    account.realloc(0, false)
+}
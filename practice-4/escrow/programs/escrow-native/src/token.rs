@@ -0,0 +1,208 @@
+// Token and associated token account CPIs, built by hand.  Their instruction
+// layouts are stable, and pulling in `spl-token-2022` for three of them
+// would cost far more binary size than it saves code.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+};
+
+use crate::error::{require, EscrowError};
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+// Base layouts shared by both token programs.  Token-2022 appends its
+// extensions after an account type byte at `ACCOUNT_LEN`.
+const MINT_LEN: usize = 82;
+const ACCOUNT_LEN: usize = 165;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+const TRANSFER_CHECKED: u8 = 12;
+const CLOSE_ACCOUNT: u8 = 9;
+const CREATE_ASSOCIATED: u8 = 0;
+const CREATE_ASSOCIATED_IDEMPOTENT: u8 = 1;
+
+pub fn check_token_program(token_program: &AccountInfo) -> ProgramResult {
+    require(
+        *token_program.key == TOKEN_PROGRAM_ID || *token_program.key == TOKEN_2022_PROGRAM_ID,
+        EscrowError::InvalidProgramId,
+    )
+}
+
+fn has_account_type(data: &[u8], base_len: usize, account_type: u8) -> bool {
+    data.len() == base_len || data.get(ACCOUNT_LEN) == Some(&account_type)
+}
+
+// Decimals of an initialized mint of `token_program`.
+pub fn mint_decimals(mint: &AccountInfo, token_program: &AccountInfo) -> Result<u8, ProgramError> {
+    require(
+        mint.owner == token_program.key,
+        EscrowError::AccountOwnedByWrongProgram,
+    )?;
+    let data = mint.try_borrow_data()?;
+    require(
+        has_account_type(&data, MINT_LEN, ACCOUNT_TYPE_MINT) && data[45] == 1,
+        EscrowError::AccountDidNotDeserialize,
+    )?;
+    Ok(data[44])
+}
+
+pub struct TokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+impl TokenAccount {
+    pub fn load(account: &AccountInfo, token_program: &AccountInfo) -> Result<Self, ProgramError> {
+        require(
+            account.owner == token_program.key,
+            EscrowError::AccountOwnedByWrongProgram,
+        )?;
+        let data = account.try_borrow_data()?;
+        require(
+            has_account_type(&data, ACCOUNT_LEN, ACCOUNT_TYPE_ACCOUNT) && data[108] != 0,
+            EscrowError::AccountNotInitialized,
+        )?;
+        Ok(Self {
+            mint: Pubkey::new_from_array(data[0..32].try_into().unwrap()),
+            owner: Pubkey::new_from_array(data[32..64].try_into().unwrap()),
+            amount: u64::from_le_bytes(data[64..72].try_into().unwrap()),
+        })
+    }
+}
+
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+pub struct AssociatedAccount<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    pub account: &'a AccountInfo<'info>,
+    pub wallet: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+}
+
+impl AssociatedAccount<'_, '_> {
+    // Fails if the account exists already, as Anchor's `init` does.  The
+    // associated token program checks the address.
+    pub fn create(&self) -> ProgramResult {
+        self.invoke(CREATE_ASSOCIATED)
+    }
+
+    // Existing accounts only have to hold `mint` for `wallet`: being the
+    // wallet's ATA matters to no one but its owner.
+    pub fn create_if_needed(&self) -> ProgramResult {
+        if self.account.owner != self.token_program.key {
+            return self.invoke(CREATE_ASSOCIATED_IDEMPOTENT);
+        }
+        let account = TokenAccount::load(self.account, self.token_program)?;
+        require(
+            account.mint == *self.mint.key,
+            EscrowError::ConstraintTokenMint,
+        )?;
+        require(
+            account.owner == *self.wallet.key,
+            EscrowError::ConstraintTokenOwner,
+        )
+    }
+
+    fn invoke(&self, instruction: u8) -> ProgramResult {
+        let instruction = Instruction {
+            program_id: *self.associated_token_program.key,
+            accounts: vec![
+                AccountMeta::new(*self.payer.key, true),
+                AccountMeta::new(*self.account.key, false),
+                AccountMeta::new_readonly(*self.wallet.key, false),
+                AccountMeta::new_readonly(*self.mint.key, false),
+                AccountMeta::new_readonly(*self.system_program.key, false),
+                AccountMeta::new_readonly(*self.token_program.key, false),
+            ],
+            data: vec![instruction],
+        };
+        invoke_signed(
+            &instruction,
+            &[
+                self.payer.clone(),
+                self.account.clone(),
+                self.wallet.clone(),
+                self.mint.clone(),
+                self.system_program.clone(),
+                self.token_program.clone(),
+            ],
+            &[],
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut data = Vec::with_capacity(10);
+    data.push(TRANSFER_CHECKED);
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(decimals);
+
+    let instruction = Instruction {
+        program_id: *token_program.key,
+        accounts: vec![
+            AccountMeta::new(*from.key, false),
+            AccountMeta::new_readonly(*mint.key, false),
+            AccountMeta::new(*to.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+        data,
+    };
+    invoke_signed(
+        &instruction,
+        &[from.clone(), mint.clone(), to.clone(), authority.clone()],
+        signer_seeds,
+    )
+}
+
+pub fn close_account<'info>(
+    token_program: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = Instruction {
+        program_id: *token_program.key,
+        accounts: vec![
+            AccountMeta::new(*account.key, false),
+            AccountMeta::new(*destination.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+        data: vec![CLOSE_ACCOUNT],
+    };
+    invoke_signed(
+        &instruction,
+        &[account.clone(), destination.clone(), authority.clone()],
+        signer_seeds,
+    )
+}
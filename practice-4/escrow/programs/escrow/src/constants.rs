@@ -3,3 +3,36 @@ use anchor_lang::prelude::*;
 #[constant]
 pub const SEED: &str = "anchor";
 pub const ANCHOR_DISCRIMINATOR: usize = 8;
+
+// Token B mints an offer accepts besides `token_mint_b`.
+pub const MAX_PAYMENT_OPTIONS: usize = 4;
+
+// Token mints on each side of a basket offer.
+pub const MAX_BASKET_LEGS: usize = 5;
+
+// Price levels a single `make_offer_ladder` call can create.
+pub const MAX_LADDER_LEVELS: usize = 8;
+
+// Layout version written to every `Offer` created or migrated by this build.
+// Version 2 grew the account from 64 to 256 bytes of optional fields.
+pub const OFFER_VERSION: u8 = 2;
+
+// Zeroed bytes at the end of every `Offer`, taken up by future fields.  Each
+// new field shrinks this by its own size so the account size stays put.
+pub const OFFER_RESERVED_SPACE: usize = 256 - 17 - 4 - 25 - 17 - 2 - 1 - 50 - 33 - 1;
+
+// Denominator of every basis point share, such as referral fees.
+pub const MAX_BPS: u16 = 10_000;
+
+// Offers listed on each `MarketIndexPage`.
+pub const OFFERS_PER_INDEX_PAGE: usize = 32;
+
+// Resting orders on each side of an `OrderBook`.
+pub const MAX_BOOK_SIDE_ORDERS: usize = 32;
+
+// Members of a `SignerSet`.
+pub const MAX_SIGNER_SET_MEMBERS: usize = 10;
+
+// Fixed-point scale of prices reported in `MarketStats` and of order book
+// prices.
+pub const PRICE_SCALE: u64 = 1_000_000_000;
@@ -2,6 +2,118 @@ use anchor_lang::prelude::*;
 
 #[error_code]
 pub enum ErrorCode {
-    #[msg("Custom error message")]
-    CustomError,
+    #[msg("Amount must be greater than zero and within the offer")]
+    InvalidAmount,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Taker is not on the offer allowlist")]
+    NotAllowlisted,
+    #[msg("Merkle proof is too long")]
+    ProofTooLong,
+    #[msg("Offer has a per-taker cap, taker fill account is required")]
+    TakerFillRequired,
+    #[msg("Fill would exceed the per-taker cap")]
+    TakerCapExceeded,
+    #[msg("Too many payment options")]
+    TooManyPaymentOptions,
+    #[msg("Payment options must use distinct mints other than token A")]
+    DuplicatePaymentMint,
+    #[msg("Offer does not accept this token B mint")]
+    PaymentMintNotAccepted,
+    #[msg("Basket needs between one and MAX_BASKET_LEGS legs on each side")]
+    InvalidBasketLegCount,
+    #[msg("Basket legs must use distinct mints")]
+    DuplicateBasketMint,
+    #[msg("Remaining accounts do not match the basket legs")]
+    BasketAccountMismatch,
+    #[msg("Rent receiver does not match the offer")]
+    InvalidRentReceiver,
+    #[msg("Vault is not the offer's associated token account")]
+    InvalidVault,
+    #[msg("Account is not an offer in the legacy layout or an older version")]
+    NotLegacyOffer,
+    #[msg("Taker does not meet the offer's reputation requirement")]
+    InsufficientReputation,
+    #[msg("Market index page is full")]
+    IndexPageFull,
+    #[msg("Market index pages must be created in order")]
+    InvalidIndexPage,
+    #[msg("Remaining accounts do not match the offers being closed")]
+    CloseAccountMismatch,
+    #[msg("Ladder needs between one and MAX_LADDER_LEVELS levels")]
+    InvalidLadderLevelCount,
+    #[msg("Ladder levels must add up to the total amount")]
+    LadderAmountMismatch,
+    #[msg("Remaining accounts do not match the ladder levels")]
+    LadderAccountMismatch,
+    #[msg("Order book needs two different mints")]
+    InvalidOrderBookMints,
+    #[msg("Order book side is full")]
+    BookSideFull,
+    #[msg("Order not found")]
+    OrderNotFound,
+    #[msg("User balance accounts do not match the orders being matched")]
+    UserBalanceMismatch,
+    #[msg("Tranche schedule needs a positive interval and amount")]
+    InvalidTrancheSchedule,
+    #[msg("Amount exceeds what the offer has unlocked so far")]
+    TrancheLocked,
+    #[msg("Vesting needs a positive duration and a cliff within it")]
+    InvalidVestingSchedule,
+    #[msg("Vesting accounts must be passed exactly when the offer vests")]
+    VestingAccountsMismatch,
+    #[msg("Nothing has vested since the last claim")]
+    NothingToClaim,
+    #[msg("Referral share cannot exceed MAX_BPS")]
+    InvalidReferralFee,
+    #[msg("Referrer and its token account must be passed together")]
+    ReferralAccountsMismatch,
+    #[msg("Token metadata account is missing or invalid")]
+    InvalidMetadata,
+    #[msg("Remaining accounts must be the verified creators' token B accounts")]
+    CreatorAccountMismatch,
+    #[msg("Price trigger needs a positive staleness limit")]
+    InvalidPriceTrigger,
+    #[msg("Price feed account is missing or invalid")]
+    InvalidPriceFeed,
+    #[msg("Price feed has not been updated recently enough")]
+    StalePrice,
+    #[msg("Offer price trigger has not been reached")]
+    TriggerNotMet,
+    #[msg("Auction needs two different mints")]
+    InvalidAuctionMints,
+    #[msg("Auction needs positive commit and reveal phases")]
+    InvalidAuctionSchedule,
+    #[msg("Not allowed in the auction's current phase")]
+    WrongAuctionPhase,
+    #[msg("Bid has already been revealed")]
+    BidAlreadyRevealed,
+    #[msg("Amount and salt do not match the bid's commitment")]
+    BidCommitmentMismatch,
+    #[msg("Revealed bid is larger than its deposit")]
+    BidExceedsDeposit,
+    #[msg("Lot receiver must be the highest bidder, or the seller without one")]
+    InvalidLotReceiver,
+    #[msg("Signer set needs unique members and a threshold between one and their number")]
+    InvalidSignerSet,
+    #[msg("Signer is not a member of the signer set")]
+    NotSignerSetMember,
+    #[msg("Member has already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal does not have enough approvals yet")]
+    ThresholdNotMet,
+    #[msg("Remaining accounts do not match the proposed action")]
+    ProposalAccountMismatch,
+    #[msg("Signer is not a delegate of the offer or its maker")]
+    NotOfferDelegate,
+    #[msg("Receipt offers need both receipt mints, their token accounts and Token-2022")]
+    ReceiptAccountsMismatch,
+    #[msg("Offer is in an older layout, run migrate_offer on it first")]
+    OutdatedOffer,
+    #[msg("Delegates can only raise the amount an offer wants")]
+    DelegateCannotLowerPrice,
+    #[msg("Takers cannot be their own referrer")]
+    SelfReferral,
+    #[msg("Ladder offer ids must come after those the maker counter handed out")]
+    InvalidLadderOfferId,
 }
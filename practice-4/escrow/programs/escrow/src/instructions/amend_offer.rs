@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Offer, OFFER_VERSION};
+
+#[derive(Accounts)]
+pub struct AmendOffer<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        constraint = offer.version == OFFER_VERSION @ ErrorCode::OutdatedOffer,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+pub fn update_wanted_amount(ctx: Context<AmendOffer>, token_b_wanted_amount: u64) -> Result<()> {
+    ctx.accounts.offer.reprice(token_b_wanted_amount)
+}
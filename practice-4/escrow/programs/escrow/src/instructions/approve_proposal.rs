@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Proposal, SignerSet};
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        constraint = signer_set.is_member(&member.key()) @ ErrorCode::NotSignerSetMember,
+        seeds = [b"signer_set", signer_set.creator.as_ref(), signer_set.id.to_le_bytes().as_ref()],
+        bump = signer_set.bump
+    )]
+    pub signer_set: Box<Account<'info, SignerSet>>,
+
+    #[account(
+        mut,
+        has_one = signer_set,
+        seeds = [b"proposal", signer_set.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+}
+
+pub fn record_approval(ctx: Context<ApproveProposal>) -> Result<()> {
+    let member = ctx.accounts.member.key();
+    ctx.accounts.proposal.approve(member)
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::error::ErrorCode;
+
+// Both parties sign the same transaction, so tokens move directly between
+// their accounts: no `Offer`, no vault and no rent to pay or reclaim.
+#[derive(Accounts)]
+pub struct AtomicSwap<'info> {
+    pub maker: Signer<'info>,
+
+    pub taker: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint_b,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub maker_token_account_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+        token::authority = taker,
+        token::token_program = token_program,
+    )]
+    pub taker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint_b,
+        token::authority = taker,
+        token::token_program = token_program,
+    )]
+    pub taker_token_account_b: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn swap_tokens(
+    context: Context<AtomicSwap>,
+    token_a_amount: u64,
+    token_b_amount: u64,
+) -> Result<()> {
+    require!(
+        token_a_amount > 0 && token_b_amount > 0,
+        ErrorCode::InvalidAmount
+    );
+
+    let accounts = &context.accounts;
+
+    let transfer_accounts = TransferChecked {
+        from: accounts.maker_token_account_a.to_account_info(),
+        mint: accounts.token_mint_a.to_account_info(),
+        to: accounts.taker_token_account_a.to_account_info(),
+        authority: accounts.maker.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(accounts.token_program.to_account_info(), transfer_accounts);
+    transfer_checked(cpi_context, token_a_amount, accounts.token_mint_a.decimals)?;
+
+    let transfer_accounts = TransferChecked {
+        from: accounts.taker_token_account_b.to_account_info(),
+        mint: accounts.token_mint_b.to_account_info(),
+        to: accounts.maker_token_account_b.to_account_info(),
+        authority: accounts.taker.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(accounts.token_program.to_account_info(), transfer_accounts);
+    transfer_checked(cpi_context, token_b_amount, accounts.token_mint_b.decimals)
+}
@@ -0,0 +1,76 @@
+// Token moves out of an auction's vaults, shared by the settlement
+// instructions.  The auction PDA signs for its vaults.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TransferChecked,
+};
+
+use crate::Auction;
+
+pub fn transfer_from_vault<'info>(
+    auction: &Account<'info, Auction>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let id = auction.id.to_le_bytes();
+    let signer_seeds: [&[&[u8]]; 1] =
+        [&[b"auction", auction.seller.as_ref(), &id, &[auction.bump]]];
+
+    let accounts = TransferChecked {
+        from: vault.to_account_info(),
+        mint: mint.to_account_info(),
+        to,
+        authority: auction.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(token_program, accounts, &signer_seeds);
+    transfer_checked(cpi_context, amount, mint.decimals)
+}
+
+pub fn close_vault<'info>(
+    auction: &Account<'info, Auction>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    destination: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let id = auction.id.to_le_bytes();
+    let signer_seeds: [&[&[u8]]; 1] =
+        [&[b"auction", auction.seller.as_ref(), &id, &[auction.bump]]];
+
+    let accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination,
+        authority: auction.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(token_program, accounts, &signer_seeds);
+    close_account(cpi_context)
+}
+
+// Called once every bid is settled.  Anything still in the token B vault
+// was sent to it directly, outside any bid, and goes to the seller along with
+// the rent of the vault and the auction.
+pub fn close_token_b_vault_and_auction<'info>(
+    auction: &mut Account<'info, Auction>,
+    vault_b: &mut InterfaceAccount<'info, TokenAccount>,
+    token_mint_b: &InterfaceAccount<'info, Mint>,
+    seller: AccountInfo<'info>,
+    seller_token_account_b: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    vault_b.reload()?;
+    if vault_b.amount > 0 {
+        transfer_from_vault(
+            auction,
+            vault_b,
+            token_mint_b,
+            seller_token_account_b,
+            token_program.clone(),
+            vault_b.amount,
+        )?;
+    }
+    close_vault(auction, vault_b, seller.clone(), token_program)?;
+    auction.close(seller)
+}
@@ -0,0 +1,345 @@
+// Account checks and token moves shared by the basket offer instructions.
+//
+// Basket legs are passed through `remaining_accounts`, so none of Anchor's
+// account constraints apply to them.  Every account is matched here against
+// what the `BasketOffer` recorded before it is used.
+//
+// The flows at the end serve both `BasketOffer` and its zero-copy twin
+// `FixedBasketOffer`, which only differ in how they store the legs.
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, Create},
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, BasketLeg, Leg, MAX_BASKET_LEGS};
+
+pub const ACCOUNTS_PER_LEG: usize = 3;
+
+pub fn check_legs(offered: &[BasketLeg], wanted: &[BasketLeg]) -> Result<()> {
+    for legs in [offered, wanted] {
+        require!(
+            !legs.is_empty() && legs.len() <= MAX_BASKET_LEGS,
+            ErrorCode::InvalidBasketLegCount
+        );
+    }
+
+    let legs: Vec<&BasketLeg> = offered.iter().chain(wanted).collect();
+    for (index, leg) in legs.iter().enumerate() {
+        require!(leg.amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            legs[..index].iter().all(|other| other.mint != leg.mint),
+            ErrorCode::DuplicateBasketMint
+        );
+    }
+    Ok(())
+}
+
+// Splits `remaining_accounts` into one `[mint, a, b]` triple per leg.
+pub fn leg_accounts<'info>(
+    remaining_accounts: &'info [AccountInfo<'info>],
+    legs: usize,
+) -> Result<std::slice::Chunks<'info, AccountInfo<'info>>> {
+    require!(
+        remaining_accounts.len() == legs * ACCOUNTS_PER_LEG,
+        ErrorCode::BasketAccountMismatch
+    );
+    Ok(remaining_accounts.chunks(ACCOUNTS_PER_LEG))
+}
+
+pub fn load_mint<'info>(
+    info: &'info AccountInfo<'info>,
+    leg: &impl Leg,
+    token_program: &Pubkey,
+) -> Result<InterfaceAccount<'info, Mint>> {
+    require_keys_eq!(info.key(), leg.mint(), ErrorCode::BasketAccountMismatch);
+    require_keys_eq!(
+        *info.owner,
+        *token_program,
+        ErrorCode::BasketAccountMismatch
+    );
+    InterfaceAccount::try_from(info)
+}
+
+pub fn require_ata(
+    info: &AccountInfo,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(
+        info.key(),
+        get_associated_token_address_with_program_id(wallet, mint, token_program),
+        ErrorCode::BasketAccountMismatch
+    );
+    Ok(())
+}
+
+// Loads `wallet`'s ATA for `mint`, such as a vault of the basket offer,
+// which must already exist.
+pub fn load_ata<'info>(
+    info: &'info AccountInfo<'info>,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<InterfaceAccount<'info, TokenAccount>> {
+    require_ata(info, wallet, mint, token_program)?;
+    let ata = InterfaceAccount::<TokenAccount>::try_from(info)?;
+    require_keys_eq!(ata.mint, *mint, ErrorCode::BasketAccountMismatch);
+    require_keys_eq!(ata.owner, *wallet, ErrorCode::BasketAccountMismatch);
+    Ok(ata)
+}
+
+pub struct AtaCreator<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> AtaCreator<'_, 'info> {
+    // Creates `wallet`'s ATA for `mint` unless it already exists.
+    pub fn create(
+        &self,
+        ata: &AccountInfo<'info>,
+        wallet: &AccountInfo<'info>,
+        mint: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let accounts = Create {
+            payer: self.payer.clone(),
+            associated_token: ata.clone(),
+            authority: wallet.clone(),
+            mint: mint.clone(),
+            system_program: self.system_program.clone(),
+            token_program: self.token_program.clone(),
+        };
+        create_idempotent(CpiContext::new(
+            self.associated_token_program.clone(),
+            accounts,
+        ))
+    }
+}
+
+pub struct LegTransfers<'a, 'info> {
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> LegTransfers<'_, 'info> {
+    pub fn transfer(
+        &self,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        mint: &InterfaceAccount<'info, Mint>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let accounts = TransferChecked {
+            from: from.clone(),
+            mint: mint.to_account_info(),
+            to: to.clone(),
+            authority: authority.clone(),
+        };
+        let cpi_context =
+            CpiContext::new_with_signer(self.token_program.clone(), accounts, signer_seeds);
+        transfer_checked(cpi_context, amount, mint.decimals)
+    }
+
+    pub fn close_vault(
+        &self,
+        vault: &AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let accounts = CloseAccount {
+            account: vault.clone(),
+            destination: destination.clone(),
+            authority: authority.clone(),
+        };
+        close_account(CpiContext::new_with_signer(
+            self.token_program.clone(),
+            accounts,
+            signer_seeds,
+        ))
+    }
+}
+
+// The basket offer, which owns the vaults, and the seeds it signs with.
+pub struct VaultOwner<'a, 'info> {
+    pub info: AccountInfo<'info>,
+    pub signer_seeds: &'a [&'a [&'a [u8]]],
+}
+
+// Moves every offered leg from the maker into a vault owned by the basket
+// offer.  `remaining_accounts` holds one triple per leg:
+//
+//   [token mint, maker ATA for the mint, basket offer ATA (vault) for the mint]
+pub fn fund_vaults<'info>(
+    atas: &AtaCreator<'_, 'info>,
+    maker: &AccountInfo<'info>,
+    basket_offer: &AccountInfo<'info>,
+    offered: &[BasketLeg],
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let token_program = atas.token_program.key();
+    let transfers = LegTransfers {
+        token_program: atas.token_program,
+    };
+
+    let accounts = leg_accounts(remaining_accounts, offered.len())?;
+    for (leg, accounts) in offered.iter().zip(accounts) {
+        let [mint, maker_token_account, vault] = accounts else {
+            unreachable!();
+        };
+
+        let mint = load_mint(mint, leg, &token_program)?;
+        require_ata(maker_token_account, &maker.key(), &leg.mint, &token_program)?;
+        require_ata(vault, &basket_offer.key(), &leg.mint, &token_program)?;
+
+        atas.create(vault, basket_offer, &mint.to_account_info())?;
+        transfers.transfer(maker_token_account, vault, maker, &mint, leg.amount, &[])?;
+    }
+    Ok(())
+}
+
+// Pays the maker every wanted leg, then empties every vault into the taker's
+// ATAs and closes it.  The maker's ATAs for the wanted mints must exist
+// already, as takers are not made to pay for them.  `remaining_accounts`
+// holds one triple for every offered leg, followed by one triple for every
+// wanted leg:
+//
+//   offered: [token mint, vault, taker ATA for the mint]
+//   wanted:  [token mint, taker ATA for the mint, maker ATA for the mint]
+pub fn swap_legs<'info>(
+    atas: &AtaCreator<'_, 'info>,
+    taker: &AccountInfo<'info>,
+    maker: &AccountInfo<'info>,
+    vault_owner: &VaultOwner<'_, 'info>,
+    offered: &[impl Leg],
+    wanted: &[impl Leg],
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    require!(
+        remaining_accounts.len() == (offered.len() + wanted.len()) * ACCOUNTS_PER_LEG,
+        ErrorCode::BasketAccountMismatch
+    );
+    let (offered_accounts, wanted_accounts) =
+        remaining_accounts.split_at(offered.len() * ACCOUNTS_PER_LEG);
+
+    let token_program = atas.token_program.key();
+    let transfers = LegTransfers {
+        token_program: atas.token_program,
+    };
+
+    // Pay the maker first, so a taker short of any wanted leg fails the
+    // whole swap before the vaults are touched.
+    for (leg, accounts) in wanted
+        .iter()
+        .zip(leg_accounts(wanted_accounts, wanted.len())?)
+    {
+        let [mint, taker_token_account, maker_token_account] = accounts else {
+            unreachable!();
+        };
+
+        let mint = load_mint(mint, leg, &token_program)?;
+        require_ata(
+            taker_token_account,
+            &taker.key(),
+            &leg.mint(),
+            &token_program,
+        )?;
+        load_ata(
+            maker_token_account,
+            &maker.key(),
+            &leg.mint(),
+            &token_program,
+        )?;
+
+        transfers.transfer(
+            taker_token_account,
+            maker_token_account,
+            taker,
+            &mint,
+            leg.amount(),
+            &[],
+        )?;
+    }
+
+    for (leg, accounts) in offered
+        .iter()
+        .zip(leg_accounts(offered_accounts, offered.len())?)
+    {
+        let [mint, vault, taker_token_account] = accounts else {
+            unreachable!();
+        };
+
+        let mint = load_mint(mint, leg, &token_program)?;
+        let vault_balance =
+            load_ata(vault, &vault_owner.info.key(), &leg.mint(), &token_program)?.amount;
+        require_ata(
+            taker_token_account,
+            &taker.key(),
+            &leg.mint(),
+            &token_program,
+        )?;
+
+        atas.create(taker_token_account, taker, &mint.to_account_info())?;
+        transfers.transfer(
+            vault,
+            taker_token_account,
+            &vault_owner.info,
+            &mint,
+            vault_balance,
+            vault_owner.signer_seeds,
+        )?;
+        // The maker funded the vault, so its rent goes back to the maker.
+        transfers.close_vault(vault, maker, &vault_owner.info, vault_owner.signer_seeds)?;
+    }
+    Ok(())
+}
+
+// Returns every offered leg to the maker and closes its vault.
+// `remaining_accounts` holds one triple per leg:
+//
+//   [token mint, vault, maker ATA for the mint]
+pub fn empty_vaults<'info>(
+    transfers: &LegTransfers<'_, 'info>,
+    maker: &AccountInfo<'info>,
+    vault_owner: &VaultOwner<'_, 'info>,
+    offered: &[impl Leg],
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let token_program = transfers.token_program.key();
+
+    let accounts = leg_accounts(remaining_accounts, offered.len())?;
+    for (leg, accounts) in offered.iter().zip(accounts) {
+        let [mint, vault, maker_token_account] = accounts else {
+            unreachable!();
+        };
+
+        let mint = load_mint(mint, leg, &token_program)?;
+        let vault_balance =
+            load_ata(vault, &vault_owner.info.key(), &leg.mint(), &token_program)?.amount;
+        require_ata(
+            maker_token_account,
+            &maker.key(),
+            &leg.mint(),
+            &token_program,
+        )?;
+
+        transfers.transfer(
+            vault,
+            maker_token_account,
+            &vault_owner.info,
+            &mint,
+            vault_balance,
+            vault_owner.signer_seeds,
+        )?;
+        transfers.close_vault(vault, maker, &vault_owner.info, vault_owner.signer_seeds)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{BookSide, OrderBook, Side, UserBalance};
+
+// Takes a resting order off the book.  What it still had deposited is
+// credited to the owner's balance, to be withdrawn with `withdraw_balance`.
+#[derive(Accounts)]
+#[instruction(side: Side)]
+pub struct CancelOrder<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [
+            b"order_book",
+            order_book.token_mint_a.as_ref(),
+            order_book.token_mint_b.as_ref(),
+        ],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        mut,
+        seeds = [side.seed(), order_book.key().as_ref()],
+        bump = book_side.bump
+    )]
+    pub book_side: Box<Account<'info, BookSide>>,
+
+    #[account(
+        mut,
+        seeds = [b"balance", order_book.key().as_ref(), owner.key().as_ref()],
+        bump = user_balance.bump
+    )]
+    pub user_balance: Box<Account<'info, UserBalance>>,
+}
+
+pub fn remove_order_and_refund(
+    context: Context<CancelOrder>,
+    side: Side,
+    order_id: u64,
+) -> Result<()> {
+    let order = context
+        .accounts
+        .book_side
+        .remove(order_id, &context.accounts.owner.key())?;
+
+    context.accounts.user_balance.refund(side, &order)
+}
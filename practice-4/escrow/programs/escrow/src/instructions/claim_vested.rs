@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, Vesting};
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = taker,
+        has_one = token_mint_a,
+        seeds = [b"vesting", vesting.offer.as_ref(), taker.key().as_ref()],
+        bump = vesting.bump
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn release_vested_tokens(ctx: Context<ClaimVested>) -> Result<()> {
+    let vesting = &ctx.accounts.vesting;
+    let claimable = vesting
+        .vested_amount(Clock::get()?.unix_timestamp)
+        .saturating_sub(vesting.claimed_amount);
+    require!(claimable > 0, ErrorCode::NothingToClaim);
+
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"vesting",
+        vesting.offer.as_ref(),
+        vesting.taker.as_ref(),
+        &[vesting.bump],
+    ]];
+
+    // The last claim empties the vault, including anything sent to it on top,
+    // so that it can be closed.
+    let claimed_amount = vesting.claimed_amount + claimable;
+    let fully_claimed = claimed_amount == vesting.total_amount;
+    let amount = if fully_claimed {
+        ctx.accounts.vesting_vault.amount
+    } else {
+        claimable
+    };
+
+    let accounts = TransferChecked {
+        from: ctx.accounts.vesting_vault.to_account_info(),
+        mint: ctx.accounts.token_mint_a.to_account_info(),
+        to: ctx.accounts.taker_token_account_a.to_account_info(),
+        authority: vesting.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        accounts,
+        &signer_seeds,
+    );
+    transfer_checked(cpi_context, amount, ctx.accounts.token_mint_a.decimals)?;
+
+    if !fully_claimed {
+        ctx.accounts.vesting.claimed_amount = claimed_amount;
+        return Ok(());
+    }
+
+    // Fully claimed: the taker paid for both accounts when taking.
+    let accounts = CloseAccount {
+        account: ctx.accounts.vesting_vault.to_account_info(),
+        destination: ctx.accounts.taker.to_account_info(),
+        authority: vesting.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        accounts,
+        &signer_seeds,
+    );
+    close_account(cpi_context)?;
+
+    ctx.accounts
+        .vesting
+        .close(ctx.accounts.taker.to_account_info())
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::TokenInterface;
+
+use super::basket::{self, LegTransfers, VaultOwner};
+use crate::BasketOffer;
+
+// One triple for every offered leg follows in `remaining_accounts`:
+//
+//   [token mint, vault, maker ATA for the mint]
+#[derive(Accounts)]
+pub struct CloseBasketOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket_offer.id.to_le_bytes().as_ref()],
+        bump = basket_offer.bump
+    )]
+    pub basket_offer: Account<'info, BasketOffer>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn return_tokens_and_close_vaults<'info>(
+    context: Context<'_, '_, 'info, 'info, CloseBasketOffer<'info>>,
+) -> Result<()> {
+    let basket_offer = &context.accounts.basket_offer;
+    let maker = context.accounts.maker.to_account_info();
+    let transfers = LegTransfers {
+        token_program: &context.accounts.token_program.to_account_info(),
+    };
+
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"basket",
+        maker.key.as_ref(),
+        &basket_offer.id.to_le_bytes()[..],
+        &[basket_offer.bump],
+    ]];
+    let vault_owner = VaultOwner {
+        info: basket_offer.to_account_info(),
+        signer_seeds: &signer_seeds,
+    };
+    basket::empty_vaults(
+        &transfers,
+        &maker,
+        &vault_owner,
+        &basket_offer.offered,
+        context.remaining_accounts,
+    )
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::TokenInterface;
+
+use super::basket::{self, LegTransfers, VaultOwner};
+use crate::FixedBasketOffer;
+
+// Same accounts as `close_basket_offer`, with one triple per offered leg in
+// `remaining_accounts`:
+//
+//   [token mint, vault, maker ATA for the mint]
+#[derive(Accounts)]
+pub struct CloseFixedBasketOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [
+            b"fixed_basket",
+            maker.key().as_ref(),
+            basket_offer.load()?.id.to_le_bytes().as_ref()
+        ],
+        bump = basket_offer.load()?.bump
+    )]
+    pub basket_offer: AccountLoader<'info, FixedBasketOffer>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn return_tokens_and_close_fixed_vaults<'info>(
+    context: Context<'_, '_, 'info, 'info, CloseFixedBasketOffer<'info>>,
+) -> Result<()> {
+    let basket_offer = context.accounts.basket_offer.load()?;
+
+    let maker = context.accounts.maker.to_account_info();
+    let transfers = LegTransfers {
+        token_program: &context.accounts.token_program.to_account_info(),
+    };
+
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"fixed_basket",
+        maker.key.as_ref(),
+        &basket_offer.id.to_le_bytes()[..],
+        &[basket_offer.bump],
+    ]];
+    let vault_owner = VaultOwner {
+        info: context.accounts.basket_offer.to_account_info(),
+        signer_seeds: &signer_seeds,
+    };
+    basket::empty_vaults(
+        &transfers,
+        &maker,
+        &vault_owner,
+        basket_offer.offered_legs(),
+        context.remaining_accounts,
+    )
+}
@@ -4,7 +4,7 @@ use anchor_spl::token_interface::{
     TransferChecked,
 };
 
-use crate::Offer;
+use crate::{error::ErrorCode, MarketIndexPage, MarketStats, Offer, Reputation, OFFER_VERSION};
 
 #[derive(Accounts)]
 pub struct CloseOffer<'info> {
@@ -13,10 +13,10 @@ pub struct CloseOffer<'info> {
 
     #[account(
         mut,
-        close = maker,
         has_one = maker,
         has_one = token_mint_a,
         has_one = token_mint_b,
+        constraint = offer.version == OFFER_VERSION @ ErrorCode::OutdatedOffer,
         seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
         bump = offer.bump
     )]
@@ -24,14 +24,20 @@ pub struct CloseOffer<'info> {
 
     #[account(
         mut,
+        address = Offer::vault_address(&offer.key(), &token_mint_a.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
         associated_token::mint = token_mint_a,
         associated_token::authority = offer,
         associated_token::token_program = token_program,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(mint::token_program = token_program)]
     pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program = token_program)]
     pub token_mint_b: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
         associated_token::mint = token_mint_a,
@@ -40,51 +46,119 @@ pub struct CloseOffer<'info> {
     )]
     pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
 
+    /// CHECK: Only receives lamports, checked against the offer when closing.
+    #[account(mut)]
+    pub rent_receiver: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"market_stats", offer.token_mint_a.as_ref(), offer.token_mint_b.as_ref()],
+        bump = market_stats.bump
+    )]
+    pub market_stats: Box<Account<'info, MarketStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", maker.key().as_ref()],
+        bump = maker_reputation.bump
+    )]
+    pub maker_reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"market_index",
+            offer.token_mint_a.as_ref(),
+            offer.token_mint_b.as_ref(),
+            offer.index_page.to_le_bytes().as_ref()
+        ],
+        bump = market_index_page.bump
+    )]
+    pub market_index_page: Box<Account<'info, MarketIndexPage>>,
+
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn return_tokens_and_close_accounts(ctx: Context<CloseOffer>) -> Result<()> {
+    let accounts = ctx.accounts;
+    return_tokens_and_close(OfferToClose {
+        maker: accounts.maker.to_account_info(),
+        offer: &accounts.offer,
+        vault: &accounts.vault,
+        token_mint_a: &accounts.token_mint_a,
+        maker_token_account_a: &accounts.maker_token_account_a,
+        rent_receiver: accounts
+            .rent_receiver
+            .as_ref()
+            .map(|rent_receiver| rent_receiver.to_account_info()),
+        market_stats: &mut accounts.market_stats,
+        maker_reputation: &mut accounts.maker_reputation,
+        market_index_page: &mut accounts.market_index_page,
+        token_program: accounts.token_program.to_account_info(),
+    })
+}
+
+// Accounts closing an offer touches, checked by `CloseOffer` or
+// `DelegateCloseOffer` depending on who signs.
+pub(crate) struct OfferToClose<'a, 'info> {
+    pub maker: AccountInfo<'info>,
+    pub offer: &'a Account<'info, Offer>,
+    pub vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_mint_a: &'a InterfaceAccount<'info, Mint>,
+    pub maker_token_account_a: &'a InterfaceAccount<'info, TokenAccount>,
+    pub rent_receiver: Option<AccountInfo<'info>>,
+    pub market_stats: &'a mut MarketStats,
+    pub maker_reputation: &'a mut Reputation,
+    pub market_index_page: &'a mut MarketIndexPage,
+    pub token_program: AccountInfo<'info>,
+}
+
+pub(crate) fn return_tokens_and_close(accounts: OfferToClose) -> Result<()> {
     let signer_seeds: [&[&[u8]]; 1] = [&[
         b"offer",
-        ctx.accounts.maker.to_account_info().key.as_ref(),
-        &ctx.accounts.offer.id.to_le_bytes()[..],
-        &[ctx.accounts.offer.bump],
+        accounts.maker.key.as_ref(),
+        &accounts.offer.id.to_le_bytes()[..],
+        &[accounts.offer.bump],
     ]];
 
     // Return offered tokens from vault to maker
     let transfer_accounts = TransferChecked {
-        from: ctx.accounts.vault.to_account_info(),
-        mint: ctx.accounts.token_mint_a.to_account_info(),
-        to: ctx.accounts.maker_token_account_a.to_account_info(),
-        authority: ctx.accounts.offer.to_account_info(),
+        from: accounts.vault.to_account_info(),
+        mint: accounts.token_mint_a.to_account_info(),
+        to: accounts.maker_token_account_a.to_account_info(),
+        authority: accounts.offer.to_account_info(),
     };
 
     let cpi_context = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
+        accounts.token_program.clone(),
         transfer_accounts,
         &signer_seeds,
     );
 
     transfer_checked(
         cpi_context,
-        ctx.accounts.vault.amount,
-        ctx.accounts.token_mint_a.decimals,
+        accounts.vault.amount,
+        accounts.token_mint_a.decimals,
     )?;
 
+    let rent_destination = accounts
+        .offer
+        .rent_destination(accounts.maker.clone(), accounts.rent_receiver)?;
+
     // Close the vault account
     let close_vault_accounts = CloseAccount {
-        account: ctx.accounts.vault.to_account_info(),
-        destination: ctx.accounts.maker.to_account_info(),
-        authority: ctx.accounts.offer.to_account_info(),
+        account: accounts.vault.to_account_info(),
+        destination: rent_destination.clone(),
+        authority: accounts.offer.to_account_info(),
     };
 
-    let cpi_context = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        close_vault_accounts,
-        &signer_seeds,
-    );
+    let cpi_context =
+        CpiContext::new_with_signer(accounts.token_program, close_vault_accounts, &signer_seeds);
     close_account(cpi_context)?;
 
-    Ok(())
+    accounts.market_stats.record_close();
+    accounts.maker_reputation.record_cancellation();
+    accounts.market_index_page.remove(&accounts.offer.key());
+    accounts.offer.close(rent_destination)
 }
@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::basket::LegTransfers;
+use crate::{error::ErrorCode, MarketIndexPage, MarketStats, Offer, Reputation, OFFER_VERSION};
+
+pub const ACCOUNTS_PER_CLOSED_OFFER: usize = 6;
+
+// One group for every offer to close follows in `remaining_accounts`:
+//
+//   [offer, vault, token mint A, maker ATA for mint A, market stats,
+//    market index page]
+//
+// Each group gets the checks `CloseOffer` applies through its constraints.
+// Offers with a rent receiver are rejected, as everything here is closed to
+// the maker; use `close_offer` for those.
+#[derive(Accounts)]
+pub struct CloseOffers<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", maker.key().as_ref()],
+        bump = maker_reputation.bump
+    )]
+    pub maker_reputation: Box<Account<'info, Reputation>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Returns the number of offers closed.
+pub fn return_tokens_and_close_all<'info>(
+    context: Context<'_, '_, 'info, 'info, CloseOffers<'info>>,
+) -> Result<u64> {
+    let remaining_accounts = context.remaining_accounts;
+    require!(
+        !remaining_accounts.is_empty()
+            && remaining_accounts
+                .len()
+                .is_multiple_of(ACCOUNTS_PER_CLOSED_OFFER),
+        ErrorCode::CloseAccountMismatch
+    );
+
+    let maker = context.accounts.maker.to_account_info();
+    let token_program = context.accounts.token_program.key();
+    let token_program_info = context.accounts.token_program.to_account_info();
+    let transfers = LegTransfers {
+        token_program: &token_program_info,
+    };
+
+    let mut closed = 0;
+    for accounts in remaining_accounts.chunks(ACCOUNTS_PER_CLOSED_OFFER) {
+        let [offer_info, vault, mint_a, maker_token_account_a, market_stats, market_index_page] =
+            accounts
+        else {
+            unreachable!();
+        };
+
+        let offer = Account::<Offer>::try_from(offer_info)?;
+        require_eq!(offer.version, OFFER_VERSION, ErrorCode::OutdatedOffer);
+        require_keys_eq!(offer.maker, maker.key(), ErrorCode::CloseAccountMismatch);
+        let offer_address = Pubkey::create_program_address(
+            &[
+                b"offer",
+                maker.key.as_ref(),
+                &offer.id.to_le_bytes(),
+                &[offer.bump],
+            ],
+            context.program_id,
+        )
+        .map_err(|_| ErrorCode::CloseAccountMismatch)?;
+        require_keys_eq!(
+            offer_address,
+            offer_info.key(),
+            ErrorCode::CloseAccountMismatch
+        );
+        let rent_destination = offer.rent_destination(maker.clone(), None)?;
+
+        require_keys_eq!(
+            mint_a.key(),
+            offer.token_mint_a,
+            ErrorCode::CloseAccountMismatch
+        );
+        require_keys_eq!(
+            *mint_a.owner,
+            token_program,
+            ErrorCode::CloseAccountMismatch
+        );
+        let mint_a = InterfaceAccount::<Mint>::try_from(mint_a)?;
+
+        require_keys_eq!(
+            vault.key(),
+            Offer::vault_address(&offer_info.key(), &offer.token_mint_a, &token_program),
+            ErrorCode::InvalidVault
+        );
+        let vault_balance = InterfaceAccount::<TokenAccount>::try_from(vault)?.amount;
+
+        require_keys_eq!(
+            maker_token_account_a.key(),
+            get_associated_token_address_with_program_id(
+                maker.key,
+                &offer.token_mint_a,
+                &token_program
+            ),
+            ErrorCode::CloseAccountMismatch
+        );
+
+        // Several offers may share a market, so each update is written back
+        // before the next group loads the same account again.
+        let mut stats = Account::<MarketStats>::try_from(market_stats)?;
+        require_keys_eq!(
+            stats.key(),
+            Pubkey::create_program_address(
+                &[
+                    b"market_stats",
+                    offer.token_mint_a.as_ref(),
+                    offer.token_mint_b.as_ref(),
+                    &[stats.bump],
+                ],
+                context.program_id,
+            )
+            .map_err(|_| ErrorCode::CloseAccountMismatch)?,
+            ErrorCode::CloseAccountMismatch
+        );
+        stats.record_close();
+        stats.exit(context.program_id)?;
+
+        let mut index_page = Account::<MarketIndexPage>::try_from(market_index_page)?;
+        require_keys_eq!(
+            index_page.key(),
+            Pubkey::create_program_address(
+                &[
+                    b"market_index",
+                    offer.token_mint_a.as_ref(),
+                    offer.token_mint_b.as_ref(),
+                    &offer.index_page.to_le_bytes(),
+                    &[index_page.bump],
+                ],
+                context.program_id,
+            )
+            .map_err(|_| ErrorCode::CloseAccountMismatch)?,
+            ErrorCode::CloseAccountMismatch
+        );
+        index_page.remove(&offer_info.key());
+        index_page.exit(context.program_id)?;
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"offer",
+            maker.key.as_ref(),
+            &offer.id.to_le_bytes()[..],
+            &[offer.bump],
+        ]];
+        transfers.transfer(
+            vault,
+            maker_token_account_a,
+            offer_info,
+            &mint_a,
+            vault_balance,
+            &signer_seeds,
+        )?;
+        transfers.close_vault(vault, &rent_destination, offer_info, &signer_seeds)?;
+        offer.close(rent_destination)?;
+
+        context.accounts.maker_reputation.record_cancellation();
+        closed += 1;
+    }
+
+    Ok(closed)
+}
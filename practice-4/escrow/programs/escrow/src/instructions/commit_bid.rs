@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{error::ErrorCode, Auction, SealedBid, ANCHOR_DISCRIMINATOR};
+
+#[derive(Accounts)]
+pub struct CommitBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = token_mint_b,
+        seeds = [b"auction", auction.seller.as_ref(), auction.id.to_le_bytes().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = bidder,
+        associated_token::token_program = token_program
+    )]
+    pub bidder_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // One bid per bidder, so nobody can commit to several amounts and only
+    // reveal the best one for them.
+    #[account(
+        init,
+        payer = bidder,
+        space = ANCHOR_DISCRIMINATOR + SealedBid::INIT_SPACE,
+        seeds = [b"sealed_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    #[account(
+        mut,
+        address = Auction::vault_address(&auction.key(), &token_mint_b.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+    )]
+    pub vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn deposit_and_save_bid(
+    ctx: Context<CommitBid>,
+    commitment: [u8; 32],
+    deposit_amount: u64,
+) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp < ctx.accounts.auction.commit_end,
+        ErrorCode::WrongAuctionPhase
+    );
+    require!(deposit_amount > 0, ErrorCode::InvalidAmount);
+
+    let transfer_accounts = TransferChecked {
+        from: ctx.accounts.bidder_token_account_b.to_account_info(),
+        mint: ctx.accounts.token_mint_b.to_account_info(),
+        to: ctx.accounts.vault_b.to_account_info(),
+        authority: ctx.accounts.bidder.to_account_info(),
+    };
+
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_accounts,
+    );
+    transfer_checked(
+        cpi_context,
+        deposit_amount,
+        ctx.accounts.token_mint_b.decimals,
+    )?;
+
+    ctx.accounts.auction.open_bids += 1;
+    ctx.accounts.sealed_bid.set_inner(SealedBid {
+        auction: ctx.accounts.auction.key(),
+        bidder: ctx.accounts.bidder.key(),
+        commitment,
+        deposit_amount,
+        revealed_amount: None,
+        bump: ctx.bumps.sealed_bid,
+    });
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::ErrorCode, Auction, UnrevealedDeposits, ANCHOR_DISCRIMINATOR};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateAuction<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = token_mint_b.key() != token_mint_a.key() @ ErrorCode::InvalidAuctionMints
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program
+    )]
+    pub seller_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = ANCHOR_DISCRIMINATOR + Auction::INIT_SPACE,
+        seeds = [b"auction", seller.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program
+    )]
+    pub vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program
+    )]
+    pub vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn send_lot_to_vault(ctx: &Context<CreateAuction>, token_a_amount: u64) -> Result<()> {
+    require!(token_a_amount > 0, ErrorCode::InvalidAmount);
+
+    let transfer_accounts = TransferChecked {
+        from: ctx.accounts.seller_token_account_a.to_account_info(),
+        mint: ctx.accounts.token_mint_a.to_account_info(),
+        to: ctx.accounts.vault_a.to_account_info(),
+        authority: ctx.accounts.seller.to_account_info(),
+    };
+
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_accounts,
+    );
+    transfer_checked(
+        cpi_context,
+        token_a_amount,
+        ctx.accounts.token_mint_a.decimals,
+    )
+}
+
+pub fn save_auction(
+    ctx: Context<CreateAuction>,
+    id: u64,
+    token_a_amount: u64,
+    reserve_price: u64,
+    commit_seconds: i64,
+    reveal_seconds: i64,
+    unrevealed_deposits: UnrevealedDeposits,
+) -> Result<()> {
+    require!(
+        commit_seconds > 0 && reveal_seconds > 0,
+        ErrorCode::InvalidAuctionSchedule
+    );
+
+    let commit_end = Clock::get()?
+        .unix_timestamp
+        .checked_add(commit_seconds)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let reveal_end = commit_end
+        .checked_add(reveal_seconds)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    ctx.accounts.auction.set_inner(Auction {
+        id,
+        seller: ctx.accounts.seller.key(),
+        token_mint_a: ctx.accounts.token_mint_a.key(),
+        token_mint_b: ctx.accounts.token_mint_b.key(),
+        token_a_amount,
+        reserve_price,
+        commit_end,
+        reveal_end,
+        unrevealed_deposits,
+        highest_bid: 0,
+        highest_bidder: None,
+        open_bids: 0,
+        settled: false,
+        bump: ctx.bumps.auction,
+    });
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{error::ErrorCode, BookSide, OrderBook, Side, ANCHOR_DISCRIMINATOR};
+
+#[derive(Accounts)]
+pub struct CreateOrderBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = token_mint_b.key() != token_mint_a.key() @ ErrorCode::InvalidOrderBookMints
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ANCHOR_DISCRIMINATOR + OrderBook::INIT_SPACE,
+        seeds = [b"order_book", token_mint_a.key().as_ref(), token_mint_b.key().as_ref()],
+        bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ANCHOR_DISCRIMINATOR + BookSide::INIT_SPACE,
+        seeds = [b"bids", order_book.key().as_ref()],
+        bump
+    )]
+    pub bids: Box<Account<'info, BookSide>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ANCHOR_DISCRIMINATOR + BookSide::INIT_SPACE,
+        seeds = [b"asks", order_book.key().as_ref()],
+        bump
+    )]
+    pub asks: Box<Account<'info, BookSide>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn save_order_book(context: Context<CreateOrderBook>) -> Result<()> {
+    let order_book = context.accounts.order_book.key();
+
+    context.accounts.order_book.set_inner(OrderBook {
+        token_mint_a: context.accounts.token_mint_a.key(),
+        token_mint_b: context.accounts.token_mint_b.key(),
+        next_order_id: 0,
+        bump: context.bumps.order_book,
+    });
+    context.accounts.bids.set_inner(BookSide {
+        order_book,
+        side: Side::Bid,
+        orders: Vec::new(),
+        bump: context.bumps.bids,
+    });
+    context.accounts.asks.set_inner(BookSide {
+        order_book,
+        side: Side::Ask,
+        orders: Vec::new(),
+        bump: context.bumps.asks,
+    });
+    Ok(())
+}
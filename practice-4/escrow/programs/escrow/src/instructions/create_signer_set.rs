@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, SignerSet, ANCHOR_DISCRIMINATOR, MAX_SIGNER_SET_MEMBERS};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateSignerSet<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR + SignerSet::INIT_SPACE,
+        seeds = [b"signer_set", creator.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub signer_set: Account<'info, SignerSet>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn save_signer_set(
+    ctx: Context<CreateSignerSet>,
+    id: u64,
+    members: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    require!(
+        members.len() <= MAX_SIGNER_SET_MEMBERS
+            && (1..=members.len()).contains(&(threshold as usize)),
+        ErrorCode::InvalidSignerSet
+    );
+    for (index, member) in members.iter().enumerate() {
+        require!(
+            !members[..index].contains(member),
+            ErrorCode::InvalidSignerSet
+        );
+    }
+
+    let signer_set = ctx.accounts.signer_set.key();
+    ctx.accounts.signer_set.set_inner(SignerSet {
+        creator: ctx.accounts.creator.key(),
+        id,
+        members,
+        threshold,
+        next_proposal_id: 0,
+        authority_bump: SignerSet::authority_address(&signer_set).1,
+        bump: ctx.bumps.signer_set,
+    });
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, MakerDelegate, Offer, OFFER_VERSION};
+
+#[derive(Accounts)]
+pub struct DelegateAmendOffer<'info> {
+    pub delegate: Signer<'info>,
+
+    pub maker: SystemAccount<'info>,
+
+    // Only needed when acting as the maker's delegate rather than the
+    // offer's.
+    #[account(
+        seeds = [b"maker_delegate", maker.key().as_ref()],
+        bump = maker_delegate.bump
+    )]
+    pub maker_delegate: Option<Account<'info, MakerDelegate>>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        constraint = offer.is_delegate(&delegate.key(), maker_delegate.as_deref())
+            @ ErrorCode::NotOfferDelegate,
+        constraint = offer.version == OFFER_VERSION @ ErrorCode::OutdatedOffer,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+pub fn update_wanted_amount_as_delegate(
+    ctx: Context<DelegateAmendOffer>,
+    token_b_wanted_amount: u64,
+) -> Result<()> {
+    // Only the maker can sell for less: a delegate lowering the price could
+    // take the offer for next to nothing itself.
+    require!(
+        token_b_wanted_amount >= ctx.accounts.offer.token_b_wanted_amount,
+        ErrorCode::DelegateCannotLowerPrice
+    );
+    ctx.accounts.offer.reprice(token_b_wanted_amount)
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::close_offer::{return_tokens_and_close, OfferToClose};
+use crate::{
+    error::ErrorCode, MakerDelegate, MarketIndexPage, MarketStats, Offer, Reputation, OFFER_VERSION,
+};
+
+#[derive(Accounts)]
+pub struct DelegateCloseOffer<'info> {
+    pub delegate: Signer<'info>,
+
+    // Tokens and rent only ever go back to the maker, or to the rent
+    // receiver the maker chose.
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    // Only needed when acting as the maker's delegate rather than the
+    // offer's.
+    #[account(
+        seeds = [b"maker_delegate", maker.key().as_ref()],
+        bump = maker_delegate.bump
+    )]
+    pub maker_delegate: Option<Account<'info, MakerDelegate>>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        constraint = offer.is_delegate(&delegate.key(), maker_delegate.as_deref())
+            @ ErrorCode::NotOfferDelegate,
+        has_one = token_mint_a,
+        has_one = token_mint_b,
+        constraint = offer.version == OFFER_VERSION @ ErrorCode::OutdatedOffer,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        address = Offer::vault_address(&offer.key(), &token_mint_a.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Only receives lamports, checked against the offer when closing.
+    #[account(mut)]
+    pub rent_receiver: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"market_stats", offer.token_mint_a.as_ref(), offer.token_mint_b.as_ref()],
+        bump = market_stats.bump
+    )]
+    pub market_stats: Box<Account<'info, MarketStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", maker.key().as_ref()],
+        bump = maker_reputation.bump
+    )]
+    pub maker_reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"market_index",
+            offer.token_mint_a.as_ref(),
+            offer.token_mint_b.as_ref(),
+            offer.index_page.to_le_bytes().as_ref()
+        ],
+        bump = market_index_page.bump
+    )]
+    pub market_index_page: Box<Account<'info, MarketIndexPage>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn return_tokens_and_close_as_delegate(ctx: Context<DelegateCloseOffer>) -> Result<()> {
+    let accounts = ctx.accounts;
+    return_tokens_and_close(OfferToClose {
+        maker: accounts.maker.to_account_info(),
+        offer: &accounts.offer,
+        vault: &accounts.vault,
+        token_mint_a: &accounts.token_mint_a,
+        maker_token_account_a: &accounts.maker_token_account_a,
+        rent_receiver: accounts
+            .rent_receiver
+            .as_ref()
+            .map(|rent_receiver| rent_receiver.to_account_info()),
+        market_stats: &mut accounts.market_stats,
+        maker_reputation: &mut accounts.maker_reputation,
+        market_index_page: &mut accounts.market_index_page,
+        token_program: accounts.token_program.to_account_info(),
+    })
+}
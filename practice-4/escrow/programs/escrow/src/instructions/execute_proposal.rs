@@ -0,0 +1,134 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    },
+    InstructionData,
+};
+
+use crate::{error::ErrorCode, program::Escrow, Proposal, ProposalAction, SignerSet};
+
+// Anyone can execute a proposal once it has enough approvals.  The accounts
+// of the escrow instruction it runs follow in `remaining_accounts`, in that
+// instruction's order, with the signer set's authority as the maker.
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        seeds = [b"signer_set", signer_set.creator.as_ref(), signer_set.id.to_le_bytes().as_ref()],
+        bump = signer_set.bump
+    )]
+    pub signer_set: Box<Account<'info, SignerSet>>,
+
+    // The proposer paid its rent.
+    #[account(
+        mut,
+        close = proposer,
+        has_one = signer_set,
+        has_one = proposer,
+        seeds = [b"proposal", signer_set.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+
+    pub escrow_program: Program<'info, Escrow>,
+}
+
+// Runs the approved action by calling back into the escrow, so it goes
+// through exactly the same checks as when a wallet is the maker.
+pub fn invoke_approved_action<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+) -> Result<()> {
+    let signer_set = &ctx.accounts.signer_set;
+    let proposal = &ctx.accounts.proposal;
+    require!(
+        proposal.approvals.len() >= signer_set.threshold as usize,
+        ErrorCode::ThresholdNotMet
+    );
+
+    // Each instruction takes the maker first.  Arguments cannot pin down the
+    // mints or offer it acts on, so those are matched by position.
+    let signer_set_key = signer_set.key();
+    let authority = Pubkey::create_program_address(
+        &[
+            b"authority",
+            signer_set_key.as_ref(),
+            &[signer_set.authority_bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| ErrorCode::InvalidSignerSet)?;
+    let (data, pinned_accounts) = match proposal.action.clone() {
+        ProposalAction::MakeOffer {
+            id,
+            token_mint_a,
+            token_mint_b,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            terms,
+            index_page,
+        } => (
+            crate::instruction::MakeOffer {
+                id,
+                token_a_offered_amount,
+                token_b_wanted_amount,
+                terms,
+                index_page,
+            }
+            .data(),
+            vec![authority, token_mint_a, token_mint_b],
+        ),
+        ProposalAction::AmendOffer {
+            offer,
+            token_b_wanted_amount,
+        } => (
+            crate::instruction::AmendOffer {
+                token_b_wanted_amount,
+            }
+            .data(),
+            vec![authority, offer],
+        ),
+        ProposalAction::CloseOffer { offer } => (
+            crate::instruction::CloseOffer {}.data(),
+            vec![authority, offer],
+        ),
+    };
+
+    let accounts = ctx.remaining_accounts;
+    require!(
+        accounts.len() >= pinned_accounts.len()
+            && accounts
+                .iter()
+                .zip(&pinned_accounts)
+                .all(|(account, pinned)| account.key() == *pinned),
+        ErrorCode::ProposalAccountMismatch
+    );
+
+    let instruction = Instruction {
+        program_id: crate::ID,
+        accounts: accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer || account.key() == authority,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    let mut account_infos = accounts.to_vec();
+    account_infos.push(ctx.accounts.escrow_program.to_account_info());
+    invoke_signed(
+        &instruction,
+        &account_infos,
+        &[&[
+            b"authority",
+            signer_set_key.as_ref(),
+            &[signer_set.authority_bump],
+        ]],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+use crate::MarketStats;
+
+// Read-only: returns the stats through return data, so clients can get them
+// by simulating the instruction instead of decoding the account themselves.
+#[derive(Accounts)]
+pub struct GetMarketStats<'info> {
+    #[account(
+        seeds = [
+            b"market_stats",
+            market_stats.token_mint_a.as_ref(),
+            market_stats.token_mint_b.as_ref(),
+        ],
+        bump = market_stats.bump
+    )]
+    pub market_stats: Account<'info, MarketStats>,
+}
+
+pub fn read_market_stats(ctx: Context<GetMarketStats>) -> Result<MarketStats> {
+    Ok(ctx.accounts.market_stats.clone().into_inner())
+}
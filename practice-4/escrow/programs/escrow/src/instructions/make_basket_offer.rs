@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+use super::basket::{self, AtaCreator};
+use crate::{BasketLeg, BasketOffer, ANCHOR_DISCRIMINATOR};
+
+// Offered legs follow in `remaining_accounts`, one triple per leg, in the
+// same order as `offered`:
+//
+//   [token mint, maker ATA for the mint, basket offer ATA (vault) for the mint]
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct MakeBasketOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        init,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + BasketOffer::INIT_SPACE,
+        seeds = [b"basket", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub basket_offer: Account<'info, BasketOffer>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn send_offered_tokens_to_vaults<'info>(
+    context: &Context<'_, '_, 'info, 'info, MakeBasketOffer<'info>>,
+    offered: &[BasketLeg],
+    wanted: &[BasketLeg],
+) -> Result<()> {
+    basket::check_legs(offered, wanted)?;
+
+    let maker = context.accounts.maker.to_account_info();
+    let atas = AtaCreator {
+        payer: &maker,
+        token_program: &context.accounts.token_program.to_account_info(),
+        associated_token_program: &context.accounts.associated_token_program.to_account_info(),
+        system_program: &context.accounts.system_program.to_account_info(),
+    };
+    basket::fund_vaults(
+        &atas,
+        &maker,
+        &context.accounts.basket_offer.to_account_info(),
+        offered,
+        context.remaining_accounts,
+    )
+}
+
+pub fn save_basket_offer(
+    context: Context<MakeBasketOffer>,
+    id: u64,
+    offered: Vec<BasketLeg>,
+    wanted: Vec<BasketLeg>,
+) -> Result<()> {
+    context.accounts.basket_offer.set_inner(BasketOffer {
+        id,
+        maker: context.accounts.maker.key(),
+        offered,
+        wanted,
+        bump: context.bumps.basket_offer,
+    });
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+use super::basket::{self, AtaCreator};
+use crate::{BasketLeg, FixedBasketOffer, ANCHOR_DISCRIMINATOR};
+
+// Same accounts as `make_basket_offer`, with one triple per offered leg in
+// `remaining_accounts`:
+//
+//   [token mint, maker ATA for the mint, basket offer ATA (vault) for the mint]
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct MakeFixedBasketOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        init,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + FixedBasketOffer::SPACE,
+        seeds = [b"fixed_basket", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub basket_offer: AccountLoader<'info, FixedBasketOffer>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn send_offered_tokens_to_fixed_vaults<'info>(
+    context: &Context<'_, '_, 'info, 'info, MakeFixedBasketOffer<'info>>,
+    offered: &[BasketLeg],
+    wanted: &[BasketLeg],
+) -> Result<()> {
+    basket::check_legs(offered, wanted)?;
+
+    let maker = context.accounts.maker.to_account_info();
+    let atas = AtaCreator {
+        payer: &maker,
+        token_program: &context.accounts.token_program.to_account_info(),
+        associated_token_program: &context.accounts.associated_token_program.to_account_info(),
+        system_program: &context.accounts.system_program.to_account_info(),
+    };
+    basket::fund_vaults(
+        &atas,
+        &maker,
+        &context.accounts.basket_offer.to_account_info(),
+        offered,
+        context.remaining_accounts,
+    )
+}
+
+pub fn save_fixed_basket_offer(
+    context: Context<MakeFixedBasketOffer>,
+    id: u64,
+    offered: &[BasketLeg],
+    wanted: &[BasketLeg],
+) -> Result<()> {
+    let mut basket_offer = context.accounts.basket_offer.load_init()?;
+    basket_offer.id = id;
+    basket_offer.maker = context.accounts.maker.key();
+    basket_offer.set_legs(offered, wanted);
+    basket_offer.bump = context.bumps.basket_offer;
+    Ok(())
+}
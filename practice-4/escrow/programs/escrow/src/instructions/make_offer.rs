@@ -5,10 +5,19 @@ use anchor_spl::{
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::{Offer, ANCHOR_DISCRIMINATOR};
+use crate::{
+    error::ErrorCode, MarketIndexPage, MarketStats, Offer, OfferTerms, Reputation,
+    ANCHOR_DISCRIMINATOR, MAX_BPS, MAX_PAYMENT_OPTIONS,
+};
 
 #[derive(Accounts)]
-#[instruction(id: u64)]
+#[instruction(
+    id: u64,
+    token_a_offered_amount: u64,
+    token_b_wanted_amount: u64,
+    terms: OfferTerms,
+    index_page: u32
+)]
 pub struct MakeOffer<'info> {
     #[account(mut)]
     pub maker: Signer<'info>,
@@ -26,6 +35,17 @@ pub struct MakeOffer<'info> {
         associated_token::token_program = token_program
     )]
     pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    // Created here, at the maker's expense, so takers do not pay its rent.
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(
         init,
         payer = maker,
@@ -44,6 +64,51 @@ pub struct MakeOffer<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MarketStats::INIT_SPACE,
+        seeds = [b"market_stats", token_mint_a.key().as_ref(), token_mint_b.key().as_ref()],
+        bump
+    )]
+    pub market_stats: Box<Account<'info, MarketStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + Reputation::INIT_SPACE,
+        seeds = [b"reputation", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MarketIndexPage::INIT_SPACE,
+        seeds = [
+            b"market_index",
+            token_mint_a.key().as_ref(),
+            token_mint_b.key().as_ref(),
+            index_page.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub market_index_page: Box<Account<'info, MarketIndexPage>>,
+
+    // Only needed when `market_index_page` is created, to link it.
+    #[account(
+        mut,
+        seeds = [
+            b"market_index",
+            token_mint_a.key().as_ref(),
+            token_mint_b.key().as_ref(),
+            index_page.saturating_sub(1).to_le_bytes().as_ref()
+        ],
+        bump = previous_index_page.bump
+    )]
+    pub previous_index_page: Option<Box<Account<'info, MarketIndexPage>>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
@@ -52,6 +117,8 @@ pub fn send_offered_tokens_to_vault(
     context: &Context<MakeOffer>,
     token_a_offered_amount: u64,
 ) -> Result<()> {
+    require!(token_a_offered_amount > 0, ErrorCode::InvalidAmount);
+
     let transfer_accounts = TransferChecked {
         from: context.accounts.maker_token_account_a.to_account_info(),
         mint: context.accounts.token_mint_a.to_account_info(),
@@ -70,14 +137,101 @@ pub fn send_offered_tokens_to_vault(
         context.accounts.token_mint_a.decimals,
     )
 }
-pub fn save_offer(context: Context<MakeOffer>, id: u64, token_b_wanted_amount: u64) -> Result<()> {
-    context.accounts.offer.set_inner(Offer {
+pub fn check_terms(terms: &OfferTerms, token_mint_a: &Pubkey, token_mint_b: &Pubkey) -> Result<()> {
+    if let Some(allowlist) = &terms.allowlist {
+        require!(allowlist.per_taker_cap != Some(0), ErrorCode::InvalidAmount);
+    }
+
+    require!(
+        terms.payment_options.len() <= MAX_PAYMENT_OPTIONS,
+        ErrorCode::TooManyPaymentOptions
+    );
+    let mut seen_mints = vec![*token_mint_a, *token_mint_b];
+    for option in &terms.payment_options {
+        require!(option.wanted_amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            !seen_mints.contains(&option.mint),
+            ErrorCode::DuplicatePaymentMint
+        );
+        seen_mints.push(option.mint);
+    }
+
+    if let Some(tranches) = &terms.tranches {
+        require!(
+            tranches.interval_seconds > 0 && tranches.tranche_amount > 0,
+            ErrorCode::InvalidTrancheSchedule
+        );
+    }
+
+    if let Some(vesting) = &terms.vesting {
+        require!(
+            vesting.duration_seconds > 0
+                && (0..=vesting.duration_seconds).contains(&vesting.cliff_seconds),
+            ErrorCode::InvalidVestingSchedule
+        );
+    }
+
+    if let Some(trigger) = &terms.price_trigger {
+        require!(
+            trigger.max_staleness_seconds > 0,
+            ErrorCode::InvalidPriceTrigger
+        );
+    }
+
+    require!(
+        terms.max_referral_bps <= MAX_BPS,
+        ErrorCode::InvalidReferralFee
+    );
+    Ok(())
+}
+
+pub fn save_offer(
+    context: Context<MakeOffer>,
+    id: u64,
+    token_a_offered_amount: u64,
+    token_b_wanted_amount: u64,
+    terms: OfferTerms,
+    index_page: u32,
+) -> Result<()> {
+    check_terms(
+        &terms,
+        &context.accounts.token_mint_a.key(),
+        &context.accounts.token_mint_b.key(),
+    )?;
+
+    context.accounts.offer.set_inner(Offer::new(
         id,
-        maker: context.accounts.maker.key(),
-        token_mint_a: context.accounts.token_mint_a.key(),
-        token_mint_b: context.accounts.token_mint_b.key(),
+        context.accounts.maker.key(),
+        context.accounts.token_mint_a.key(),
+        context.accounts.token_mint_b.key(),
         token_b_wanted_amount,
-        bump: context.bumps.offer,
-    });
-    Ok(())
+        context.bumps.offer,
+        token_a_offered_amount,
+        terms,
+        index_page,
+    ));
+
+    context.accounts.market_stats.record_open(
+        context.accounts.token_mint_a.key(),
+        context.accounts.token_mint_b.key(),
+        context.bumps.market_stats,
+    );
+    context.accounts.maker_reputation.init_if_new(
+        context.accounts.maker.key(),
+        context.bumps.maker_reputation,
+        Clock::get()?.unix_timestamp,
+    );
+
+    let accounts = &mut *context.accounts;
+    accounts.market_index_page.init_if_new(
+        accounts.token_mint_a.key(),
+        accounts.token_mint_b.key(),
+        index_page,
+        context.bumps.market_index_page,
+        accounts
+            .previous_index_page
+            .as_deref_mut()
+            .map(|previous_page| &mut **previous_page),
+    )?;
+    accounts.market_index_page.insert(accounts.offer.key())
 }
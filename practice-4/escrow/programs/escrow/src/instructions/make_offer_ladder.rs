@@ -0,0 +1,321 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+};
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::{
+    basket::{AtaCreator, LegTransfers},
+    make_offer::check_terms,
+};
+use crate::{
+    error::ErrorCode, MakerCounter, MarketIndexPage, MarketStats, Offer, OfferTerms, Reputation,
+    ANCHOR_DISCRIMINATOR, MAX_LADDER_LEVELS,
+};
+
+pub const ACCOUNTS_PER_LEVEL: usize = 2;
+
+// One price level of a ladder: `token_a_amount` of the deposit offered for
+// `token_b_wanted_amount`, by the offer with id `offer_id`.  The client
+// derives the offer's address and passes its bump, so the program only has
+// to check it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LadderLevel {
+    pub token_a_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub offer_id: u64,
+    pub offer_bump: u8,
+}
+
+// Every level gets its own `Offer`.  Their ids have to increase, past those
+// of the maker's earlier ladders.  The accounts to create follow in
+// `remaining_accounts`, one pair per level, in the same order as `levels`:
+//
+//   [offer PDA for the level's id, offer ATA (vault) for token A]
+#[derive(Accounts)]
+#[instruction(
+    token_a_total_amount: u64,
+    levels: Vec<LadderLevel>,
+    terms: OfferTerms,
+    index_page: u32
+)]
+pub struct MakeOfferLadder<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MakerCounter::INIT_SPACE,
+        seeds = [b"maker_counter", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_counter: Box<Account<'info, MakerCounter>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MarketStats::INIT_SPACE,
+        seeds = [b"market_stats", token_mint_a.key().as_ref(), token_mint_b.key().as_ref()],
+        bump
+    )]
+    pub market_stats: Box<Account<'info, MarketStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + Reputation::INIT_SPACE,
+        seeds = [b"reputation", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_reputation: Box<Account<'info, Reputation>>,
+
+    // Every level is listed on this page, so it needs room for all of them.
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MarketIndexPage::INIT_SPACE,
+        seeds = [
+            b"market_index",
+            token_mint_a.key().as_ref(),
+            token_mint_b.key().as_ref(),
+            index_page.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub market_index_page: Box<Account<'info, MarketIndexPage>>,
+
+    // Only needed when `market_index_page` is created, to link it.
+    #[account(
+        mut,
+        seeds = [
+            b"market_index",
+            token_mint_a.key().as_ref(),
+            token_mint_b.key().as_ref(),
+            index_page.saturating_sub(1).to_le_bytes().as_ref()
+        ],
+        bump = previous_index_page.bump
+    )]
+    pub previous_index_page: Option<Box<Account<'info, MarketIndexPage>>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn check_levels(token_a_total_amount: u64, levels: &[LadderLevel]) -> Result<()> {
+    require!(
+        !levels.is_empty() && levels.len() <= MAX_LADDER_LEVELS,
+        ErrorCode::InvalidLadderLevelCount
+    );
+
+    let mut split_amount: u64 = 0;
+    for level in levels {
+        require!(
+            level.token_a_amount > 0 && level.token_b_wanted_amount > 0,
+            ErrorCode::InvalidAmount
+        );
+        split_amount = split_amount
+            .checked_add(level.token_a_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+    require!(
+        split_amount == token_a_total_amount,
+        ErrorCode::LadderAmountMismatch
+    );
+    Ok(())
+}
+
+pub fn create_level_offers<'info>(
+    context: Context<'_, '_, 'info, 'info, MakeOfferLadder<'info>>,
+    levels: Vec<LadderLevel>,
+    terms: OfferTerms,
+    index_page: u32,
+) -> Result<()> {
+    check_terms(
+        &terms,
+        &context.accounts.token_mint_a.key(),
+        &context.accounts.token_mint_b.key(),
+    )?;
+    require!(
+        context.remaining_accounts.len() == levels.len() * ACCOUNTS_PER_LEVEL,
+        ErrorCode::LadderAccountMismatch
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let accounts = &mut *context.accounts;
+    let maker = accounts.maker.to_account_info();
+    let token_mint_a = accounts.token_mint_a.key();
+    let token_mint_b = accounts.token_mint_b.key();
+    let token_program = accounts.token_program.key();
+    let token_program_info = accounts.token_program.to_account_info();
+    let system_program = accounts.system_program.to_account_info();
+    let atas = AtaCreator {
+        payer: &maker,
+        token_program: &token_program_info,
+        associated_token_program: &accounts.associated_token_program.to_account_info(),
+        system_program: &system_program,
+    };
+    let transfers = LegTransfers {
+        token_program: &token_program_info,
+    };
+
+    accounts.maker_counter.maker = maker.key();
+    accounts.maker_counter.bump = context.bumps.maker_counter;
+    accounts
+        .maker_reputation
+        .init_if_new(maker.key(), context.bumps.maker_reputation, now);
+    accounts.market_index_page.init_if_new(
+        token_mint_a,
+        token_mint_b,
+        index_page,
+        context.bumps.market_index_page,
+        accounts
+            .previous_index_page
+            .as_deref_mut()
+            .map(|previous_page| &mut **previous_page),
+    )?;
+
+    for (level, level_accounts) in levels
+        .iter()
+        .zip(context.remaining_accounts.chunks(ACCOUNTS_PER_LEVEL))
+    {
+        let [offer_info, vault] = level_accounts else {
+            unreachable!();
+        };
+
+        let offer_address = offer_info.key();
+        let (id, bump) = (level.offer_id, level.offer_bump);
+        accounts.maker_counter.take_id(id)?;
+        let signer_seeds: [&[&[u8]]; 1] =
+            [&[b"offer", maker.key.as_ref(), &id.to_le_bytes()[..], &[bump]]];
+        require_keys_eq!(
+            offer_address,
+            Pubkey::create_program_address(signer_seeds[0], context.program_id)
+                .map_err(|_| ErrorCode::LadderAccountMismatch)?,
+            ErrorCode::LadderAccountMismatch
+        );
+        require_keys_eq!(
+            vault.key(),
+            Offer::vault_address(&offer_address, &token_mint_a, &token_program),
+            ErrorCode::InvalidVault
+        );
+
+        create_offer_account(
+            &maker,
+            offer_info,
+            &system_program,
+            context.program_id,
+            &signer_seeds,
+        )?;
+        {
+            let offer = Offer::new(
+                id,
+                maker.key(),
+                token_mint_a,
+                token_mint_b,
+                level.token_b_wanted_amount,
+                bump,
+                level.token_a_amount,
+                terms.clone(),
+                index_page,
+            );
+            let mut data = offer_info.try_borrow_mut_data()?;
+            offer.try_serialize(&mut &mut data[..])?;
+        }
+
+        atas.create(vault, offer_info, &accounts.token_mint_a.to_account_info())?;
+        transfers.transfer(
+            &accounts.maker_token_account_a.to_account_info(),
+            vault,
+            &maker,
+            &accounts.token_mint_a,
+            level.token_a_amount,
+            &[],
+        )?;
+
+        accounts
+            .market_stats
+            .record_open(token_mint_a, token_mint_b, context.bumps.market_stats);
+        accounts.market_index_page.insert(offer_address)?;
+    }
+    Ok(())
+}
+
+// Same as Anchor's `init`: also works when someone already sent lamports to
+// the address to keep it from being created.
+fn create_offer_account<'info>(
+    maker: &AccountInfo<'info>,
+    offer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let space = ANCHOR_DISCRIMINATOR + Offer::INIT_SPACE;
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if offer.lamports() == 0 {
+        let accounts = CreateAccount {
+            from: maker.clone(),
+            to: offer.clone(),
+        };
+        return create_account(
+            CpiContext::new_with_signer(system_program.clone(), accounts, signer_seeds),
+            rent,
+            space as u64,
+            program_id,
+        );
+    }
+
+    let top_up = rent.saturating_sub(offer.lamports());
+    if top_up > 0 {
+        let accounts = Transfer {
+            from: maker.clone(),
+            to: offer.clone(),
+        };
+        transfer(CpiContext::new(system_program.clone(), accounts), top_up)?;
+    }
+    let accounts = Allocate {
+        account_to_allocate: offer.clone(),
+    };
+    allocate(
+        CpiContext::new_with_signer(system_program.clone(), accounts, signer_seeds),
+        space as u64,
+    )?;
+    let accounts = Assign {
+        account_to_assign: offer.clone(),
+    };
+    assign(
+        CpiContext::new_with_signer(system_program.clone(), accounts, signer_seeds),
+        program_id,
+    )
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, token_b_amount_at, BookSide, OrderBook, Side, UserBalance};
+
+// Permissionless crank: fills the best bid against the best ask while they
+// cross, up to `limit` fills.  Each fill trades at the price of whichever
+// order was placed first, and settles into the owners' `UserBalance`
+// accounts, which follow in `remaining_accounts` for every owner involved.
+#[derive(Accounts)]
+pub struct MatchOrders<'info> {
+    #[account(
+        seeds = [
+            b"order_book",
+            order_book.token_mint_a.as_ref(),
+            order_book.token_mint_b.as_ref(),
+        ],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        mut,
+        seeds = [b"bids", order_book.key().as_ref()],
+        bump = bids.bump
+    )]
+    pub bids: Box<Account<'info, BookSide>>,
+
+    #[account(
+        mut,
+        seeds = [b"asks", order_book.key().as_ref()],
+        bump = asks.bump
+    )]
+    pub asks: Box<Account<'info, BookSide>>,
+}
+
+// Returns the number of fills made.  A crossing pair whose fill would be
+// worth no token B is not filled: the smaller order is dropped and refunded
+// instead, and counts towards `limit` without being a fill.
+pub fn match_crossing_orders<'info>(
+    context: Context<'_, '_, 'info, 'info, MatchOrders<'info>>,
+    limit: u8,
+) -> Result<u8> {
+    let order_book = context.accounts.order_book.key();
+
+    // A balance passed twice would be loaded twice, and the last copy written
+    // back would drop the credits made to the other.
+    let mut seen = BTreeSet::new();
+    let mut balances = Vec::with_capacity(context.remaining_accounts.len());
+    for info in context.remaining_accounts {
+        require!(seen.insert(info.key()), ErrorCode::UserBalanceMismatch);
+        let balance = Account::<UserBalance>::try_from(info)?;
+        require_keys_eq!(
+            balance.order_book,
+            order_book,
+            ErrorCode::UserBalanceMismatch
+        );
+        balances.push(balance);
+    }
+    let bids = &mut context.accounts.bids.orders;
+    let asks = &mut context.accounts.asks.orders;
+    let mut fills = 0;
+    for _ in 0..limit {
+        let (Some(bid), Some(ask)) = (bids.first_mut(), asks.first_mut()) else {
+            break;
+        };
+        if bid.price < ask.price {
+            break;
+        }
+
+        let price = if bid.order_id < ask.order_id {
+            bid.price
+        } else {
+            ask.price
+        };
+        let token_a_amount = bid.token_a_amount.min(ask.token_a_amount);
+        let token_b_amount = token_b_amount_at(token_a_amount, price, false)?;
+        if token_b_amount == 0 {
+            // What is left of an order after partial fills can be too small
+            // to pay the ask anything.
+            if bid.token_a_amount == token_a_amount {
+                let bid = bids.remove(0);
+                balance_of(&mut balances, &bid.owner)?.refund(Side::Bid, &bid)?;
+            } else {
+                let ask = asks.remove(0);
+                balance_of(&mut balances, &ask.owner)?.refund(Side::Ask, &ask)?;
+            }
+            continue;
+        }
+
+        bid.token_a_amount -= token_a_amount;
+        ask.token_a_amount -= token_a_amount;
+        bid.token_b_locked = bid
+            .token_b_locked
+            .checked_sub(token_b_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        balance_of(&mut balances, &bid.owner)?.credit_token_a(token_a_amount)?;
+        balance_of(&mut balances, &ask.owner)?.credit_token_b(token_b_amount)?;
+
+        let (bid_filled, ask_filled) = (bid.token_a_amount == 0, ask.token_a_amount == 0);
+        if bid_filled {
+            // Filling at or below the bid's price can leave part of its
+            // deposit unspent.
+            let bid = bids.remove(0);
+            balance_of(&mut balances, &bid.owner)?.credit_token_b(bid.token_b_locked)?;
+        }
+        if ask_filled {
+            asks.remove(0);
+        }
+        fills += 1;
+    }
+
+    for balance in &balances {
+        balance.exit(context.program_id)?;
+    }
+    Ok(fills)
+}
+
+fn balance_of<'a, 'info>(
+    balances: &'a mut [Account<'info, UserBalance>],
+    owner: &Pubkey,
+) -> Result<&'a mut Account<'info, UserBalance>> {
+    balances
+        .iter_mut()
+        .find(|balance| balance.owner == *owner)
+        .ok_or(error!(ErrorCode::UserBalanceMismatch))
+}
@@ -0,0 +1,232 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+    Discriminator,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::ErrorCode, LegacyOffer, MarketIndexPage, MarketStats, Offer, OfferTerms, Reputation,
+    ANCHOR_DISCRIMINATOR, OFFER_VERSION,
+};
+
+#[derive(Accounts)]
+#[instruction(index_page: u32)]
+pub struct MigrateOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// CHECK: Cannot be loaded as an `Offer` until it is migrated.  The
+    /// older layout, the maker and the PDA seeds are checked in
+    /// `migrate_to_current_layout`.
+    #[account(mut, owner = crate::ID)]
+    pub offer: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = Offer::vault_address(&offer.key(), &token_mint_a.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // Takes of older offers created it on the taker's rent, which they no
+    // longer do, so the maker pays for it here as `make_offer` makes them.
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Legacy offers predate market stats, so they are counted as open here,
+    // as are version 1 offers made before the market's index.
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MarketStats::INIT_SPACE,
+        seeds = [b"market_stats", token_mint_a.key().as_ref(), token_mint_b.key().as_ref()],
+        bump
+    )]
+    pub market_stats: Box<Account<'info, MarketStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + Reputation::INIT_SPACE,
+        seeds = [b"reputation", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MarketIndexPage::INIT_SPACE,
+        seeds = [
+            b"market_index",
+            token_mint_a.key().as_ref(),
+            token_mint_b.key().as_ref(),
+            index_page.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub market_index_page: Box<Account<'info, MarketIndexPage>>,
+
+    // Only needed when `market_index_page` is created, to link it.
+    #[account(
+        mut,
+        seeds = [
+            b"market_index",
+            token_mint_a.key().as_ref(),
+            token_mint_b.key().as_ref(),
+            index_page.saturating_sub(1).to_le_bytes().as_ref()
+        ],
+        bump = previous_index_page.bump
+    )]
+    pub previous_index_page: Option<Box<Account<'info, MarketIndexPage>>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_to_current_layout(ctx: Context<MigrateOffer>, index_page: u32) -> Result<()> {
+    let offer_info = ctx.accounts.offer.to_account_info();
+
+    // Every older layout starts with the legacy fields.
+    let (legacy, version) = {
+        let data = offer_info.try_borrow_data()?;
+        require!(
+            data.len() >= LegacyOffer::SPACE
+                && data[..ANCHOR_DISCRIMINATOR] == *Offer::DISCRIMINATOR,
+            ErrorCode::NotLegacyOffer
+        );
+        let version = match data.len() {
+            LegacyOffer::SPACE => None,
+            LegacyOffer::VERSION_ONE_SPACE if data[LegacyOffer::SPACE] == 1 => Some(1),
+            _ => return err!(ErrorCode::NotLegacyOffer),
+        };
+        (
+            LegacyOffer::deserialize(&mut &data[ANCHOR_DISCRIMINATOR..])?,
+            version,
+        )
+    };
+
+    require_keys_eq!(
+        legacy.maker,
+        ctx.accounts.maker.key(),
+        ErrorCode::NotLegacyOffer
+    );
+    require_keys_eq!(
+        legacy.token_mint_a,
+        ctx.accounts.token_mint_a.key(),
+        ErrorCode::NotLegacyOffer
+    );
+    require_keys_eq!(
+        legacy.token_mint_b,
+        ctx.accounts.token_mint_b.key(),
+        ErrorCode::NotLegacyOffer
+    );
+    let offer_address = Pubkey::create_program_address(
+        &[
+            b"offer",
+            legacy.maker.as_ref(),
+            &legacy.id.to_le_bytes(),
+            &[legacy.bump],
+        ],
+        ctx.program_id,
+    )
+    .map_err(|_| ErrorCode::NotLegacyOffer)?;
+    require_keys_eq!(offer_address, offer_info.key(), ErrorCode::NotLegacyOffer);
+
+    // The maker pays the rent for the extra space.
+    let space = ANCHOR_DISCRIMINATOR + Offer::INIT_SPACE;
+    let extra_rent = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(offer_info.lamports());
+    if extra_rent > 0 {
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.maker.to_account_info(),
+            to: offer_info.clone(),
+        };
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            transfer_accounts,
+        );
+        transfer(cpi_context, extra_rent)?;
+    }
+    offer_info.realloc(space, true)?;
+
+    // Version 2 only added reserved space, which `realloc` has just zeroed,
+    // so version 1 offers keep their fields where they are.  The market's
+    // stats and index came while offers were at version 1, so only those
+    // not on their index page yet are counted and listed here.  Offers made
+    // before the index have page 0 stored, and stay on it.
+    let listed = if version == Some(1) {
+        let mut data = offer_info.try_borrow_mut_data()?;
+        data[LegacyOffer::SPACE] = OFFER_VERSION;
+        let offer = Offer::try_deserialize(&mut &data[..])?;
+        require_eq!(offer.index_page, index_page, ErrorCode::InvalidIndexPage);
+        ctx.accounts
+            .market_index_page
+            .offers
+            .contains(&offer_info.key())
+    } else {
+        // Legacy offers could only be taken in full, so whatever is in the
+        // vault is still the original deposit.
+        let offer = Offer::new(
+            legacy.id,
+            legacy.maker,
+            legacy.token_mint_a,
+            legacy.token_mint_b,
+            legacy.token_b_wanted_amount,
+            legacy.bump,
+            ctx.accounts.vault.amount,
+            OfferTerms::default(),
+            index_page,
+        );
+        let mut data = offer_info.try_borrow_mut_data()?;
+        offer.try_serialize(&mut &mut data[..])?;
+        false
+    };
+
+    ctx.accounts.maker_reputation.init_if_new(
+        legacy.maker,
+        ctx.bumps.maker_reputation,
+        Clock::get()?.unix_timestamp,
+    );
+    if listed {
+        return Ok(());
+    }
+
+    ctx.accounts.market_stats.record_open(
+        legacy.token_mint_a,
+        legacy.token_mint_b,
+        ctx.bumps.market_stats,
+    );
+    let accounts = &mut *ctx.accounts;
+    accounts.market_index_page.init_if_new(
+        legacy.token_mint_a,
+        legacy.token_mint_b,
+        index_page,
+        ctx.bumps.market_index_page,
+        accounts
+            .previous_index_page
+            .as_deref_mut()
+            .map(|previous_page| &mut **previous_page),
+    )?;
+    accounts.market_index_page.insert(offer_info.key())
+}
@@ -1,8 +1,113 @@
 pub mod make_offer;
 pub use make_offer::*;
 
+pub mod make_offer_ladder;
+pub use make_offer_ladder::*;
+
+mod receipt;
+
 pub mod take_offer;
 pub use take_offer::*;
 
+pub mod amend_offer;
+pub use amend_offer::*;
+
+pub mod register_referrer;
+pub use register_referrer::*;
+
 pub mod close_offer;
 pub use close_offer::*;
+
+pub mod claim_vested;
+pub use claim_vested::*;
+
+pub mod set_offer_delegate;
+pub use set_offer_delegate::*;
+
+pub mod set_maker_delegate;
+pub use set_maker_delegate::*;
+
+pub mod delegate_amend_offer;
+pub use delegate_amend_offer::*;
+
+pub mod delegate_close_offer;
+pub use delegate_close_offer::*;
+
+pub mod close_offers;
+pub use close_offers::*;
+
+pub mod get_market_stats;
+pub use get_market_stats::*;
+
+pub mod quote_offer;
+pub use quote_offer::*;
+
+pub mod migrate_offer;
+pub use migrate_offer::*;
+
+pub mod atomic_swap;
+pub use atomic_swap::*;
+
+mod basket;
+
+pub mod make_basket_offer;
+pub use make_basket_offer::*;
+
+pub mod take_basket_offer;
+pub use take_basket_offer::*;
+
+pub mod close_basket_offer;
+pub use close_basket_offer::*;
+
+pub mod make_fixed_basket_offer;
+pub use make_fixed_basket_offer::*;
+
+pub mod take_fixed_basket_offer;
+pub use take_fixed_basket_offer::*;
+
+pub mod close_fixed_basket_offer;
+pub use close_fixed_basket_offer::*;
+
+pub mod create_order_book;
+pub use create_order_book::*;
+
+pub mod place_order;
+pub use place_order::*;
+
+pub mod cancel_order;
+pub use cancel_order::*;
+
+pub mod match_orders;
+pub use match_orders::*;
+
+pub mod withdraw_balance;
+pub use withdraw_balance::*;
+
+mod auction_vaults;
+
+pub mod create_auction;
+pub use create_auction::*;
+
+pub mod commit_bid;
+pub use commit_bid::*;
+
+pub mod reveal_bid;
+pub use reveal_bid::*;
+
+pub mod settle_auction;
+pub use settle_auction::*;
+
+pub mod settle_bid;
+pub use settle_bid::*;
+
+pub mod create_signer_set;
+pub use create_signer_set::*;
+
+pub mod propose;
+pub use propose::*;
+
+pub mod approve_proposal;
+pub use approve_proposal::*;
+
+pub mod execute_proposal;
+pub use execute_proposal::*;
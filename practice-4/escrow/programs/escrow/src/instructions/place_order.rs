@@ -0,0 +1,151 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    error::ErrorCode, token_b_amount_at, BookSide, Order, OrderBook, Side, UserBalance,
+    ANCHOR_DISCRIMINATOR,
+};
+
+// Places a limit order on the book.  It rests until `match_orders` crosses
+// it with the other side or its owner cancels it.
+#[derive(Accounts)]
+#[instruction(side: Side)]
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"order_book",
+            order_book.token_mint_a.as_ref(),
+            order_book.token_mint_b.as_ref(),
+        ],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        mut,
+        seeds = [side.seed(), order_book.key().as_ref()],
+        bump = book_side.bump
+    )]
+    pub book_side: Box<Account<'info, BookSide>>,
+
+    // Token A for asks, token B for bids.
+    #[account(
+        address = order_book.deposit_mint(side),
+        mint::token_program = token_program
+    )]
+    pub deposit_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = deposit_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = OrderBook::vault_address(&order_book.key(), &deposit_mint.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Created here so `match_orders` always has somewhere to settle to.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR + UserBalance::INIT_SPACE,
+        seeds = [b"balance", order_book.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_balance: Box<Account<'info, UserBalance>>,
+
+    // Only needed when the side is full: the balance of the owner of its
+    // worst order, which this one evicts.
+    #[account(mut)]
+    pub evicted_balance: Option<Box<Account<'info, UserBalance>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// `price` is in token B per token A, scaled by `PRICE_SCALE`.
+pub fn deposit_and_insert_order(
+    context: Context<PlaceOrder>,
+    side: Side,
+    price: u64,
+    token_a_amount: u64,
+) -> Result<()> {
+    // Orders too small to be worth any token B could only be filled for free.
+    require!(
+        token_b_amount_at(token_a_amount, price, false)? > 0,
+        ErrorCode::InvalidAmount
+    );
+
+    let token_b_locked = match side {
+        Side::Ask => 0,
+        Side::Bid => token_b_amount_at(token_a_amount, price, true)?,
+    };
+    let deposit = match side {
+        Side::Ask => token_a_amount,
+        Side::Bid => token_b_locked,
+    };
+
+    let transfer_accounts = TransferChecked {
+        from: context.accounts.owner_token_account.to_account_info(),
+        mint: context.accounts.deposit_mint.to_account_info(),
+        to: context.accounts.vault.to_account_info(),
+        authority: context.accounts.owner.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(
+        context.accounts.token_program.to_account_info(),
+        transfer_accounts,
+    );
+    transfer_checked(cpi_context, deposit, context.accounts.deposit_mint.decimals)?;
+
+    let user_balance = &mut context.accounts.user_balance;
+    user_balance.order_book = context.accounts.order_book.key();
+    user_balance.owner = context.accounts.owner.key();
+    user_balance.bump = context.bumps.user_balance;
+
+    let order_id = context.accounts.order_book.take_next_order_id()?;
+    let owner = context.accounts.owner.key();
+    let evicted = context.accounts.book_side.insert(Order {
+        order_id,
+        owner,
+        price,
+        token_a_amount,
+        token_b_locked,
+    })?;
+    let Some(evicted) = evicted else {
+        return Ok(());
+    };
+
+    let accounts = &mut *context.accounts;
+    let balance = match accounts.evicted_balance.as_deref_mut() {
+        // Passing the owner's own balance twice would write back a stale
+        // copy over the other.
+        Some(balance) if balance.key() == accounts.user_balance.key() => {
+            return err!(ErrorCode::UserBalanceMismatch)
+        }
+        _ if evicted.owner == owner => &mut **accounts.user_balance,
+        Some(balance) => {
+            require_keys_eq!(balance.owner, evicted.owner, ErrorCode::UserBalanceMismatch);
+            require_keys_eq!(
+                balance.order_book,
+                accounts.order_book.key(),
+                ErrorCode::UserBalanceMismatch
+            );
+            &mut **balance
+        }
+        None => return err!(ErrorCode::BookSideFull),
+    };
+    balance.refund(side, &evicted)
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Proposal, ProposalAction, SignerSet, ANCHOR_DISCRIMINATOR};
+
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = signer_set.is_member(&proposer.key()) @ ErrorCode::NotSignerSetMember,
+        seeds = [b"signer_set", signer_set.creator.as_ref(), signer_set.id.to_le_bytes().as_ref()],
+        bump = signer_set.bump
+    )]
+    pub signer_set: Box<Account<'info, SignerSet>>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = ANCHOR_DISCRIMINATOR + Proposal::INIT_SPACE,
+        seeds = [
+            b"proposal",
+            signer_set.key().as_ref(),
+            signer_set.next_proposal_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn save_proposal(ctx: Context<Propose>, action: ProposalAction) -> Result<()> {
+    let signer_set = &mut ctx.accounts.signer_set;
+    let id = signer_set.next_proposal_id;
+    signer_set.next_proposal_id = id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+    ctx.accounts.proposal.set_inner(Proposal {
+        signer_set: signer_set.key(),
+        id,
+        proposer: ctx.accounts.proposer.key(),
+        action,
+        approvals: vec![ctx.accounts.proposer.key()],
+        bump: ctx.bumps.proposal,
+    });
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{error::ErrorCode, metadata, price_feed, Offer, Referrer, MAX_BPS, OFFER_VERSION};
+
+// Read-only: returns what a take of a given size would transfer right now
+// through return data, so clients can simulate it for the program's own
+// answer instead of redoing the fee maths.  Takes the same optional accounts
+// as `take_offer` for referrals, royalties and price triggers.
+#[derive(Accounts)]
+pub struct QuoteOffer<'info> {
+    #[account(
+        constraint = offer.wanted_amount_in(&token_mint_b.key()).is_some()
+            @ ErrorCode::PaymentMintNotAccepted,
+        constraint = offer.version == OFFER_VERSION @ ErrorCode::OutdatedOffer,
+        seeds = [b"offer", offer.maker.as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        address = Offer::vault_address(&offer.key(), &offer.token_mint_a, vault.to_account_info().owner)
+            @ ErrorCode::InvalidVault,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"referrer", referrer.wallet.as_ref()],
+        bump = referrer.bump
+    )]
+    pub referrer: Option<Account<'info, Referrer>>,
+
+    /// CHECK: Owner, address and layout are checked by `metadata::read_royalties`.
+    pub token_a_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Checked against the trigger, then by `price_feed::read_price`.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+// Every leg of a take.  The taker pays `token_b_amount`, which is split
+// between the maker, the referrer and token A's creators.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Quote {
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub maker_amount: u64,
+    pub referral_amount: u64,
+    // One per verified creator in metadata order, zero amounts included, as
+    // `take_offer` wants all their token B ATAs as remaining accounts.
+    // Empty unless the offer pays royalties.
+    pub royalties: Vec<RoyaltyLeg>,
+    // Token A goes to a vesting vault instead of the taker.
+    pub vests: bool,
+    // The take empties the vault and closes the offer.
+    pub closes_offer: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RoyaltyLeg {
+    pub creator: Pubkey,
+    pub amount: u64,
+}
+
+impl Quote {
+    // `take_offer` transfers exactly these amounts, so quotes and takes
+    // cannot drift apart.  Royalties and the referrer's share come out of
+    // what the maker receives, so the taker pays the same price either way.
+    pub fn new(
+        offer: &Offer,
+        token_mint_b: &Pubkey,
+        token_a_amount: u64,
+        vault_amount: u64,
+        referrer: Option<&Referrer>,
+        token_a_metadata: Option<&AccountInfo>,
+    ) -> Result<Self> {
+        let token_b_amount = offer.token_b_amount_for(token_mint_b, token_a_amount)?;
+
+        let mut royalties = Vec::new();
+        if offer.pay_royalties {
+            let metadata = token_a_metadata.ok_or(ErrorCode::InvalidMetadata)?;
+            let terms = metadata::read_royalties(metadata, &offer.token_mint_a)?;
+            let royalty_amount =
+                token_b_amount as u128 * terms.seller_fee_basis_points as u128 / MAX_BPS as u128;
+            royalties = terms
+                .creators
+                .iter()
+                .map(|creator| RoyaltyLeg {
+                    creator: creator.address,
+                    amount: (royalty_amount * creator.share as u128 / 100) as u64,
+                })
+                .collect();
+        }
+
+        let referral_amount = referrer.map_or(0, |referrer| {
+            referrer.share_of(token_b_amount, offer.max_referral_bps)
+        });
+
+        let royalty_amount: u64 = royalties.iter().map(|royalty| royalty.amount).sum();
+        let maker_amount = token_b_amount
+            .checked_sub(royalty_amount)
+            .and_then(|amount| amount.checked_sub(referral_amount))
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(Self {
+            token_a_amount,
+            token_b_amount,
+            maker_amount,
+            referral_amount,
+            royalties,
+            vests: offer.vesting.is_some(),
+            closes_offer: token_a_amount == vault_amount,
+        })
+    }
+}
+
+// Whether anyone could take `token_a_amount` right now: the vault holds it,
+// the price trigger is met and the tranches have unlocked it.  Checks on the
+// taker themselves are left to `take_offer`.
+pub fn check_takeable(
+    offer: &Offer,
+    vault_amount: u64,
+    token_a_amount: u64,
+    price_feed: Option<&AccountInfo>,
+    now: i64,
+) -> Result<()> {
+    require!(
+        token_a_amount > 0 && token_a_amount <= vault_amount,
+        ErrorCode::InvalidAmount
+    );
+
+    if let Some(trigger) = &offer.price_trigger {
+        let feed = price_feed
+            .filter(|feed| *feed.key == trigger.price_feed)
+            .ok_or(ErrorCode::InvalidPriceFeed)?;
+        let price = price_feed::read_price(feed, trigger.max_staleness_seconds, now)?;
+        require!(trigger.is_met(price), ErrorCode::TriggerNotMet);
+    }
+
+    if let Some(tranches) = &offer.tranches {
+        // Saturates if someone sent extra tokens straight to the vault.
+        let filled_amount = offer.token_a_offered_amount.saturating_sub(vault_amount);
+        require!(
+            filled_amount.saturating_add(token_a_amount) <= offer.unlocked_amount(tranches, now),
+            ErrorCode::TrancheLocked
+        );
+    }
+    Ok(())
+}
+
+pub fn quote_take(ctx: Context<QuoteOffer>, token_a_amount: u64) -> Result<Quote> {
+    let accounts = &ctx.accounts;
+    check_takeable(
+        &accounts.offer,
+        accounts.vault.amount,
+        token_a_amount,
+        accounts.price_feed.as_deref(),
+        Clock::get()?.unix_timestamp,
+    )?;
+    Quote::new(
+        &accounts.offer,
+        &accounts.token_mint_b.key(),
+        token_a_amount,
+        accounts.vault.amount,
+        accounts.referrer.as_deref(),
+        accounts.token_a_metadata.as_deref(),
+    )
+}
@@ -0,0 +1,190 @@
+// Non-transferable Token-2022 receipts of completed takes.
+//
+// Each receipt is its own mint with a supply of one, created from a fresh
+// keypair the taker signs with.  The take is recorded in the mint's own
+// metadata extension, so reading it needs no other program.  The offer signs
+// as mint and update authority, and gives up both once the receipt is out, so
+// no receipt can be minted again or rewritten.
+
+use anchor_lang::{
+    prelude::*,
+    system_program::{create_account, CreateAccount},
+};
+use anchor_spl::{
+    associated_token::{create, Create},
+    token_2022::{
+        initialize_mint2, mint_to, set_authority,
+        spl_token_2022::{extension::ExtensionType, instruction::AuthorityType, state::Mint},
+        InitializeMint2, MintTo, SetAuthority,
+    },
+    token_interface::{
+        metadata_pointer_initialize, non_transferable_mint_initialize, token_metadata_initialize,
+        token_metadata_update_authority, token_metadata_update_field, MetadataPointerInitialize,
+        NonTransferableMintInitialize, TokenMetadataInitialize, TokenMetadataUpdateAuthority,
+        TokenMetadataUpdateField,
+    },
+};
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+
+const RECEIPT_NAME: &str = "Escrow receipt";
+const RECEIPT_SYMBOL: &str = "RECEIPT";
+
+pub struct ReceiptMinter<'a, 'info> {
+    pub payer: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub authority_seeds: &'a [&'a [u8]],
+    pub token_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> ReceiptMinter<'_, 'info> {
+    // Creates `mint` with `fields` as its additional metadata and mints its
+    // only token to `owner`'s ATA.
+    pub fn mint(
+        &self,
+        mint: AccountInfo<'info>,
+        owner: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        fields: Vec<(String, String)>,
+    ) -> Result<()> {
+        let signer_seeds = [self.authority_seeds];
+
+        // The metadata is appended to the mint as it is written, so the mint
+        // is funded up front for its final size.
+        let mint_space = ExtensionType::try_calculate_account_len::<Mint>(&[
+            ExtensionType::NonTransferable,
+            ExtensionType::MetadataPointer,
+        ])?;
+        let metadata_space = TokenMetadata {
+            name: RECEIPT_NAME.to_string(),
+            symbol: RECEIPT_SYMBOL.to_string(),
+            additional_metadata: fields.clone(),
+            ..Default::default()
+        }
+        .tlv_size_of()?;
+        create_account(
+            CpiContext::new(
+                self.system_program.clone(),
+                CreateAccount {
+                    from: self.payer.clone(),
+                    to: mint.clone(),
+                },
+            ),
+            Rent::get()?.minimum_balance(mint_space + metadata_space),
+            mint_space as u64,
+            self.token_program.key,
+        )?;
+
+        non_transferable_mint_initialize(CpiContext::new(
+            self.token_program.clone(),
+            NonTransferableMintInitialize {
+                token_program_id: self.token_program.clone(),
+                mint: mint.clone(),
+            },
+        ))?;
+        metadata_pointer_initialize(
+            CpiContext::new(
+                self.token_program.clone(),
+                MetadataPointerInitialize {
+                    token_program_id: self.token_program.clone(),
+                    mint: mint.clone(),
+                },
+            ),
+            None,
+            Some(mint.key()),
+        )?;
+        initialize_mint2(
+            CpiContext::new(
+                self.token_program.clone(),
+                InitializeMint2 { mint: mint.clone() },
+            ),
+            0,
+            self.authority.key,
+            None,
+        )?;
+
+        token_metadata_initialize(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                TokenMetadataInitialize {
+                    program_id: self.token_program.clone(),
+                    mint: mint.clone(),
+                    metadata: mint.clone(),
+                    mint_authority: self.authority.clone(),
+                    update_authority: self.authority.clone(),
+                },
+                &signer_seeds,
+            ),
+            RECEIPT_NAME.to_string(),
+            RECEIPT_SYMBOL.to_string(),
+            String::new(),
+        )?;
+        for (key, value) in fields {
+            token_metadata_update_field(
+                CpiContext::new_with_signer(
+                    self.token_program.clone(),
+                    TokenMetadataUpdateField {
+                        program_id: self.token_program.clone(),
+                        metadata: mint.clone(),
+                        update_authority: self.authority.clone(),
+                    },
+                    &signer_seeds,
+                ),
+                Field::Key(key),
+                value,
+            )?;
+        }
+
+        create(CpiContext::new(
+            self.associated_token_program.clone(),
+            Create {
+                payer: self.payer.clone(),
+                associated_token: token_account.clone(),
+                authority: owner,
+                mint: mint.clone(),
+                system_program: self.system_program.clone(),
+                token_program: self.token_program.clone(),
+            },
+        ))?;
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                MintTo {
+                    mint: mint.clone(),
+                    to: token_account,
+                    authority: self.authority.clone(),
+                },
+                &signer_seeds,
+            ),
+            1,
+        )?;
+        set_authority(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                SetAuthority {
+                    current_authority: self.authority.clone(),
+                    account_or_mint: mint.clone(),
+                },
+                &signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+        // The default update authority is none, which makes the metadata
+        // immutable.
+        token_metadata_update_authority(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                TokenMetadataUpdateAuthority {
+                    program_id: self.token_program.clone(),
+                    metadata: mint,
+                    current_authority: self.authority.clone(),
+                    new_authority: self.authority.clone(),
+                },
+                &signer_seeds,
+            ),
+            Default::default(),
+        )
+    }
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Referrer, ANCHOR_DISCRIMINATOR, MAX_BPS};
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    // Called again to change the fee, keeping the totals.
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        space = ANCHOR_DISCRIMINATOR + Referrer::INIT_SPACE,
+        seeds = [b"referrer", wallet.key().as_ref()],
+        bump
+    )]
+    pub referrer: Account<'info, Referrer>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn save_referrer(ctx: Context<RegisterReferrer>, fee_bps: u16) -> Result<()> {
+    require!(fee_bps <= MAX_BPS, ErrorCode::InvalidReferralFee);
+
+    let referrer = &mut ctx.accounts.referrer;
+    referrer.wallet = ctx.accounts.wallet.key();
+    referrer.fee_bps = fee_bps;
+    referrer.bump = ctx.bumps.referrer;
+    Ok(())
+}
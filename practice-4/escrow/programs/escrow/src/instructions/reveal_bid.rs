@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Auction, SealedBid};
+
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.seller.as_ref(), auction.id.to_le_bytes().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [b"sealed_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = sealed_bid.bump
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+}
+
+pub fn check_and_record_bid(ctx: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &mut ctx.accounts.auction;
+    require!(
+        (auction.commit_end..auction.reveal_end).contains(&now),
+        ErrorCode::WrongAuctionPhase
+    );
+
+    let sealed_bid = &mut ctx.accounts.sealed_bid;
+    require!(
+        sealed_bid.revealed_amount.is_none(),
+        ErrorCode::BidAlreadyRevealed
+    );
+    require!(
+        SealedBid::commitment_for(&sealed_bid.bidder, amount, &salt) == sealed_bid.commitment,
+        ErrorCode::BidCommitmentMismatch
+    );
+    require!(
+        amount <= sealed_bid.deposit_amount,
+        ErrorCode::BidExceedsDeposit
+    );
+
+    // A bid under the reserve price counts as revealed, so its deposit is
+    // refunded, but cannot win.
+    sealed_bid.revealed_amount = Some(amount);
+    if amount >= auction.reserve_price {
+        auction.record_bid(sealed_bid.bidder, amount);
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{MakerDelegate, ANCHOR_DISCRIMINATOR};
+
+#[derive(Accounts)]
+pub struct SetMakerDelegate<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MakerDelegate::INIT_SPACE,
+        seeds = [b"maker_delegate", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_delegate: Account<'info, MakerDelegate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// `None` revokes the current delegate.
+pub fn save_maker_delegate(ctx: Context<SetMakerDelegate>, delegate: Option<Pubkey>) -> Result<()> {
+    let maker_delegate = &mut ctx.accounts.maker_delegate;
+    maker_delegate.maker = ctx.accounts.maker.key();
+    maker_delegate.delegate = delegate;
+    maker_delegate.bump = ctx.bumps.maker_delegate;
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Offer, OFFER_VERSION};
+
+#[derive(Accounts)]
+pub struct SetOfferDelegate<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        constraint = offer.version == OFFER_VERSION @ ErrorCode::OutdatedOffer,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+// `None` revokes the current delegate.
+pub fn save_offer_delegate(ctx: Context<SetOfferDelegate>, delegate: Option<Pubkey>) -> Result<()> {
+    ctx.accounts.offer.delegate = delegate;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::auction_vaults;
+use crate::{error::ErrorCode, Auction};
+
+// Anyone can settle an auction once its reveal phase is over.
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub seller: SystemAccount<'info>,
+
+    /// CHECK: Only owns `lot_receiver_token_account_a`.  Must be the highest
+    /// bidder, or the seller if no bid met the reserve price.
+    #[account(
+        constraint = lot_receiver.key() == auction.highest_bidder.unwrap_or(auction.seller)
+            @ ErrorCode::InvalidLotReceiver
+    )]
+    pub lot_receiver: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = seller,
+        has_one = token_mint_a,
+        has_one = token_mint_b,
+        seeds = [b"auction", seller.key().as_ref(), auction.id.to_le_bytes().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = Auction::vault_address(&auction.key(), &token_mint_a.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+    )]
+    pub vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = Auction::vault_address(&auction.key(), &token_mint_b.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+    )]
+    pub vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = lot_receiver,
+        associated_token::token_program = token_program
+    )]
+    pub lot_receiver_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Also receives forfeited deposits in `settle_bid`.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program
+    )]
+    pub seller_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Sends the lot to the winner and the winning bid to the seller.  Deposits
+// are then returned bid by bid through `settle_bid`.
+pub fn deliver_lot_and_payment(ctx: Context<SettleAuction>) -> Result<()> {
+    let accounts = ctx.accounts;
+    require!(
+        Clock::get()?.unix_timestamp >= accounts.auction.reveal_end && !accounts.auction.settled,
+        ErrorCode::WrongAuctionPhase
+    );
+
+    let token_program = accounts.token_program.to_account_info();
+    auction_vaults::transfer_from_vault(
+        &accounts.auction,
+        &accounts.vault_a,
+        &accounts.token_mint_a,
+        accounts.lot_receiver_token_account_a.to_account_info(),
+        token_program.clone(),
+        accounts.vault_a.amount,
+    )?;
+    auction_vaults::close_vault(
+        &accounts.auction,
+        &accounts.vault_a,
+        accounts.seller.to_account_info(),
+        token_program.clone(),
+    )?;
+
+    if accounts.auction.highest_bidder.is_some() {
+        auction_vaults::transfer_from_vault(
+            &accounts.auction,
+            &accounts.vault_b,
+            &accounts.token_mint_b,
+            accounts.seller_token_account_b.to_account_info(),
+            token_program.clone(),
+            accounts.auction.highest_bid,
+        )?;
+    }
+    accounts.auction.settled = true;
+
+    if accounts.auction.open_bids > 0 {
+        return Ok(());
+    }
+    auction_vaults::close_token_b_vault_and_auction(
+        &mut accounts.auction,
+        &mut accounts.vault_b,
+        &accounts.token_mint_b,
+        accounts.seller.to_account_info(),
+        accounts.seller_token_account_b.to_account_info(),
+        token_program,
+    )
+}
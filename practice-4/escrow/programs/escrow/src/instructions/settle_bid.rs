@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::auction_vaults;
+use crate::{error::ErrorCode, Auction, SealedBid, UnrevealedDeposits};
+
+// Anyone can settle a bid once its auction is settled, so forfeits do not
+// depend on the bidder showing up.
+#[derive(Accounts)]
+pub struct SettleBid<'info> {
+    #[account(mut)]
+    pub bidder: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = seller,
+        has_one = token_mint_b,
+        seeds = [b"auction", seller.key().as_ref(), auction.id.to_le_bytes().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    // The bidder paid its rent when committing.
+    #[account(
+        mut,
+        close = bidder,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [b"sealed_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = sealed_bid.bump
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = Auction::vault_address(&auction.key(), &token_mint_b.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+    )]
+    pub vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = bidder,
+        associated_token::token_program = token_program
+    )]
+    pub bidder_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Created by `settle_auction`.
+    #[account(
+        mut,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program
+    )]
+    pub seller_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Refunds the deposit, less the winning bid which already went to the
+// seller, or forfeits it if the bid was never revealed and the auction says
+// so.  The last bid settled closes the auction.
+pub fn refund_or_forfeit_deposit(ctx: Context<SettleBid>) -> Result<()> {
+    let accounts = ctx.accounts;
+    require!(accounts.auction.settled, ErrorCode::WrongAuctionPhase);
+
+    let sealed_bid = &accounts.sealed_bid;
+    let won = accounts.auction.highest_bidder == Some(sealed_bid.bidder);
+    let (destination, amount) = match sealed_bid.revealed_amount {
+        Some(_) if won => (
+            accounts.bidder_token_account_b.to_account_info(),
+            sealed_bid.deposit_amount - accounts.auction.highest_bid,
+        ),
+        None if accounts.auction.unrevealed_deposits == UnrevealedDeposits::Forfeit => (
+            accounts.seller_token_account_b.to_account_info(),
+            sealed_bid.deposit_amount,
+        ),
+        _ => (
+            accounts.bidder_token_account_b.to_account_info(),
+            sealed_bid.deposit_amount,
+        ),
+    };
+
+    let token_program = accounts.token_program.to_account_info();
+    if amount > 0 {
+        auction_vaults::transfer_from_vault(
+            &accounts.auction,
+            &accounts.vault_b,
+            &accounts.token_mint_b,
+            destination,
+            token_program.clone(),
+            amount,
+        )?;
+    }
+
+    accounts.auction.open_bids -= 1;
+    if accounts.auction.open_bids > 0 {
+        return Ok(());
+    }
+    auction_vaults::close_token_b_vault_and_auction(
+        &mut accounts.auction,
+        &mut accounts.vault_b,
+        &accounts.token_mint_b,
+        accounts.seller.to_account_info(),
+        accounts.seller_token_account_b.to_account_info(),
+        token_program,
+    )
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+use super::basket::{self, AtaCreator, VaultOwner};
+use crate::BasketOffer;
+
+// `remaining_accounts` holds one triple for every offered leg, followed by
+// one triple for every wanted leg, in the order recorded on the offer:
+//
+//   offered: [token mint, vault, taker ATA for the mint]
+//   wanted:  [token mint, taker ATA for the mint, maker ATA for the mint]
+//
+// The maker's ATAs have to exist before the offer can be taken.
+#[derive(Accounts)]
+pub struct TakeBasketOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket_offer.id.to_le_bytes().as_ref()],
+        bump = basket_offer.bump
+    )]
+    pub basket_offer: Account<'info, BasketOffer>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn swap_basket<'info>(
+    context: Context<'_, '_, 'info, 'info, TakeBasketOffer<'info>>,
+) -> Result<()> {
+    let basket_offer = &context.accounts.basket_offer;
+    let taker = context.accounts.taker.to_account_info();
+    let maker = context.accounts.maker.to_account_info();
+    let atas = AtaCreator {
+        payer: &taker,
+        token_program: &context.accounts.token_program.to_account_info(),
+        associated_token_program: &context.accounts.associated_token_program.to_account_info(),
+        system_program: &context.accounts.system_program.to_account_info(),
+    };
+
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"basket",
+        maker.key.as_ref(),
+        &basket_offer.id.to_le_bytes()[..],
+        &[basket_offer.bump],
+    ]];
+    let vault_owner = VaultOwner {
+        info: basket_offer.to_account_info(),
+        signer_seeds: &signer_seeds,
+    };
+    basket::swap_legs(
+        &atas,
+        &taker,
+        &maker,
+        &vault_owner,
+        &basket_offer.offered,
+        &basket_offer.wanted,
+        context.remaining_accounts,
+    )
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+use super::basket::{self, AtaCreator, VaultOwner};
+use crate::FixedBasketOffer;
+
+// Same accounts as `take_basket_offer`:
+//
+//   offered: [token mint, vault, taker ATA for the mint]
+//   wanted:  [token mint, taker ATA for the mint, maker ATA for the mint]
+#[derive(Accounts)]
+pub struct TakeFixedBasketOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [
+            b"fixed_basket",
+            maker.key().as_ref(),
+            basket_offer.load()?.id.to_le_bytes().as_ref()
+        ],
+        bump = basket_offer.load()?.bump
+    )]
+    pub basket_offer: AccountLoader<'info, FixedBasketOffer>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn swap_fixed_basket<'info>(
+    context: Context<'_, '_, 'info, 'info, TakeFixedBasketOffer<'info>>,
+) -> Result<()> {
+    // The legs are read in place.  The offer only signs the vault transfers
+    // as their authority, which reads the account without writing it.
+    let basket_offer = context.accounts.basket_offer.load()?;
+
+    let taker = context.accounts.taker.to_account_info();
+    let maker = context.accounts.maker.to_account_info();
+    let atas = AtaCreator {
+        payer: &taker,
+        token_program: &context.accounts.token_program.to_account_info(),
+        associated_token_program: &context.accounts.associated_token_program.to_account_info(),
+        system_program: &context.accounts.system_program.to_account_info(),
+    };
+
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"fixed_basket",
+        maker.key.as_ref(),
+        &basket_offer.id.to_le_bytes()[..],
+        &[basket_offer.bump],
+    ]];
+    let vault_owner = VaultOwner {
+        info: context.accounts.basket_offer.to_account_info(),
+        signer_seeds: &signer_seeds,
+    };
+    basket::swap_legs(
+        &atas,
+        &taker,
+        &maker,
+        &vault_owner,
+        basket_offer.offered_legs(),
+        basket_offer.wanted_legs(),
+        context.remaining_accounts,
+    )
+}
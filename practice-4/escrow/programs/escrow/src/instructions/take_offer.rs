@@ -1,14 +1,22 @@
 use anchor_lang::prelude::*;
 
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_2022::Token2022,
     token_interface::{
         close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
         TransferChecked,
     },
 };
 
-use crate::Offer;
+use super::{
+    quote_offer::{check_takeable, Quote},
+    receipt::ReceiptMinter,
+};
+use crate::{
+    error::ErrorCode, merkle, MarketIndexPage, MarketStats, Offer, Referrer, Reputation, TakerFill,
+    Vesting, ANCHOR_DISCRIMINATOR, OFFER_VERSION,
+};
 
 #[derive(Accounts)]
 pub struct TakeOffer<'info> {
@@ -18,9 +26,12 @@ pub struct TakeOffer<'info> {
     #[account(mut)]
     pub maker: SystemAccount<'info>,
 
+    #[account(mint::token_program = token_program)]
     pub token_mint_a: InterfaceAccount<'info, Mint>,
 
+    #[account(mint::token_program = token_program)]
     pub token_mint_b: InterfaceAccount<'info, Mint>,
+
     #[account(
         init_if_needed,
         payer = taker,
@@ -37,9 +48,10 @@ pub struct TakeOffer<'info> {
         associated_token::token_program = token_program,
     )]
     pub taker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    // Never created here, so takers do not pay the maker's rent.
+    // `make_offer` creates it for the offer's own token B mint.
     #[account(
-        init_if_needed,
-        payer = taker,
+        mut,
         associated_token::mint = token_mint_b,
         associated_token::authority = maker,
         associated_token::token_program = token_program,
@@ -48,14 +60,17 @@ pub struct TakeOffer<'info> {
 
     #[account(
         mut,
-        close = maker,
         has_one = maker,
         has_one = token_mint_a,
-        has_one = token_mint_b,
-        // seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
-        // bump = offer.bump
+        // The taker picks which of the accepted token B mints to pay with.
+        constraint = offer.wanted_amount_in(&token_mint_b.key()).is_some()
+            @ ErrorCode::PaymentMintNotAccepted,
+        constraint = offer.version == OFFER_VERSION @ ErrorCode::OutdatedOffer,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
     )]
-    offer: Account<'info, Offer>,
+    pub offer: Account<'info, Offer>,
+
     #[account(
         mut,
         associated_token::mint = token_mint_a,
@@ -64,31 +79,390 @@ pub struct TakeOffer<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    // Only needed when the offer caps how much each allowlisted taker may fill.
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = ANCHOR_DISCRIMINATOR + TakerFill::INIT_SPACE,
+        seeds = [b"taker_fill", offer.key().as_ref(), taker.key().as_ref()],
+        bump
+    )]
+    pub taker_fill: Option<Account<'info, TakerFill>>,
+
+    /// CHECK: Only receives lamports, checked against the offer when closing.
+    #[account(mut)]
+    pub rent_receiver: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"market_stats", offer.token_mint_a.as_ref(), offer.token_mint_b.as_ref()],
+        bump = market_stats.bump
+    )]
+    pub market_stats: Box<Account<'info, MarketStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = ANCHOR_DISCRIMINATOR + Reputation::INIT_SPACE,
+        seeds = [b"reputation", taker.key().as_ref()],
+        bump
+    )]
+    pub taker_reputation: Box<Account<'info, Reputation>>,
+
+    // Created by make_offer, so the taker never pays for the maker's record.
+    #[account(
+        mut,
+        seeds = [b"reputation", maker.key().as_ref()],
+        bump = maker_reputation.bump
+    )]
+    pub maker_reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"market_index",
+            offer.token_mint_a.as_ref(),
+            offer.token_mint_b.as_ref(),
+            offer.index_page.to_le_bytes().as_ref()
+        ],
+        bump = market_index_page.bump
+    )]
+    pub market_index_page: Box<Account<'info, MarketIndexPage>>,
+
+    // Only needed when the offer vests, and then token A goes to
+    // `vesting_vault` instead of `taker_token_account_a`.  Created on the
+    // taker's first take and added to on later ones.
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = ANCHOR_DISCRIMINATOR + Vesting::INIT_SPACE,
+        seeds = [b"vesting", offer.key().as_ref(), taker.key().as_ref()],
+        bump
+    )]
+    pub vesting: Option<Box<Account<'info, Vesting>>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub vesting_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // Only needed when a frontend referred the taker, and then both are.
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer.wallet.as_ref()],
+        bump = referrer.bump
+    )]
+    pub referrer: Option<Box<Account<'info, Referrer>>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint_b,
+        token::token_program = token_program,
+    )]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // Only read when the offer pays royalties.
+    /// CHECK: Owner, address and layout are checked by `metadata::read_royalties`.
+    pub token_a_metadata: Option<UncheckedAccount<'info>>,
+
+    // Only read when the offer has a price trigger.
+    /// CHECK: Checked against the trigger, then by `price_feed::read_price`.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    // Only needed when the offer issues receipts, and then all of them are.
+    // The mints are fresh keypairs, the token accounts their ATAs for the
+    // maker and the taker.
+    #[account(mut)]
+    pub maker_receipt_mint: Option<Signer<'info>>,
+
+    /// CHECK: Created as the maker's ATA, which the ATA program checks.
+    #[account(mut)]
+    pub maker_receipt_account: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub taker_receipt_mint: Option<Signer<'info>>,
+
+    /// CHECK: Created as the taker's ATA, which the ATA program checks.
+    #[account(mut)]
+    pub taker_receipt_account: Option<UncheckedAccount<'info>>,
+
+    pub receipt_token_program: Option<Program<'info, Token2022>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn send_wanted_tokens_to_maker(ctx: &Context<TakeOffer>) -> Result<()> {
-    let transfer_accounts = TransferChecked {
-        from: ctx.accounts.taker_token_account_b.to_account_info(),
-        mint: ctx.accounts.token_mint_b.to_account_info(),
-        to: ctx.accounts.maker_token_account_b.to_account_info(),
-        authority: ctx.accounts.taker.to_account_info(),
+pub fn check_taker_allowed(
+    ctx: &mut Context<TakeOffer>,
+    token_a_amount: u64,
+    proof: &[[u8; 32]],
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    check_takeable(
+        &ctx.accounts.offer,
+        ctx.accounts.vault.amount,
+        token_a_amount,
+        ctx.accounts.price_feed.as_deref(),
+        now,
+    )?;
+
+    let taker_reputation = &mut ctx.accounts.taker_reputation;
+    taker_reputation.init_if_new(ctx.accounts.taker.key(), ctx.bumps.taker_reputation, now);
+    if let Some(requirement) = &ctx.accounts.offer.min_taker_reputation {
+        require!(
+            taker_reputation.meets(requirement, now),
+            ErrorCode::InsufficientReputation
+        );
+    }
+
+    let Some(allowlist) = ctx.accounts.offer.allowlist.clone() else {
+        return Ok(());
     };
 
-    let cpi_context = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        transfer_accounts,
+    require!(
+        proof.len() <= merkle::MAX_PROOF_LEN,
+        ErrorCode::ProofTooLong
     );
-    transfer_checked(
-        cpi_context,
-        ctx.accounts.offer.token_b_wanted_amount,
-        ctx.accounts.token_mint_b.decimals,
+    let taker = ctx.accounts.taker.key();
+    require!(
+        merkle::verify(proof, &allowlist.root, merkle::leaf_hash(&taker)),
+        ErrorCode::NotAllowlisted
+    );
+
+    if let Some(cap) = allowlist.per_taker_cap {
+        let offer = ctx.accounts.offer.key();
+        let taker_fill = ctx
+            .accounts
+            .taker_fill
+            .as_mut()
+            .ok_or(ErrorCode::TakerFillRequired)?;
+
+        let filled_amount = taker_fill
+            .filled_amount
+            .checked_add(token_a_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(filled_amount <= cap, ErrorCode::TakerCapExceeded);
+
+        taker_fill.offer = offer;
+        taker_fill.taker = taker;
+        taker_fill.filled_amount = filled_amount;
+    }
+
+    Ok(())
+}
+
+pub fn start_vesting(ctx: &mut Context<TakeOffer>, token_a_amount: u64) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let (schedule, vesting) = match (&accounts.offer.vesting, &mut accounts.vesting) {
+        (None, None) if accounts.vesting_vault.is_none() => return Ok(()),
+        (Some(schedule), Some(vesting)) if accounts.vesting_vault.is_some() => (schedule, vesting),
+        _ => return err!(ErrorCode::VestingAccountsMismatch),
+    };
+
+    // A later take restarts the schedule with what is still unclaimed on top,
+    // so nothing bought later vests early.  Claiming first keeps what has
+    // vested already out of the restart.
+    let unclaimed_amount = vesting.total_amount - vesting.claimed_amount;
+    vesting.set_inner(Vesting {
+        offer: accounts.offer.key(),
+        taker: accounts.taker.key(),
+        token_mint_a: accounts.token_mint_a.key(),
+        total_amount: unclaimed_amount
+            .checked_add(token_a_amount)
+            .ok_or(ErrorCode::MathOverflow)?,
+        claimed_amount: 0,
+        start_timestamp: Clock::get()?.unix_timestamp,
+        cliff_seconds: schedule.cliff_seconds,
+        duration_seconds: schedule.duration_seconds,
+        bump: ctx.bumps.vesting.unwrap_or_default(),
+    });
+    Ok(())
+}
+
+// Pays out each token B leg of the take's `Quote`.  The creators' token B
+// ATAs are the remaining accounts, in metadata order.
+pub fn send_wanted_tokens_to_maker<'info>(
+    ctx: &mut Context<'_, '_, 'info, 'info, TakeOffer<'info>>,
+    token_a_amount: u64,
+) -> Result<()> {
+    let remaining_accounts = ctx.remaining_accounts;
+    let accounts = &mut *ctx.accounts;
+    match (&accounts.referrer, &accounts.referrer_token_account) {
+        (None, None) => {}
+        (Some(referrer), Some(referrer_token_account)) => require_keys_eq!(
+            referrer_token_account.owner,
+            referrer.wallet,
+            ErrorCode::ReferralAccountsMismatch
+        ),
+        _ => return err!(ErrorCode::ReferralAccountsMismatch),
+    }
+
+    let quote = Quote::new(
+        &accounts.offer,
+        &accounts.token_mint_b.key(),
+        token_a_amount,
+        accounts.vault.amount,
+        accounts.referrer.as_deref().map(|referrer| &**referrer),
+        accounts.token_a_metadata.as_deref(),
+    )?;
+
+    if accounts.offer.pay_royalties {
+        require!(
+            remaining_accounts.len() == quote.royalties.len(),
+            ErrorCode::CreatorAccountMismatch
+        );
+    }
+    for (royalty, creator_token_account) in quote.royalties.iter().zip(remaining_accounts) {
+        require_keys_eq!(
+            creator_token_account.key(),
+            get_associated_token_address_with_program_id(
+                &royalty.creator,
+                &accounts.token_mint_b.key(),
+                &accounts.token_program.key()
+            ),
+            ErrorCode::CreatorAccountMismatch
+        );
+        if royalty.amount > 0 {
+            transfer_from_taker(accounts, creator_token_account.clone(), royalty.amount)?;
+        }
+    }
+
+    if let Some(referrer) = &mut accounts.referrer {
+        referrer.record_referral(&accounts.taker.key(), quote.referral_amount)?;
+    }
+    if let Some(referrer_token_account) = &accounts.referrer_token_account {
+        if quote.referral_amount > 0 {
+            transfer_from_taker(
+                accounts,
+                referrer_token_account.to_account_info(),
+                quote.referral_amount,
+            )?;
+        }
+    }
+
+    transfer_from_taker(
+        accounts,
+        accounts.maker_token_account_b.to_account_info(),
+        quote.maker_amount,
+    )
+}
+
+fn transfer_from_taker<'info>(
+    accounts: &TakeOffer<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let transfer_accounts = TransferChecked {
+        from: accounts.taker_token_account_b.to_account_info(),
+        mint: accounts.token_mint_b.to_account_info(),
+        to,
+        authority: accounts.taker.to_account_info(),
+    };
+
+    let cpi_context = CpiContext::new(accounts.token_program.to_account_info(), transfer_accounts);
+    transfer_checked(cpi_context, amount, accounts.token_mint_b.decimals)
+}
+
+// Receipts are minted while the offer is still open, as it signs for them.
+pub fn issue_receipts(ctx: &Context<TakeOffer>, token_a_amount: u64) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let offer = &accounts.offer;
+    if !offer.issue_receipts {
+        return Ok(());
+    }
+    let (
+        Some(maker_receipt_mint),
+        Some(maker_receipt_account),
+        Some(taker_receipt_mint),
+        Some(taker_receipt_account),
+        Some(receipt_token_program),
+    ) = (
+        &accounts.maker_receipt_mint,
+        &accounts.maker_receipt_account,
+        &accounts.taker_receipt_mint,
+        &accounts.taker_receipt_account,
+        &accounts.receipt_token_program,
+    )
+    else {
+        return err!(ErrorCode::ReceiptAccountsMismatch);
+    };
+
+    let paid_mint = accounts.token_mint_b.key();
+    let token_b_amount = offer.token_b_amount_for(&paid_mint, token_a_amount)?;
+    let now = Clock::get()?.unix_timestamp;
+    let fields = |role: &str| {
+        vec![
+            ("role".to_string(), role.to_string()),
+            ("offer".to_string(), offer.key().to_string()),
+            ("offer_id".to_string(), offer.id.to_string()),
+            ("token_mint_a".to_string(), offer.token_mint_a.to_string()),
+            ("token_mint_b".to_string(), paid_mint.to_string()),
+            ("token_a_amount".to_string(), token_a_amount.to_string()),
+            ("token_b_amount".to_string(), token_b_amount.to_string()),
+            ("timestamp".to_string(), now.to_string()),
+        ]
+    };
+
+    let maker = accounts.maker.key();
+    let id = offer.id.to_le_bytes();
+    let bump = [offer.bump];
+    let minter = ReceiptMinter {
+        payer: accounts.taker.to_account_info(),
+        authority: offer.to_account_info(),
+        authority_seeds: &[b"offer", maker.as_ref(), &id, &bump],
+        token_program: receipt_token_program.to_account_info(),
+        associated_token_program: accounts.associated_token_program.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+    };
+    minter.mint(
+        maker_receipt_mint.to_account_info(),
+        accounts.maker.to_account_info(),
+        maker_receipt_account.to_account_info(),
+        fields("maker"),
+    )?;
+    minter.mint(
+        taker_receipt_mint.to_account_info(),
+        accounts.taker.to_account_info(),
+        taker_receipt_account.to_account_info(),
+        fields("taker"),
     )
 }
 
-pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
+pub fn record_fill(ctx: &mut Context<TakeOffer>, token_a_amount: u64) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    let paid_mint = ctx.accounts.token_mint_b.key();
+    let token_b_amount = if paid_mint == offer.token_mint_b {
+        Some(offer.token_b_amount_for(&paid_mint, token_a_amount)?)
+    } else {
+        None
+    };
+    let offer_filled = token_a_amount == ctx.accounts.vault.amount;
+
+    let market_stats = &mut ctx.accounts.market_stats;
+    market_stats.record_fill(token_a_amount, token_b_amount, Clock::get()?.unix_timestamp);
+    if offer_filled {
+        market_stats.record_close();
+        let offer = ctx.accounts.offer.key();
+        ctx.accounts.market_index_page.remove(&offer);
+    }
+
+    // Taking one's own offer builds no track record on either side.
+    if ctx.accounts.taker.key() == ctx.accounts.maker.key() {
+        return Ok(());
+    }
+    ctx.accounts
+        .taker_reputation
+        .record_take(token_a_amount, ctx.accounts.token_mint_a.decimals);
+    ctx.accounts.maker_reputation.record_sale(token_a_amount);
+    Ok(())
+}
+
+pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>, token_a_amount: u64) -> Result<()> {
     let signer_seeds: [&[&[u8]]; 1] = [&[
         b"offer",
         ctx.accounts.maker.to_account_info().key.as_ref(),
@@ -96,10 +470,14 @@ pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
         &[ctx.accounts.offer.bump],
     ]];
 
+    let destination = match &ctx.accounts.vesting_vault {
+        Some(vesting_vault) => vesting_vault.to_account_info(),
+        None => ctx.accounts.taker_token_account_a.to_account_info(),
+    };
     let accounts = TransferChecked {
         from: ctx.accounts.vault.to_account_info(),
         mint: ctx.accounts.token_mint_a.to_account_info(),
-        to: ctx.accounts.taker_token_account_a.to_account_info(),
+        to: destination,
         authority: ctx.accounts.offer.to_account_info(),
     };
 
@@ -110,13 +488,28 @@ pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
     );
     transfer_checked(
         cpi_context,
-        ctx.accounts.vault.amount,
+        token_a_amount,
         ctx.accounts.token_mint_a.decimals,
     )?;
 
+    // A partial take leaves the offer open for the rest of the vault.
+    if token_a_amount < ctx.accounts.vault.amount {
+        return Ok(());
+    }
+
+    // The maker paid for the vault and the offer, so their rent goes back to
+    // the maker, or wherever the maker asked it to go.
+    let rent_destination = ctx.accounts.offer.rent_destination(
+        ctx.accounts.maker.to_account_info(),
+        ctx.accounts
+            .rent_receiver
+            .as_ref()
+            .map(|rent_receiver| rent_receiver.to_account_info()),
+    )?;
+
     let accounts = CloseAccount {
         account: ctx.accounts.vault.to_account_info(),
-        destination: ctx.accounts.taker.to_account_info(),
+        destination: rent_destination.clone(),
         authority: ctx.accounts.offer.to_account_info(),
     };
 
@@ -126,5 +519,7 @@ pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
         &signer_seeds,
     );
 
-    close_account(cpi_context)
+    close_account(cpi_context)?;
+
+    ctx.accounts.offer.close(rent_destination)
 }
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::ErrorCode, OrderBook, UserBalance};
+
+#[derive(Accounts)]
+pub struct WithdrawBalance<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        has_one = token_mint_a,
+        has_one = token_mint_b,
+        seeds = [b"order_book", token_mint_a.key().as_ref(), token_mint_b.key().as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        mut,
+        seeds = [b"balance", order_book.key().as_ref(), owner.key().as_ref()],
+        bump = user_balance.bump
+    )]
+    pub user_balance: Box<Account<'info, UserBalance>>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = OrderBook::vault_address(&order_book.key(), &token_mint_a.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+    )]
+    pub vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = OrderBook::vault_address(&order_book.key(), &token_mint_b.key(), &token_program.key())
+            @ ErrorCode::InvalidVault,
+    )]
+    pub vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn send_free_balance_to_owner(context: Context<WithdrawBalance>) -> Result<()> {
+    let accounts = &mut *context.accounts;
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"order_book",
+        accounts.order_book.token_mint_a.as_ref(),
+        accounts.order_book.token_mint_b.as_ref(),
+        &[accounts.order_book.bump],
+    ]];
+
+    let withdrawals = [
+        (
+            &accounts.vault_a,
+            &accounts.token_mint_a,
+            &accounts.owner_token_account_a,
+            accounts.user_balance.token_a_free,
+        ),
+        (
+            &accounts.vault_b,
+            &accounts.token_mint_b,
+            &accounts.owner_token_account_b,
+            accounts.user_balance.token_b_free,
+        ),
+    ];
+    for (vault, mint, owner_token_account, amount) in withdrawals {
+        if amount == 0 {
+            continue;
+        }
+        let transfer_accounts = TransferChecked {
+            from: vault.to_account_info(),
+            mint: mint.to_account_info(),
+            to: owner_token_account.to_account_info(),
+            authority: accounts.order_book.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            transfer_accounts,
+            &signer_seeds,
+        );
+        transfer_checked(cpi_context, amount, mint.decimals)?;
+    }
+
+    accounts.user_balance.token_a_free = 0;
+    accounts.user_balance.token_b_free = 0;
+    Ok(())
+}
@@ -1,6 +1,9 @@
 pub mod constants;
 pub mod error;
 pub mod instructions;
+pub mod merkle;
+pub mod metadata;
+pub mod price_feed;
 pub mod state;
 
 use anchor_lang::prelude::*;
@@ -20,18 +23,253 @@ pub mod escrow {
         id: u64,
         token_a_offered_amount: u64,
         token_b_wanted_amount: u64,
+        terms: OfferTerms,
+        index_page: u32,
     ) -> Result<()> {
         instructions::make_offer::send_offered_tokens_to_vault(&context, token_a_offered_amount)?;
-        instructions::make_offer::save_offer(context, id, token_b_wanted_amount)
+        instructions::make_offer::save_offer(
+            context,
+            id,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            terms,
+            index_page,
+        )
     }
 
-    pub fn take_offer(context: Context<TakeOffer>) -> Result<()> {
-        instructions::take_offer::send_wanted_tokens_to_maker(&context)?;
-        instructions::take_offer::withdraw_and_close_vault(context)
+    pub fn make_offer_ladder<'info>(
+        context: Context<'_, '_, 'info, 'info, MakeOfferLadder<'info>>,
+        token_a_total_amount: u64,
+        levels: Vec<LadderLevel>,
+        terms: OfferTerms,
+        index_page: u32,
+    ) -> Result<()> {
+        instructions::make_offer_ladder::check_levels(token_a_total_amount, &levels)?;
+        instructions::make_offer_ladder::create_level_offers(context, levels, terms, index_page)
+    }
+
+    pub fn take_offer<'info>(
+        mut context: Context<'_, '_, 'info, 'info, TakeOffer<'info>>,
+        token_a_amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::take_offer::check_taker_allowed(&mut context, token_a_amount, &proof)?;
+        instructions::take_offer::start_vesting(&mut context, token_a_amount)?;
+        instructions::take_offer::send_wanted_tokens_to_maker(&mut context, token_a_amount)?;
+        instructions::take_offer::issue_receipts(&context, token_a_amount)?;
+        instructions::take_offer::record_fill(&mut context, token_a_amount)?;
+        instructions::take_offer::withdraw_and_close_vault(context, token_a_amount)
+    }
+
+    pub fn amend_offer(context: Context<AmendOffer>, token_b_wanted_amount: u64) -> Result<()> {
+        instructions::amend_offer::update_wanted_amount(context, token_b_wanted_amount)
+    }
+
+    pub fn register_referrer(context: Context<RegisterReferrer>, fee_bps: u16) -> Result<()> {
+        instructions::register_referrer::save_referrer(context, fee_bps)
+    }
+
+    pub fn claim_vested(context: Context<ClaimVested>) -> Result<()> {
+        instructions::claim_vested::release_vested_tokens(context)
     }
 
     pub fn close_offer(context: Context<CloseOffer>) -> Result<()> {
         instructions::close_offer::return_tokens_and_close_accounts(context)
     }
-}
 
+    pub fn set_offer_delegate(
+        context: Context<SetOfferDelegate>,
+        delegate: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_offer_delegate::save_offer_delegate(context, delegate)
+    }
+
+    pub fn set_maker_delegate(
+        context: Context<SetMakerDelegate>,
+        delegate: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_maker_delegate::save_maker_delegate(context, delegate)
+    }
+
+    pub fn delegate_amend_offer(
+        context: Context<DelegateAmendOffer>,
+        token_b_wanted_amount: u64,
+    ) -> Result<()> {
+        instructions::delegate_amend_offer::update_wanted_amount_as_delegate(
+            context,
+            token_b_wanted_amount,
+        )
+    }
+
+    pub fn delegate_close_offer(context: Context<DelegateCloseOffer>) -> Result<()> {
+        instructions::delegate_close_offer::return_tokens_and_close_as_delegate(context)
+    }
+
+    pub fn close_offers<'info>(
+        context: Context<'_, '_, 'info, 'info, CloseOffers<'info>>,
+    ) -> Result<u64> {
+        instructions::close_offers::return_tokens_and_close_all(context)
+    }
+
+    pub fn get_market_stats(context: Context<GetMarketStats>) -> Result<MarketStats> {
+        instructions::get_market_stats::read_market_stats(context)
+    }
+
+    pub fn quote_offer(context: Context<QuoteOffer>, token_a_amount: u64) -> Result<Quote> {
+        instructions::quote_offer::quote_take(context, token_a_amount)
+    }
+
+    pub fn migrate_offer(context: Context<MigrateOffer>, index_page: u32) -> Result<()> {
+        instructions::migrate_offer::migrate_to_current_layout(context, index_page)
+    }
+
+    pub fn atomic_swap(
+        context: Context<AtomicSwap>,
+        token_a_amount: u64,
+        token_b_amount: u64,
+    ) -> Result<()> {
+        instructions::atomic_swap::swap_tokens(context, token_a_amount, token_b_amount)
+    }
+
+    pub fn make_basket_offer<'info>(
+        context: Context<'_, '_, 'info, 'info, MakeBasketOffer<'info>>,
+        id: u64,
+        offered: Vec<BasketLeg>,
+        wanted: Vec<BasketLeg>,
+    ) -> Result<()> {
+        instructions::make_basket_offer::send_offered_tokens_to_vaults(
+            &context, &offered, &wanted,
+        )?;
+        instructions::make_basket_offer::save_basket_offer(context, id, offered, wanted)
+    }
+
+    pub fn take_basket_offer<'info>(
+        context: Context<'_, '_, 'info, 'info, TakeBasketOffer<'info>>,
+    ) -> Result<()> {
+        instructions::take_basket_offer::swap_basket(context)
+    }
+
+    pub fn close_basket_offer<'info>(
+        context: Context<'_, '_, 'info, 'info, CloseBasketOffer<'info>>,
+    ) -> Result<()> {
+        instructions::close_basket_offer::return_tokens_and_close_vaults(context)
+    }
+
+    pub fn make_fixed_basket_offer<'info>(
+        context: Context<'_, '_, 'info, 'info, MakeFixedBasketOffer<'info>>,
+        id: u64,
+        offered: Vec<BasketLeg>,
+        wanted: Vec<BasketLeg>,
+    ) -> Result<()> {
+        instructions::make_fixed_basket_offer::send_offered_tokens_to_fixed_vaults(
+            &context, &offered, &wanted,
+        )?;
+        instructions::make_fixed_basket_offer::save_fixed_basket_offer(
+            context, id, &offered, &wanted,
+        )
+    }
+
+    pub fn take_fixed_basket_offer<'info>(
+        context: Context<'_, '_, 'info, 'info, TakeFixedBasketOffer<'info>>,
+    ) -> Result<()> {
+        instructions::take_fixed_basket_offer::swap_fixed_basket(context)
+    }
+
+    pub fn close_fixed_basket_offer<'info>(
+        context: Context<'_, '_, 'info, 'info, CloseFixedBasketOffer<'info>>,
+    ) -> Result<()> {
+        instructions::close_fixed_basket_offer::return_tokens_and_close_fixed_vaults(context)
+    }
+
+    pub fn create_order_book(context: Context<CreateOrderBook>) -> Result<()> {
+        instructions::create_order_book::save_order_book(context)
+    }
+
+    pub fn place_order(
+        context: Context<PlaceOrder>,
+        side: Side,
+        price: u64,
+        token_a_amount: u64,
+    ) -> Result<()> {
+        instructions::place_order::deposit_and_insert_order(context, side, price, token_a_amount)
+    }
+
+    pub fn cancel_order(context: Context<CancelOrder>, side: Side, order_id: u64) -> Result<()> {
+        instructions::cancel_order::remove_order_and_refund(context, side, order_id)
+    }
+
+    pub fn match_orders<'info>(
+        context: Context<'_, '_, 'info, 'info, MatchOrders<'info>>,
+        limit: u8,
+    ) -> Result<u8> {
+        instructions::match_orders::match_crossing_orders(context, limit)
+    }
+
+    pub fn withdraw_balance(context: Context<WithdrawBalance>) -> Result<()> {
+        instructions::withdraw_balance::send_free_balance_to_owner(context)
+    }
+
+    pub fn create_auction(
+        context: Context<CreateAuction>,
+        id: u64,
+        token_a_amount: u64,
+        reserve_price: u64,
+        commit_seconds: i64,
+        reveal_seconds: i64,
+        unrevealed_deposits: UnrevealedDeposits,
+    ) -> Result<()> {
+        instructions::create_auction::send_lot_to_vault(&context, token_a_amount)?;
+        instructions::create_auction::save_auction(
+            context,
+            id,
+            token_a_amount,
+            reserve_price,
+            commit_seconds,
+            reveal_seconds,
+            unrevealed_deposits,
+        )
+    }
+
+    pub fn commit_bid(
+        context: Context<CommitBid>,
+        commitment: [u8; 32],
+        deposit_amount: u64,
+    ) -> Result<()> {
+        instructions::commit_bid::deposit_and_save_bid(context, commitment, deposit_amount)
+    }
+
+    pub fn reveal_bid(context: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+        instructions::reveal_bid::check_and_record_bid(context, amount, salt)
+    }
+
+    pub fn settle_auction(context: Context<SettleAuction>) -> Result<()> {
+        instructions::settle_auction::deliver_lot_and_payment(context)
+    }
+
+    pub fn settle_bid(context: Context<SettleBid>) -> Result<()> {
+        instructions::settle_bid::refund_or_forfeit_deposit(context)
+    }
+
+    pub fn create_signer_set(
+        context: Context<CreateSignerSet>,
+        id: u64,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::create_signer_set::save_signer_set(context, id, members, threshold)
+    }
+
+    pub fn propose(context: Context<Propose>, action: ProposalAction) -> Result<()> {
+        instructions::propose::save_proposal(context, action)
+    }
+
+    pub fn approve_proposal(context: Context<ApproveProposal>) -> Result<()> {
+        instructions::approve_proposal::record_approval(context)
+    }
+
+    pub fn execute_proposal<'info>(
+        context: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
+        instructions::execute_proposal::invoke_approved_action(context)
+    }
+}
@@ -0,0 +1,30 @@
+//! Merkle tree hashing shared by the on-chain allowlist check and the
+//! off-chain tree builder in `tools/allowlist`.
+//!
+//! Leaves and inner nodes use different prefixes so an inner node can never
+//! be passed off as a leaf.  Pairs are hashed in sorted order, which means a
+//! proof is just the list of sibling hashes, without left/right flags.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub const MAX_PROOF_LEN: usize = 24;
+
+pub fn leaf_hash(wallet: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, wallet.as_ref()]).to_bytes()
+}
+
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| node_hash(&node, sibling));
+    computed == *root
+}
@@ -0,0 +1,87 @@
+//! Reader for Metaplex token metadata accounts, limited to the royalty
+//! fields the escrow needs, so the program does not depend on the Metaplex
+//! crates.
+//!
+//! A metadata account starts with the fields below, in this order, followed
+//! by others the escrow ignores.  Borsh stops after `creators`, so the rest
+//! of the account never has to match.
+
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+// `Key::MetadataV1` in Metaplex's account tag enum.
+const METADATA_V1_KEY: u8 = 4;
+
+#[derive(AnchorDeserialize)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    // Percentage of the royalty, adding up to 100 across all creators.
+    pub share: u8,
+}
+
+#[derive(AnchorDeserialize)]
+struct MetadataHead {
+    key: u8,
+    _update_authority: Pubkey,
+    mint: Pubkey,
+    _name: String,
+    _symbol: String,
+    _uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<Creator>>,
+}
+
+pub struct Royalties {
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<Creator>,
+}
+
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+// Royalty terms of `mint`, read from its metadata account.  Only verified
+// creators are returned: anyone can list unverified ones.
+pub fn read_royalties(metadata: &AccountInfo, mint: &Pubkey) -> Result<Royalties> {
+    require_keys_eq!(
+        *metadata.owner,
+        TOKEN_METADATA_PROGRAM_ID,
+        ErrorCode::InvalidMetadata
+    );
+    require_keys_eq!(
+        metadata.key(),
+        metadata_address(mint),
+        ErrorCode::InvalidMetadata
+    );
+
+    let data = metadata.try_borrow_data()?;
+    let head = MetadataHead::deserialize(&mut &data[..])
+        .map_err(|_| error!(ErrorCode::InvalidMetadata))?;
+    require!(
+        head.key == METADATA_V1_KEY && head.mint == *mint,
+        ErrorCode::InvalidMetadata
+    );
+
+    Ok(Royalties {
+        seller_fee_basis_points: head.seller_fee_basis_points,
+        creators: head
+            .creators
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|creator| creator.verified)
+            .collect(),
+    })
+}
@@ -0,0 +1,61 @@
+//! Reader for Pyth pull oracle `PriceUpdateV2` accounts, limited to the
+//! fields a price trigger needs, so the program does not depend on the Pyth
+//! crates.
+//!
+//! Only fully verified updates are accepted: a partially verified one was
+//! checked against fewer guardian signatures than Wormhole requires.
+
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+// Anchor discriminator of the receiver's `PriceUpdateV2` account.
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+#[derive(AnchorDeserialize, PartialEq)]
+enum VerificationLevel {
+    Partial { _num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorDeserialize)]
+struct PriceUpdateV2 {
+    _write_authority: Pubkey,
+    verification_level: VerificationLevel,
+    _feed_id: [u8; 32],
+    price: i64,
+    _conf: u64,
+    _exponent: i32,
+    publish_time: i64,
+}
+
+// Latest price in `feed`, in the feed's own fixed-point units, if it was
+// published no more than `max_staleness_seconds` before `now`.
+pub fn read_price(feed: &AccountInfo, max_staleness_seconds: i64, now: i64) -> Result<i64> {
+    require_keys_eq!(
+        *feed.owner,
+        PYTH_RECEIVER_PROGRAM_ID,
+        ErrorCode::InvalidPriceFeed
+    );
+
+    let data = feed.try_borrow_data()?;
+    require!(
+        data.len() > 8 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR,
+        ErrorCode::InvalidPriceFeed
+    );
+    let update = PriceUpdateV2::deserialize(&mut &data[8..])
+        .map_err(|_| error!(ErrorCode::InvalidPriceFeed))?;
+    require!(
+        update.verification_level == VerificationLevel::Full,
+        ErrorCode::InvalidPriceFeed
+    );
+
+    require!(
+        now.saturating_sub(update.publish_time) <= max_staleness_seconds,
+        ErrorCode::StalePrice
+    );
+    Ok(update.price)
+}
@@ -0,0 +1,73 @@
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+// Sealed-bid auction of `token_a_amount` of token A for token B.  Bidders
+// commit to a hidden bid during the commit phase, reveal it during the
+// reveal phase, and the highest revealed bid wins once both are over.
+//
+// The lot sits in the auction's ATA for token A, and every deposit in its
+// ATA for token B until the bids are settled.
+#[account]
+#[derive(InitSpace)]
+pub struct Auction {
+    pub id: u64,
+    pub seller: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_a_amount: u64,
+    // Lowest bid that can win.
+    pub reserve_price: u64,
+    pub commit_end: i64,
+    pub reveal_end: i64,
+    pub unrevealed_deposits: UnrevealedDeposits,
+    pub highest_bid: u64,
+    pub highest_bidder: Option<Pubkey>,
+    // Bids committed and not settled yet.
+    pub open_bids: u32,
+    pub settled: bool,
+    pub bump: u8,
+}
+
+// What happens to the deposit of a bid that was never revealed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum UnrevealedDeposits {
+    Refund,
+    // Paid to the seller, so bidders cannot commit to several bids and only
+    // reveal the one that suits them.
+    Forfeit,
+}
+
+// One bidder's sealed bid.  The deposit can be larger than the bid to hide
+// it, and the difference is refunded when the bid is settled.
+#[account]
+#[derive(InitSpace)]
+pub struct SealedBid {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub commitment: [u8; 32],
+    pub deposit_amount: u64,
+    pub revealed_amount: Option<u64>,
+    pub bump: u8,
+}
+
+impl Auction {
+    pub fn vault_address(auction: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(auction, mint, token_program)
+    }
+
+    // Keeps the first of equal bids, so revealing early never hurts.
+    pub fn record_bid(&mut self, bidder: Pubkey, amount: u64) {
+        if self.highest_bidder.is_none() || amount > self.highest_bid {
+            self.highest_bid = amount;
+            self.highest_bidder = Some(bidder);
+        }
+    }
+}
+
+impl SealedBid {
+    // Commitment a bidder submits for `amount`.  The bidder's key is part of
+    // it so nobody can copy someone else's commitment and reveal it later.
+    pub fn commitment_for(bidder: &Pubkey, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[bidder.as_ref(), &amount.to_le_bytes(), salt]).to_bytes()
+    }
+}
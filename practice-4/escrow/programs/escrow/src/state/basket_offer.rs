@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::MAX_BASKET_LEGS;
+
+// Swaps several token A mints for several token B mints in one go.  Every
+// offered mint sits in its own vault: the basket offer's ATA for that mint.
+#[account]
+#[derive(InitSpace)]
+pub struct BasketOffer {
+    pub id: u64,
+    pub maker: Pubkey,
+    #[max_len(MAX_BASKET_LEGS)]
+    pub offered: Vec<BasketLeg>,
+    #[max_len(MAX_BASKET_LEGS)]
+    pub wanted: Vec<BasketLeg>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct BasketLeg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// What the basket flows read of a leg, so `FixedBasketOffer`'s legs are used
+// in place rather than copied into `BasketLeg`s.
+pub trait Leg {
+    fn mint(&self) -> Pubkey;
+    fn amount(&self) -> u64;
+}
+
+impl Leg for BasketLeg {
+    fn mint(&self) -> Pubkey {
+        self.mint
+    }
+
+    fn amount(&self) -> u64 {
+        self.amount
+    }
+}
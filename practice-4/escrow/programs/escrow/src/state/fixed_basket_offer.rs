@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::{BasketLeg, Leg, MAX_BASKET_LEGS};
+
+// Zero-copy twin of `BasketOffer` for makers trading many legs at volume.
+// Instructions read the legs straight out of the account data through an
+// `AccountLoader` instead of deserializing them with Borsh on every call,
+// and still check the account's discriminator.  Only the first
+// `offered_count` and `wanted_count` legs are used, the rest stay zeroed.
+#[account(zero_copy)]
+pub struct FixedBasketOffer {
+    pub id: u64,
+    pub maker: Pubkey,
+    pub offered: [FixedBasketLeg; MAX_BASKET_LEGS],
+    pub wanted: [FixedBasketLeg; MAX_BASKET_LEGS],
+    pub offered_count: u8,
+    pub wanted_count: u8,
+    pub bump: u8,
+    // Keeps the size a multiple of 8 without implicit padding.
+    pub padding: [u8; 5],
+}
+
+#[zero_copy]
+pub struct FixedBasketLeg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl FixedBasketOffer {
+    pub const SPACE: usize = std::mem::size_of::<Self>();
+
+    pub fn offered_legs(&self) -> &[FixedBasketLeg] {
+        &self.offered[..self.offered_count as usize]
+    }
+
+    pub fn wanted_legs(&self) -> &[FixedBasketLeg] {
+        &self.wanted[..self.wanted_count as usize]
+    }
+
+    // Stores legs already checked by `basket::check_legs`.
+    pub fn set_legs(&mut self, offered: &[BasketLeg], wanted: &[BasketLeg]) {
+        for (slot, leg) in self.offered.iter_mut().zip(offered) {
+            *slot = FixedBasketLeg::from(leg);
+        }
+        for (slot, leg) in self.wanted.iter_mut().zip(wanted) {
+            *slot = FixedBasketLeg::from(leg);
+        }
+        self.offered_count = offered.len() as u8;
+        self.wanted_count = wanted.len() as u8;
+    }
+}
+
+impl From<&BasketLeg> for FixedBasketLeg {
+    fn from(leg: &BasketLeg) -> Self {
+        Self {
+            mint: leg.mint,
+            amount: leg.amount,
+        }
+    }
+}
+
+impl Leg for FixedBasketLeg {
+    fn mint(&self) -> Pubkey {
+        self.mint
+    }
+
+    fn amount(&self) -> u64 {
+        self.amount
+    }
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+// Keeps the offer ids of a maker's ladders increasing, used by
+// `make_offer_ladder`.  Makers pick their own ids for `make_offer`, so clients
+// skip those already taken.
+#[account]
+#[derive(InitSpace)]
+pub struct MakerCounter {
+    pub maker: Pubkey,
+    pub next_offer_id: u64,
+    pub bump: u8,
+}
+
+impl MakerCounter {
+    // Takes `id` and every id before it.
+    pub fn take_id(&mut self, id: u64) -> Result<()> {
+        require!(id >= self.next_offer_id, ErrorCode::InvalidLadderOfferId);
+        self.next_offer_id = id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+// Key a maker lets close or reprice all of its offers, such as an order
+// management bot.  It can never move tokens anywhere but the maker's own
+// ATAs.
+#[account]
+#[derive(InitSpace)]
+pub struct MakerDelegate {
+    pub maker: Pubkey,
+    // `None` once the maker revokes it.
+    pub delegate: Option<Pubkey>,
+    pub bump: u8,
+}
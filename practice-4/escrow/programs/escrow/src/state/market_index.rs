@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, OFFERS_PER_INDEX_PAGE};
+
+// One page of the open offers of a `(token_mint_a, token_mint_b)` pair.
+//
+// Pages are created in order starting at 0 and each links to the next, so
+// clients can list a market with `getAccountInfo` calls alone: start at page
+// 0 and follow `next_page` until it is `None`.  Offers are removed with a
+// swap-remove, so the order within a page carries no meaning.
+#[account]
+#[derive(InitSpace)]
+pub struct MarketIndexPage {
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub page: u32,
+    pub next_page: Option<u32>,
+    #[max_len(OFFERS_PER_INDEX_PAGE)]
+    pub offers: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl MarketIndexPage {
+    // Fills in a freshly created page and links it from the page before it,
+    // no-op for pages that already exist.
+    pub fn init_if_new(
+        &mut self,
+        token_mint_a: Pubkey,
+        token_mint_b: Pubkey,
+        page: u32,
+        bump: u8,
+        previous_page: Option<&mut MarketIndexPage>,
+    ) -> Result<()> {
+        if self.token_mint_a != Pubkey::default() {
+            return Ok(());
+        }
+
+        if page > 0 {
+            let previous_page = previous_page.ok_or(ErrorCode::InvalidIndexPage)?;
+            require!(
+                previous_page.next_page.is_none(),
+                ErrorCode::InvalidIndexPage
+            );
+            previous_page.next_page = Some(page);
+        }
+
+        self.token_mint_a = token_mint_a;
+        self.token_mint_b = token_mint_b;
+        self.page = page;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn insert(&mut self, offer: Pubkey) -> Result<()> {
+        require!(
+            self.offers.len() < OFFERS_PER_INDEX_PAGE,
+            ErrorCode::IndexPageFull
+        );
+        self.offers.push(offer);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, offer: &Pubkey) {
+        if let Some(position) = self.offers.iter().position(|key| key == offer) {
+            self.offers.swap_remove(position);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::PRICE_SCALE;
+
+// Running statistics for one `(token_mint_a, token_mint_b)` pair, kept up to
+// date by `make_offer`, `take_offer` and `close_offer` and readable through
+// `get_market_stats`.
+//
+// Offers count towards the pair of their primary token B mint.  Fills paid
+// in one of the offer's other payment mints add to `volume_a` only, as their
+// token B amounts are in a different unit.
+#[account]
+#[derive(InitSpace)]
+pub struct MarketStats {
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub open_offers: u64,
+    pub volume_a: u128,
+    pub volume_b: u128,
+    // Token B paid per token A in the last fill, scaled by `PRICE_SCALE`.
+    pub last_price: u64,
+    pub last_trade_timestamp: i64,
+    pub bump: u8,
+}
+
+impl MarketStats {
+    pub fn record_open(&mut self, token_mint_a: Pubkey, token_mint_b: Pubkey, bump: u8) {
+        self.token_mint_a = token_mint_a;
+        self.token_mint_b = token_mint_b;
+        self.bump = bump;
+        self.open_offers = self.open_offers.saturating_add(1);
+    }
+
+    pub fn record_close(&mut self) {
+        self.open_offers = self.open_offers.saturating_sub(1);
+    }
+
+    // `token_b_amount` is `None` for fills paid in another payment mint.
+    pub fn record_fill(&mut self, token_a_amount: u64, token_b_amount: Option<u64>, now: i64) {
+        self.volume_a = self.volume_a.saturating_add(token_a_amount as u128);
+        if let Some(token_b_amount) = token_b_amount {
+            self.volume_b = self.volume_b.saturating_add(token_b_amount as u128);
+            let price = token_b_amount as u128 * PRICE_SCALE as u128 / token_a_amount as u128;
+            self.last_price = u64::try_from(price).unwrap_or(u64::MAX);
+        }
+        self.last_trade_timestamp = now;
+    }
+}
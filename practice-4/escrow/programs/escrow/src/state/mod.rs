@@ -1,2 +1,44 @@
 pub mod offer;
 pub use offer::*;
+
+pub mod taker_fill;
+pub use taker_fill::*;
+
+pub mod basket_offer;
+pub use basket_offer::*;
+
+pub mod fixed_basket_offer;
+pub use fixed_basket_offer::*;
+
+pub mod market_stats;
+pub use market_stats::*;
+
+pub mod reputation;
+pub use reputation::*;
+
+pub mod market_index;
+pub use market_index::*;
+
+pub mod maker_counter;
+pub use maker_counter::*;
+
+pub mod order_book;
+pub use order_book::*;
+
+pub mod user_balance;
+pub use user_balance::*;
+
+pub mod vesting;
+pub use vesting::*;
+
+pub mod referrer;
+pub use referrer::*;
+
+pub mod auction;
+pub use auction::*;
+
+pub mod signer_set;
+pub use signer_set::*;
+
+pub mod maker_delegate;
+pub use maker_delegate::*;
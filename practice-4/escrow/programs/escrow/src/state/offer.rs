@@ -1,5 +1,18 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
 
+use crate::{
+    error::ErrorCode, MakerDelegate, ReputationRequirement, ANCHOR_DISCRIMINATOR,
+    MAX_PAYMENT_OPTIONS, OFFER_RESERVED_SPACE, OFFER_VERSION,
+};
+
+// The fields up to `bump` are the original, unversioned layout (see
+// `LegacyOffer`) and keep their offsets, so existing `memcmp` filters still
+// match.  New fields go at the end, in the order they were added and carved
+// out of `reserved`, never in between: zeroed bytes decode as `None`, empty
+// or zero, so older offers read back with every new option switched off.
+// The maker's `OfferTerms` are stored flat for the same reason, as a field
+// added to the struct would shift everything after it.
 #[account]
 #[derive(InitSpace)]
 pub struct Offer {
@@ -9,4 +22,272 @@ pub struct Offer {
     pub token_mint_b: Pubkey,
     pub token_b_wanted_amount: u64,
     pub bump: u8,
+    pub version: u8,
+    // The vault balance shrinks with every partial take, so the original
+    // deposit is kept to price each fill against the maker's full quote.
+    pub token_a_offered_amount: u64,
+    pub allowlist: Option<Allowlist>,
+    #[max_len(MAX_PAYMENT_OPTIONS)]
+    pub payment_options: Vec<PaymentOption>,
+    pub rent_receiver: Option<Pubkey>,
+    pub min_taker_reputation: Option<ReputationRequirement>,
+    // `MarketIndexPage` listing this offer while it is open.
+    pub index_page: u32,
+    pub tranches: Option<TrancheSchedule>,
+    pub vesting: Option<VestingSchedule>,
+    pub max_referral_bps: u16,
+    pub pay_royalties: bool,
+    pub price_trigger: Option<PriceTrigger>,
+    // May close or reprice the offer for the maker, see `set_offer_delegate`.
+    pub delegate: Option<Pubkey>,
+    pub issue_receipts: bool,
+    pub reserved: [u8; OFFER_RESERVED_SPACE],
+}
+
+// Offer layout written before versioning was introduced.  Such offers, and
+// those at an older version, have to go through `migrate_offer` before they
+// can be taken or closed.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyOffer {
+    pub id: u64,
+    pub maker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_b_wanted_amount: u64,
+    pub bump: u8,
+}
+
+impl LegacyOffer {
+    pub const SPACE: usize = ANCHOR_DISCRIMINATOR + 8 + 32 * 3 + 8 + 1;
+    // Offers at version 1 also start with these fields, and had 64 bytes of
+    // optional fields where version 2 has 256.
+    pub const VERSION_ONE_SPACE: usize = ANCHOR_DISCRIMINATOR + Offer::INIT_SPACE - (256 - 64);
+}
+
+// Optional conditions a maker can attach to an offer when creating it.  The
+// offer keeps each of them as a field of its own, see `Offer`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct OfferTerms {
+    pub allowlist: Option<Allowlist>,
+    // Other token B mints the maker is equally happy to be paid in.  The
+    // maker's ATA for each has to exist before it can be paid with.
+    #[max_len(MAX_PAYMENT_OPTIONS)]
+    pub payment_options: Vec<PaymentOption>,
+    // Receives the rent of the offer and its vault once they are closed,
+    // instead of the maker who paid it.
+    pub rent_receiver: Option<Pubkey>,
+    // Track record a taker's `Reputation` must show before filling.
+    pub min_taker_reputation: Option<ReputationRequirement>,
+    // Releases the offered tokens gradually instead of all at once.
+    pub tranches: Option<TrancheSchedule>,
+    // Delivers token A to takers through a `Vesting` account instead of
+    // straight to their ATA.
+    pub vesting: Option<VestingSchedule>,
+    // Most a `Referrer` may take out of the token B paid to the maker.  Zero
+    // switches referral fees off.
+    pub max_referral_bps: u16,
+    // Pays the `seller_fee_basis_points` share of token B set in token A's
+    // Metaplex metadata to its verified creators, as the collection asks.
+    pub pay_royalties: bool,
+    // Keeps the offer untakeable until an oracle price crosses a threshold,
+    // for stop-loss and take-profit orders.
+    pub price_trigger: Option<PriceTrigger>,
+    // Mints a non-transferable Token-2022 receipt of every take to both the
+    // maker and the taker, as proof of settlement.
+    pub issue_receipts: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Allowlist {
+    // Root of a `crate::merkle` tree built over the allowed taker wallets.
+    pub root: [u8; 32],
+    // Maximum amount of token A a single taker may fill across all takes.
+    pub per_taker_cap: Option<u64>,
+}
+
+// `tranche_amount` of token A unlocks at `start_timestamp` and again every
+// `interval_seconds` after it.  Unlocked tokens stay takeable until filled.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct TrancheSchedule {
+    pub start_timestamp: i64,
+    pub interval_seconds: i64,
+    pub tranche_amount: u64,
+}
+
+// Both measured from the take.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct VestingSchedule {
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+}
+
+// `threshold` is in `price_feed`'s own fixed-point units.  Takes are
+// rejected while the price is on the wrong side of it, or older than
+// `max_staleness_seconds`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PriceTrigger {
+    pub price_feed: Pubkey,
+    pub direction: TriggerDirection,
+    pub threshold: i64,
+    pub max_staleness_seconds: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum TriggerDirection {
+    // At or above the threshold, for take-profit orders.
+    Above,
+    // At or below the threshold, for stop-loss orders.
+    Below,
+}
+
+impl PriceTrigger {
+    pub fn is_met(&self, price: i64) -> bool {
+        match self.direction {
+            TriggerDirection::Above => price >= self.threshold,
+            TriggerDirection::Below => price <= self.threshold,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PaymentOption {
+    pub mint: Pubkey,
+    pub wanted_amount: u64,
+}
+
+impl Offer {
+    // A new offer at the current `OFFER_VERSION`, with no delegate.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: u64,
+        maker: Pubkey,
+        token_mint_a: Pubkey,
+        token_mint_b: Pubkey,
+        token_b_wanted_amount: u64,
+        bump: u8,
+        token_a_offered_amount: u64,
+        terms: OfferTerms,
+        index_page: u32,
+    ) -> Self {
+        Self {
+            id,
+            maker,
+            token_mint_a,
+            token_mint_b,
+            token_b_wanted_amount,
+            bump,
+            version: OFFER_VERSION,
+            token_a_offered_amount,
+            allowlist: terms.allowlist,
+            payment_options: terms.payment_options,
+            rent_receiver: terms.rent_receiver,
+            min_taker_reputation: terms.min_taker_reputation,
+            index_page,
+            tranches: terms.tranches,
+            vesting: terms.vesting,
+            max_referral_bps: terms.max_referral_bps,
+            pay_royalties: terms.pay_royalties,
+            price_trigger: terms.price_trigger,
+            delegate: None,
+            issue_receipts: terms.issue_receipts,
+            reserved: [0; OFFER_RESERVED_SPACE],
+        }
+    }
+
+    // The offer's vault must be its canonical ATA for token A, never just any
+    // token account the offer happens to own.
+    pub fn vault_address(offer: &Pubkey, token_mint_a: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(offer, token_mint_a, token_program)
+    }
+
+    // Whether `signer` may close or reprice the offer for its maker, as the
+    // offer's own delegate or as the one the maker set for all its offers.
+    pub fn is_delegate(&self, signer: &Pubkey, maker_delegate: Option<&MakerDelegate>) -> bool {
+        self.delegate == Some(*signer)
+            || maker_delegate.is_some_and(|maker_delegate| maker_delegate.delegate == Some(*signer))
+    }
+
+    // Fills are priced against the full quote, so this reprices whatever is
+    // still in the vault.  Payment options move by the same factor, rounded
+    // up like fills, so none of them is left at the old price.
+    pub fn reprice(&mut self, token_b_wanted_amount: u64) -> Result<()> {
+        require!(token_b_wanted_amount > 0, ErrorCode::InvalidAmount);
+        let old_amount = self.token_b_wanted_amount as u128;
+        for option in &mut self.payment_options {
+            require!(old_amount > 0, ErrorCode::InvalidAmount);
+            let amount = (option.wanted_amount as u128)
+                .checked_mul(token_b_wanted_amount as u128)
+                .ok_or(ErrorCode::MathOverflow)?
+                .div_ceil(old_amount);
+            option.wanted_amount = u64::try_from(amount).map_err(|_| ErrorCode::MathOverflow)?;
+        }
+        self.token_b_wanted_amount = token_b_wanted_amount;
+        Ok(())
+    }
+
+    pub fn rent_receiver(&self) -> Pubkey {
+        self.rent_receiver.unwrap_or(self.maker)
+    }
+
+    // Account to close the offer and its vault to.  Instructions take the
+    // rent receiver as an optional account, only needed when it is not the
+    // maker.
+    pub fn rent_destination<'info>(
+        &self,
+        maker: AccountInfo<'info>,
+        rent_receiver: Option<AccountInfo<'info>>,
+    ) -> Result<AccountInfo<'info>> {
+        let destination = rent_receiver.unwrap_or(maker);
+        require_keys_eq!(
+            destination.key(),
+            self.rent_receiver(),
+            ErrorCode::InvalidRentReceiver
+        );
+        Ok(destination)
+    }
+
+    // Token A released to takers by `now` under `tranches`, including what
+    // has been filled already.
+    pub fn unlocked_amount(&self, tranches: &TrancheSchedule, now: i64) -> u64 {
+        if now < tranches.start_timestamp {
+            return 0;
+        }
+        let elapsed_tranches =
+            (now.saturating_sub(tranches.start_timestamp) / tranches.interval_seconds) as u64;
+        tranches
+            .tranche_amount
+            .saturating_mul(elapsed_tranches.saturating_add(1))
+            .min(self.token_a_offered_amount)
+    }
+
+    // Full-offer price in `mint`, or `None` if the offer does not accept it.
+    pub fn wanted_amount_in(&self, mint: &Pubkey) -> Option<u64> {
+        if *mint == self.token_mint_b {
+            return Some(self.token_b_wanted_amount);
+        }
+        self.payment_options
+            .iter()
+            .find(|option| option.mint == *mint)
+            .map(|option| option.wanted_amount)
+    }
+
+    // Amount of `mint` the taker pays for `token_a_amount`, rounded up so
+    // partial fills never undercut the maker's price.
+    pub fn token_b_amount_for(&self, mint: &Pubkey, token_a_amount: u64) -> Result<u64> {
+        let wanted_amount = self
+            .wanted_amount_in(mint)
+            .ok_or(ErrorCode::PaymentMintNotAccepted)?;
+
+        let numerator = (token_a_amount as u128)
+            .checked_mul(wanted_amount as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let denominator = self.token_a_offered_amount as u128;
+        require!(denominator > 0, ErrorCode::InvalidAmount);
+
+        let amount = numerator
+            .checked_add(denominator - 1)
+            .ok_or(ErrorCode::MathOverflow)?
+            / denominator;
+        u64::try_from(amount).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
 }
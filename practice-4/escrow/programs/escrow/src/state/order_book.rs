@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+use crate::{error::ErrorCode, MAX_BOOK_SIDE_ORDERS, PRICE_SCALE};
+
+// Order book mode for one `(token_mint_a, token_mint_b)` pair, alongside the
+// standalone `Offer` flow.  Token A is the base and token B the quote: asks
+// sell token A, bids buy it.  Everything deposited by orders sits in the
+// book's two vaults, its ATAs for each mint, until `withdraw_balance`.
+#[account]
+#[derive(InitSpace)]
+pub struct OrderBook {
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    // Sequence number of the next order placed; orders placed earlier have
+    // lower ids and so time priority.
+    pub next_order_id: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Order {
+    pub order_id: u64,
+    pub owner: Pubkey,
+    // Token B per token A, scaled by `PRICE_SCALE`.
+    pub price: u64,
+    // Token A still to be bought or sold.
+    pub token_a_amount: u64,
+    // Token B deposited by a bid and not yet paid out; always 0 for asks.
+    pub token_b_locked: u64,
+}
+
+// The resting orders of one side of a book, best first: highest price for
+// bids, lowest for asks, and the earliest order among equal prices.
+#[account]
+#[derive(InitSpace)]
+pub struct BookSide {
+    pub order_book: Pubkey,
+    pub side: Side,
+    #[max_len(MAX_BOOK_SIDE_ORDERS)]
+    pub orders: Vec<Order>,
+    pub bump: u8,
+}
+
+impl OrderBook {
+    pub fn vault_address(order_book: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(order_book, mint, token_program)
+    }
+
+    // Mint an order on `side` deposits: token A for asks, token B for bids.
+    pub fn deposit_mint(&self, side: Side) -> Pubkey {
+        match side {
+            Side::Ask => self.token_mint_a,
+            Side::Bid => self.token_mint_b,
+        }
+    }
+
+    pub fn take_next_order_id(&mut self) -> Result<u64> {
+        let order_id = self.next_order_id;
+        self.next_order_id = order_id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        Ok(order_id)
+    }
+}
+
+impl Side {
+    pub fn seed(&self) -> &'static [u8] {
+        match self {
+            Side::Bid => b"bids",
+            Side::Ask => b"asks",
+        }
+    }
+}
+
+// Token B worth `token_a_amount` at `price`.  Bids deposit the amount rounded
+// up and fills pay it rounded down, so a bid's deposit always covers its
+// fills.
+pub fn token_b_amount_at(token_a_amount: u64, price: u64, round_up: bool) -> Result<u64> {
+    let numerator = (token_a_amount as u128)
+        .checked_mul(price as u128)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let scale = PRICE_SCALE as u128;
+    let amount = if round_up {
+        numerator.div_ceil(scale)
+    } else {
+        numerator / scale
+    };
+    u64::try_from(amount).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+impl BookSide {
+    // Whether `order` comes before `other` in this side's priority.
+    fn ranks_before(&self, order: &Order, other: &Order) -> bool {
+        match self.side {
+            Side::Bid => order.price > other.price,
+            Side::Ask => order.price < other.price,
+        }
+    }
+
+    // On a full side the worst order makes way for one that ranks before it,
+    // so resting orders nobody will fill cannot lock the side.  The evicted
+    // order is returned for its deposit to be refunded.
+    pub fn insert(&mut self, order: Order) -> Result<Option<Order>> {
+        let evicted = if self.orders.len() < MAX_BOOK_SIDE_ORDERS {
+            None
+        } else {
+            let worst = self.orders.last().ok_or(ErrorCode::BookSideFull)?;
+            require!(self.ranks_before(&order, worst), ErrorCode::BookSideFull);
+            self.orders.pop()
+        };
+        // Behind every order at the same price, which were placed earlier.
+        let position = self
+            .orders
+            .iter()
+            .position(|other| self.ranks_before(&order, other))
+            .unwrap_or(self.orders.len());
+        self.orders.insert(position, order);
+        Ok(evicted)
+    }
+
+    pub fn remove(&mut self, order_id: u64, owner: &Pubkey) -> Result<Order> {
+        let position = self
+            .orders
+            .iter()
+            .position(|order| order.order_id == order_id && order.owner == *owner)
+            .ok_or(ErrorCode::OrderNotFound)?;
+        Ok(self.orders.remove(position))
+    }
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, MAX_BPS};
+
+// A frontend that brings takers to the escrow, paid a share of the token B
+// of every take it refers.
+#[account]
+#[derive(InitSpace)]
+pub struct Referrer {
+    pub wallet: Pubkey,
+    // Share the referrer asks for, cut down to each offer's
+    // `max_referral_bps`.
+    pub fee_bps: u16,
+    pub referred_takes: u64,
+    // Token B paid out to the referrer across all mints, in raw units.
+    pub total_earned: u128,
+    pub bump: u8,
+}
+
+impl Referrer {
+    // Token B out of `token_b_amount` owed to the referrer on an offer that
+    // allows at most `max_referral_bps`, rounded down in the maker's favour.
+    pub fn share_of(&self, token_b_amount: u64, max_referral_bps: u16) -> u64 {
+        let bps = self.fee_bps.min(max_referral_bps);
+        (token_b_amount as u128 * bps as u128 / MAX_BPS as u128) as u64
+    }
+
+    // Takers cannot refer themselves to win back part of the price.
+    pub fn record_referral(&mut self, taker: &Pubkey, amount: u64) -> Result<()> {
+        require_keys_neq!(self.wallet, *taker, ErrorCode::SelfReferral);
+        self.referred_takes = self
+            .referred_takes
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.total_earned = self
+            .total_earned
+            .checked_add(amount as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+// Trading history of one wallet, kept by the escrow itself so counterparties
+// can check it without trusting an indexer.
+#[account]
+#[derive(InitSpace)]
+pub struct Reputation {
+    pub wallet: Pubkey,
+    // Fills of at least one whole token A on other makers' offers.
+    pub takes: u64,
+    // Fills on this wallet's own offers.
+    pub sales: u64,
+    pub cancellations: u64,
+    // Token A moved through all of the above fills, in raw units.
+    pub volume: u128,
+    pub first_seen: i64,
+    pub bump: u8,
+}
+
+// Minimum track record a maker can require from takers.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ReputationRequirement {
+    pub min_takes: u64,
+    pub min_age_seconds: i64,
+}
+
+impl Reputation {
+    // Fills in the identity of a freshly created account, no-op afterwards.
+    pub fn init_if_new(&mut self, wallet: Pubkey, bump: u8, now: i64) {
+        if self.wallet == Pubkey::default() {
+            self.wallet = wallet;
+            self.first_seen = now;
+            self.bump = bump;
+        }
+    }
+
+    pub fn meets(&self, requirement: &ReputationRequirement, now: i64) -> bool {
+        self.takes >= requirement.min_takes
+            && now.saturating_sub(self.first_seen) >= requirement.min_age_seconds
+    }
+
+    // Dust fills add to the volume but not to `takes`, which offers can
+    // require a minimum of, so that record cannot be farmed for free.
+    pub fn record_take(&mut self, token_a_amount: u64, token_a_decimals: u8) {
+        if token_a_amount >= 10u64.saturating_pow(token_a_decimals as u32) {
+            self.takes = self.takes.saturating_add(1);
+        }
+        self.volume = self.volume.saturating_add(token_a_amount as u128);
+    }
+
+    pub fn record_sale(&mut self, token_a_amount: u64) {
+        self.sales = self.sales.saturating_add(1);
+        self.volume = self.volume.saturating_add(token_a_amount as u128);
+    }
+
+    pub fn record_cancellation(&mut self) {
+        self.cancellations = self.cancellations.saturating_add(1);
+    }
+}
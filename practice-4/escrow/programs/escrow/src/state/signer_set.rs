@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, OfferTerms, MAX_SIGNER_SET_MEMBERS};
+
+// m-of-n group of wallets acting as a single maker, such as a treasury
+// multisig.  Offers made on its behalf have the set's authority as their
+// maker: a PDA without data, so it holds lamports and tokens and pays rent
+// like a wallet, and the escrow signs for it once a `Proposal` is approved.
+#[account]
+#[derive(InitSpace)]
+pub struct SignerSet {
+    pub creator: Pubkey,
+    pub id: u64,
+    #[max_len(MAX_SIGNER_SET_MEMBERS)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub next_proposal_id: u64,
+    pub authority_bump: u8,
+    pub bump: u8,
+}
+
+// An action the signer set's authority takes once `threshold` members have
+// approved it.  The proposer's approval is counted from the start.
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub signer_set: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    #[max_len(MAX_SIGNER_SET_MEMBERS)]
+    pub approvals: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+// Arguments of the escrow instruction to run as the authority, plus the
+// accounts it acts on that its arguments do not pin down.  `InitSpace`
+// cannot size a boxed `OfferTerms`, so `MakeOffer` stays inline.
+#[allow(clippy::large_enum_variant)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub enum ProposalAction {
+    MakeOffer {
+        id: u64,
+        token_mint_a: Pubkey,
+        token_mint_b: Pubkey,
+        token_a_offered_amount: u64,
+        token_b_wanted_amount: u64,
+        terms: OfferTerms,
+        index_page: u32,
+    },
+    AmendOffer {
+        offer: Pubkey,
+        token_b_wanted_amount: u64,
+    },
+    CloseOffer {
+        offer: Pubkey,
+    },
+}
+
+impl SignerSet {
+    pub fn authority_address(signer_set: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"authority", signer_set.as_ref()], &crate::ID)
+    }
+
+    pub fn is_member(&self, wallet: &Pubkey) -> bool {
+        self.members.contains(wallet)
+    }
+}
+
+impl Proposal {
+    pub fn approve(&mut self, member: Pubkey) -> Result<()> {
+        require!(
+            !self.approvals.contains(&member),
+            ErrorCode::AlreadyApproved
+        );
+        self.approvals.push(member);
+        Ok(())
+    }
+}
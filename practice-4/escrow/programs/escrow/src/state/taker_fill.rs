@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+// Running total of what one taker has filled on one offer, used to enforce
+// the allowlist's per-taker cap across partial takes.
+#[account]
+#[derive(InitSpace)]
+pub struct TakerFill {
+    pub offer: Pubkey,
+    pub taker: Pubkey,
+    pub filled_amount: u64,
+}
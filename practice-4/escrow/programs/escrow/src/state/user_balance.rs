@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Order, Side};
+
+// Tokens one user can withdraw from an `OrderBook`: proceeds of their
+// matched orders and whatever their cancelled orders had deposited.
+#[account]
+#[derive(InitSpace)]
+pub struct UserBalance {
+    pub order_book: Pubkey,
+    pub owner: Pubkey,
+    pub token_a_free: u64,
+    pub token_b_free: u64,
+    pub bump: u8,
+}
+
+impl UserBalance {
+    pub fn credit_token_a(&mut self, amount: u64) -> Result<()> {
+        self.token_a_free = self
+            .token_a_free
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn credit_token_b(&mut self, amount: u64) -> Result<()> {
+        self.token_b_free = self
+            .token_b_free
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    // Credits back what `order`, taken off the book's `side`, had deposited
+    // and not yet spent.
+    pub fn refund(&mut self, side: Side, order: &Order) -> Result<()> {
+        match side {
+            Side::Ask => self.credit_token_a(order.token_a_amount),
+            Side::Bid => self.credit_token_b(order.token_b_locked),
+        }
+    }
+}
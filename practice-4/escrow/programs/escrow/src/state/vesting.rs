@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+// Token A bought from a vesting offer, held in this account's ATA and
+// released to the taker through `claim_vested`.
+//
+// There is one per offer and taker at a time.  A later take by the same
+// taker restarts its schedule with the new token A added, rather than
+// vesting it on the old one.  It is closed once fully claimed.
+#[account]
+#[derive(InitSpace)]
+pub struct Vesting {
+    pub offer: Pubkey,
+    pub taker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub start_timestamp: i64,
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+    pub bump: u8,
+}
+
+impl Vesting {
+    // Token A vested by `now`, claimed or not: nothing before the cliff, then
+    // linearly up to `total_amount` at the end of the duration.
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start_timestamp);
+        if elapsed < self.cliff_seconds {
+            return 0;
+        }
+        if elapsed >= self.duration_seconds {
+            return self.total_amount;
+        }
+        (self.total_amount as u128 * elapsed as u128 / self.duration_seconds as u128) as u64
+    }
+}
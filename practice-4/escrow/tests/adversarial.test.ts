@@ -0,0 +1,390 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+import {
+  EscrowBankrun,
+  closeOfferAccounts,
+  createTokenAndMintTo as createBankrunTokenAndMintTo,
+  fundWallets,
+  makeOffer as makeBankrunOffer,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+// Every test swaps one account of an otherwise valid `make_offer`,
+// `take_offer` or `close_offer` call for a look-alike and expects the program
+// to refuse it.
+describe("escrow account validation", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+
+  const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  type Offer = {
+    maker: Keypair;
+    id: BN;
+    address: PublicKey;
+    vault: PublicKey;
+  };
+
+  const offerAddressFor = (maker: Keypair, offerId: BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        maker.publicKey.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  // `amount` tells apart otherwise identical calls, which the validator
+  // would reject as duplicates.
+  const makeOfferCall = (
+    maker: Keypair,
+    offerId: BN,
+    overrides: Record<string, PublicKey> = {},
+    amount = 1_000_000
+  ) =>
+    program.methods
+      .makeOffer(
+        offerId,
+        new BN(amount),
+        new BN(amount),
+        defaultOfferTerms(),
+        0
+      )
+      .accounts({
+        maker: maker.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+        ...overrides,
+      } as any)
+      .signers([maker])
+      .rpc();
+
+  const makeOffer = async (maker: Keypair): Promise<Offer> => {
+    const offerId = getRandomBigNumber();
+    await makeOfferCall(maker, offerId);
+    const address = offerAddressFor(maker, offerId);
+    return {
+      maker,
+      id: offerId,
+      address,
+      vault: ata(usdcMint.publicKey, address),
+    };
+  };
+
+  let aliceOffer: Offer;
+  let aliceOtherOffer: Offer;
+  let bobOffer: Offer;
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [alice, bob].map((owner) => ({
+          recepient: owner.publicKey,
+          amount: 100_000_000,
+        }))
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [alice, bob].map((owner) => ({
+          recepient: owner.publicKey,
+          amount: 100_000_000,
+        }))
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+
+    aliceOffer = await makeOffer(alice);
+    aliceOtherOffer = await makeOffer(alice);
+    bobOffer = await makeOffer(bob);
+  });
+
+  const takeOffer = (overrides: Record<string, PublicKey>) =>
+    program.methods
+      .takeOffer(new BN(1_000_000), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          maker: alice.publicKey,
+          offer: aliceOffer.address,
+          vault: aliceOffer.vault,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: wifMint.publicKey,
+          ...overrides,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  const closeOffer = (signer: Keypair, overrides: Record<string, PublicKey>) =>
+    program.methods
+      .closeOffer()
+      .accounts(
+        closeOfferAccounts({
+          maker: alice.publicKey,
+          offer: aliceOffer.address,
+          vault: aliceOffer.vault,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: wifMint.publicKey,
+          makerTokenAccountA: ata(usdcMint.publicKey, alice.publicKey),
+          ...overrides,
+        })
+      )
+      .signers([signer])
+      .rpc();
+
+  test("Make rejects an offer address its seeds do not derive", async () => {
+    // The address of another id, so the offer would not be found again.
+    await expect(
+      makeOfferCall(alice, getRandomBigNumber(), {
+        offer: offerAddressFor(alice, getRandomBigNumber()),
+      })
+    ).rejects.toThrow(/ConstraintSeeds/);
+  });
+
+  test("Make rejects an offer id the maker already used", async () => {
+    await expect(
+      makeOfferCall(alice, aliceOffer.id, {}, 2_000_000)
+    ).rejects.toThrow(/already in use/);
+  });
+
+  test("Make rejects a vault that is not the new offer's ATA", async () => {
+    const offerId = getRandomBigNumber();
+    await expect(
+      makeOfferCall(alice, offerId, {
+        vault: Keypair.generate().publicKey,
+      })
+    ).rejects.toThrow(/seeds do not result in a valid address/);
+  });
+
+  test("Make rejects a token A account of another wallet", async () => {
+    // Bob's USDC would be deposited with Alice signing as its owner.
+    await expect(
+      makeOfferCall(alice, getRandomBigNumber(), {
+        makerTokenAccountA: ata(usdcMint.publicKey, bob.publicKey),
+      })
+    ).rejects.toThrow(/ConstraintTokenOwner/);
+  });
+
+  test("Make rejects a token A mint that is not a mint", async () => {
+    await expect(
+      makeOfferCall(alice, getRandomBigNumber(), {
+        tokenMintA: bob.publicKey,
+        makerTokenAccountA: ata(usdcMint.publicKey, alice.publicKey),
+      })
+    ).rejects.toThrow(/AccountNotInitialized|AccountOwnedByWrongProgram/);
+  });
+
+  test("Take rejects the vault of another offer", async () => {
+    await expect(takeOffer({ vault: aliceOtherOffer.vault })).rejects.toThrow(
+      /ConstraintTokenOwner/
+    );
+  });
+
+  test("Take rejects an offer made by another maker", async () => {
+    await expect(
+      takeOffer({ offer: bobOffer.address, vault: bobOffer.vault })
+    ).rejects.toThrow(/ConstraintHasOne/);
+  });
+
+  test("Take rejects a maker that does not own the offer", async () => {
+    await expect(takeOffer({ maker: bob.publicKey })).rejects.toThrow(
+      /ConstraintHasOne/
+    );
+  });
+
+  test("Take rejects a substituted token A mint", async () => {
+    await expect(takeOffer({ tokenMintA: wifMint.publicKey })).rejects.toThrow(
+      /ConstraintHasOne/
+    );
+  });
+
+  test("Take rejects an account that is not an offer", async () => {
+    await expect(
+      takeOffer({ offer: Keypair.generate().publicKey })
+    ).rejects.toThrow(/AccountNotInitialized|AccountOwnedByWrongProgram/);
+  });
+
+  test("Take rejects a token account that is not the offer's ATA", async () => {
+    // Bob's own USDC account, correct mint but wrong owner and address.
+    await expect(
+      takeOffer({ vault: ata(usdcMint.publicKey, bob.publicKey) })
+    ).rejects.toThrow(/ConstraintTokenOwner/);
+  });
+
+  test("Close rejects the vault of another offer", async () => {
+    await expect(
+      closeOffer(alice, { vault: aliceOtherOffer.vault })
+    ).rejects.toThrow(/InvalidVault/);
+  });
+
+  test("Close rejects anyone but the maker", async () => {
+    await expect(
+      closeOffer(bob, {
+        maker: bob.publicKey,
+        makerTokenAccountA: ata(usdcMint.publicKey, bob.publicKey),
+      })
+    ).rejects.toThrow(/ConstraintHasOne/);
+  });
+
+  test("Untouched offer can still be taken with the genuine accounts", async () => {
+    await takeOffer({});
+    expect(await connection.getAccountInfo(aliceOffer.address)).toBeNull();
+  });
+});
+
+// The offer `seeds` constraint is the only check these accounts fail: each
+// one carries a genuine offer's data, so the maker, mints and vault all
+// line up, but it does not sit at the address its seeds derive.  Bankrun is
+// needed to write such accounts.
+describe("escrow offer seeds", () => {
+  let escrow: EscrowBankrun;
+
+  const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const makeOffer = async () => {
+    const { offerAddress } = await makeBankrunOffer(escrow.program, {
+      maker: alice,
+      tokenMintA: usdcMint.publicKey,
+      tokenMintB: wifMint.publicKey,
+      offeredAmount: new BN(1_000_000),
+      wantedAmount: new BN(1_000_000),
+    });
+    return offerAddress;
+  };
+
+  // Writes `offerAddress`'s account, with `edit` applied to its data, at
+  // `address`.
+  const writeOfferCopy = async (
+    offerAddress: PublicKey,
+    address: PublicKey,
+    edit: (data: Buffer) => void = () => {}
+  ) => {
+    const { context } = escrow;
+    const account = await context.banksClient.getAccount(offerAddress);
+    const data = Buffer.from(account.data);
+    edit(data);
+    context.setAccount(address, { ...account, data });
+  };
+
+  const takeOffer = (offer: PublicKey, vault: PublicKey) =>
+    escrow.program.methods
+      .takeOffer(new BN(1_000_000), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          maker: alice.publicKey,
+          offer,
+          vault,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: wifMint.publicKey,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob]);
+
+    await createBankrunTokenAndMintTo(escrow, usdcMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 100_000_000 },
+    ]);
+    await createBankrunTokenAndMintTo(escrow, wifMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 100_000_000 },
+    ]);
+  });
+
+  test("Take rejects a copy of an offer at another address", async () => {
+    const offerAddress = await makeOffer();
+    const copy = Keypair.generate().publicKey;
+    await writeOfferCopy(offerAddress, copy);
+
+    await expect(
+      takeOffer(copy, ata(usdcMint.publicKey, offerAddress))
+    ).rejects.toThrow(/ConstraintSeeds/);
+  });
+
+  test("Close rejects a copy of an offer at another address", async () => {
+    const offerAddress = await makeOffer();
+    const copy = Keypair.generate().publicKey;
+    await writeOfferCopy(offerAddress, copy);
+
+    await expect(
+      escrow.program.methods
+        .closeOffer()
+        .accounts(
+          closeOfferAccounts({
+            maker: alice.publicKey,
+            offer: copy,
+            vault: ata(usdcMint.publicKey, offerAddress),
+            tokenMintA: usdcMint.publicKey,
+            tokenMintB: wifMint.publicKey,
+            makerTokenAccountA: ata(usdcMint.publicKey, alice.publicKey),
+          })
+        )
+        .signers([alice])
+        .rpc()
+    ).rejects.toThrow(/ConstraintSeeds/);
+  });
+
+  test("Take rejects an offer whose id does not derive its address", async () => {
+    const offerAddress = await makeOffer();
+    // The id follows the 8 byte discriminator.
+    await writeOfferCopy(offerAddress, offerAddress, (data) =>
+      getRandomBigNumber().toArrayLike(Buffer, "le", 8).copy(data, 8)
+    );
+
+    await expect(
+      takeOffer(offerAddress, ata(usdcMint.publicKey, offerAddress))
+    ).rejects.toThrow(/ConstraintSeeds/);
+  });
+});
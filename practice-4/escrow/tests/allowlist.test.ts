@@ -0,0 +1,178 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+  getTokenBalanceOn,
+} from "./helpers";
+import { takeOfferAccounts } from "./bankrun";
+import { buildAllowlist } from "./merkle";
+
+describe("escrow allowlist", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+  const getTokenBalance = getTokenBalanceOn(connection);
+
+  const [alice, bob, carol, dave, usdcMint, wifMint] = makeKeypairs(6);
+
+  const allowlist = buildAllowlist([bob.publicKey, carol.publicKey]);
+  const perTakerCap = new BN(10_000_000);
+  const offerId = getRandomBigNumber();
+
+  const [offerAddress] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("offer"),
+      alice.publicKey.toBuffer(),
+      offerId.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+
+  const ata = (mint: Keypair, owner: Keypair) =>
+    getAssociatedTokenAddressSync(
+      mint.publicKey,
+      owner.publicKey,
+      false,
+      TOKEN_PROGRAM
+    );
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob, carol, dave].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [{ recepient: alice.publicKey, amount: 30_000_000 }]
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [bob, carol, dave].map((taker) => ({
+          recepient: taker.publicKey,
+          amount: 500_000_000,
+        }))
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+
+    // 30 USDC for 300 WIF, at most 10 USDC for every allowlisted taker.
+    await program.methods
+      .makeOffer(
+        offerId,
+        new BN(30_000_000),
+        new BN(300_000_000),
+        {
+          ...defaultOfferTerms(),
+          allowlist: {
+            root: Array.from(allowlist.root),
+            perTakerCap,
+          },
+        },
+        0
+      )
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+  });
+
+  const takeOffer = (
+    taker: Keypair,
+    tokenAAmount: BN,
+    proof = allowlist.proofFor(taker.publicKey)
+  ) => {
+    const [takerFill] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("taker_fill"),
+        offerAddress.toBuffer(),
+        taker.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    return program.methods
+      .takeOffer(tokenAAmount, proof)
+      .accounts(
+        takeOfferAccounts({
+          taker: taker.publicKey,
+          offer: offerAddress,
+          tokenMintB: wifMint.publicKey,
+          takerFill,
+        })
+      )
+      .signers([taker])
+      .rpc();
+  };
+
+  test("Allowlisted taker fills the offer in several partial takes", async () => {
+    await takeOffer(bob, new BN(4_000_000));
+    await takeOffer(bob, new BN(6_000_000));
+
+    expect(await getTokenBalance(ata(usdcMint, bob))).toEqual(
+      new BN(10_000_000)
+    );
+    expect(await getTokenBalance(ata(wifMint, bob))).toEqual(
+      new BN(400_000_000)
+    );
+    expect(await getTokenBalance(ata(wifMint, alice))).toEqual(
+      new BN(100_000_000)
+    );
+  });
+
+  test("Per-taker cap holds across partial takes", async () => {
+    await expect(takeOffer(bob, new BN(1))).rejects.toThrow(/TakerCapExceeded/);
+  });
+
+  test("Wallet missing from the allowlist cannot take", async () => {
+    // Borrow Bob's proof: it is valid for Bob's leaf, not for Dave's.
+    await expect(
+      takeOffer(dave, new BN(1_000_000), allowlist.proofFor(bob.publicKey))
+    ).rejects.toThrow(/NotAllowlisted/);
+  });
+
+  test("Offer stays open until the vault is drained", async () => {
+    await takeOffer(carol, new BN(10_000_000));
+
+    const offer = await program.account.offer.fetch(offerAddress);
+    expect(offer.tokenAOfferedAmount).toEqual(new BN(30_000_000));
+    expect(await getTokenBalance(ata(wifMint, alice))).toEqual(
+      new BN(200_000_000)
+    );
+  });
+});
@@ -0,0 +1,114 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  getTokenBalanceOn,
+} from "./helpers";
+
+describe("escrow atomic swap", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+  const getTokenBalance = getTokenBalanceOn(connection);
+
+  const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+
+  const ata = (mint: Keypair, owner: Keypair) =>
+    getAssociatedTokenAddressSync(
+      mint.publicKey,
+      owner.publicKey,
+      false,
+      TOKEN_PROGRAM
+    );
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      // Both parties already hold accounts for both tokens: the swap does
+      // not create any.
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [
+          { recepient: alice.publicKey, amount: 10_000_000 },
+          { recepient: bob.publicKey, amount: 0 },
+        ]
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [
+          { recepient: alice.publicKey, amount: 0 },
+          { recepient: bob.publicKey, amount: 50_000_000 },
+        ]
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+  });
+
+  const atomicSwap = (signers: Array<Keypair>) =>
+    program.methods
+      .atomicSwap(new BN(4_000_000), new BN(20_000_000))
+      .accounts({
+        maker: alice.publicKey,
+        taker: bob.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        makerTokenAccountA: ata(usdcMint, alice),
+        makerTokenAccountB: ata(wifMint, alice),
+        takerTokenAccountA: ata(usdcMint, bob),
+        takerTokenAccountB: ata(wifMint, bob),
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .signers(signers)
+      .rpc();
+
+  test("Both legs move in one transaction", async () => {
+    await atomicSwap([alice, bob]);
+
+    expect(await getTokenBalance(ata(usdcMint, alice))).toEqual(
+      new BN(6_000_000)
+    );
+    expect(await getTokenBalance(ata(usdcMint, bob))).toEqual(
+      new BN(4_000_000)
+    );
+    expect(await getTokenBalance(ata(wifMint, alice))).toEqual(
+      new BN(20_000_000)
+    );
+    expect(await getTokenBalance(ata(wifMint, bob))).toEqual(
+      new BN(30_000_000)
+    );
+  });
+
+  test("Swap needs the taker's signature", async () => {
+    await expect(atomicSwap([alice])).rejects.toThrow(/[Ss]ignature/);
+  });
+});
@@ -0,0 +1,229 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { ComputeBudgetProgram, Keypair, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { createHash, randomBytes } from "crypto";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { TOKEN_PROGRAM, getRandomBigNumber } from "./helpers";
+import {
+  EscrowBankrun,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  getUnixTimestamp,
+  setUnixTimestamp,
+  startEscrow,
+} from "./bankrun";
+
+const HOUR = 60 * 60;
+const USDC = 1_000_000;
+
+// Same hash as `SealedBid::commitment_for`.
+const commitmentFor = (bidder: PublicKey, amount: BN, salt: Buffer) =>
+  Array.from(
+    createHash("sha256")
+      .update(bidder.toBuffer())
+      .update(amount.toArrayLike(Buffer, "le", 8))
+      .update(salt)
+      .digest()
+  );
+
+describe("escrow sealed-bid auctions", () => {
+  let escrow: EscrowBankrun;
+  let createdAt: number;
+  let auctionAddress: PublicKey;
+
+  // Dave commits to a bid and never reveals it.
+  const [alice, bob, carol, dave, apeMint, usdcMint] = makeKeypairs(6);
+  const bid = (amount: number, deposit: number) => ({
+    amount: new BN(amount * USDC),
+    deposit: new BN(deposit * USDC),
+    salt: randomBytes(32),
+  });
+  const bids = new Map<Keypair, ReturnType<typeof bid>>([
+    [bob, bid(120, 200)],
+    [carol, bid(100, 100)],
+    [dave, bid(150, 150)],
+  ]);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const revealBid = (bidder: Keypair, amount: BN, salt: Buffer) =>
+    escrow.program.methods
+      .revealBid(amount, Array.from(salt))
+      .accounts({ bidder: bidder.publicKey, auction: auctionAddress } as any)
+      .signers([bidder])
+      .rpc();
+
+  // Bankrun drops a transaction identical to one it has already seen, so
+  // every attempt asks for a different compute limit.
+  let settleAttempts = 0;
+  const settleAuction = (lotReceiver: PublicKey) =>
+    escrow.program.methods
+      .settleAuction()
+      .accounts({
+        seller: alice.publicKey,
+        lotReceiver,
+        auction: auctionAddress,
+        tokenMintA: apeMint.publicKey,
+        tokenMintB: usdcMint.publicKey,
+        vaultA: ata(apeMint.publicKey, auctionAddress),
+        vaultB: ata(usdcMint.publicKey, auctionAddress),
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({
+          units: 200_000 + ++settleAttempts,
+        }),
+      ])
+      .rpc();
+
+  const settleBid = (bidder: Keypair) =>
+    escrow.program.methods
+      .settleBid()
+      .accounts({
+        bidder: bidder.publicKey,
+        seller: alice.publicKey,
+        auction: auctionAddress,
+        tokenMintB: usdcMint.publicKey,
+        vaultB: ata(usdcMint.publicKey, auctionAddress),
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .rpc();
+
+  const usdcBalance = (owner: PublicKey) =>
+    getTokenBalance(escrow, ata(usdcMint.publicKey, owner));
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob, carol, dave]);
+
+    await createTokenAndMintTo(escrow, apeMint, 0, alice, [
+      { recepient: alice.publicKey, amount: 1 },
+    ]);
+    await createTokenAndMintTo(
+      escrow,
+      usdcMint,
+      6,
+      alice,
+      [bob, carol, dave].map((bidder) => ({
+        recepient: bidder.publicKey,
+        amount: 1_000 * USDC,
+      }))
+    );
+
+    // One NFT for USDC, a 50 USDC reserve, an hour to commit and an hour to
+    // reveal.  Unrevealed deposits go to Alice.
+    createdAt = await getUnixTimestamp(escrow);
+    const auctionId = getRandomBigNumber();
+    await escrow.program.methods
+      .createAuction(
+        auctionId,
+        new BN(1),
+        new BN(50 * USDC),
+        new BN(HOUR),
+        new BN(HOUR),
+        { forfeit: {} }
+      )
+      .accounts({
+        seller: alice.publicKey,
+        tokenMintA: apeMint.publicKey,
+        tokenMintB: usdcMint.publicKey,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+    [auctionAddress] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("auction"),
+        alice.publicKey.toBuffer(),
+        auctionId.toArrayLike(Buffer, "le", 8),
+      ],
+      escrow.program.programId
+    );
+  });
+
+  test("Bidders commit to hidden bids with a deposit", async () => {
+    for (const [bidder, { amount, deposit, salt }] of bids) {
+      await escrow.program.methods
+        .commitBid(commitmentFor(bidder.publicKey, amount, salt), deposit)
+        .accounts({
+          bidder: bidder.publicKey,
+          auction: auctionAddress,
+          tokenMintB: usdcMint.publicKey,
+          vaultB: ata(usdcMint.publicKey, auctionAddress),
+          tokenProgram: TOKEN_PROGRAM,
+        } as any)
+        .signers([bidder])
+        .rpc();
+    }
+
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, auctionAddress))
+    ).toEqual(new BN(450 * USDC));
+
+    const { amount, salt } = bids.get(dave);
+    await expect(revealBid(dave, amount, salt)).rejects.toThrow(
+      /WrongAuctionPhase/
+    );
+  });
+
+  test("Bids are revealed against their commitment", async () => {
+    await setUnixTimestamp(escrow, createdAt + HOUR);
+
+    const carolBid = bids.get(carol);
+    await expect(
+      revealBid(carol, carolBid.amount.addn(1), carolBid.salt)
+    ).rejects.toThrow(/BidCommitmentMismatch/);
+
+    for (const bidder of [bob, carol]) {
+      const { amount, salt } = bids.get(bidder);
+      await revealBid(bidder, amount, salt);
+    }
+    await expect(
+      revealBid(carol, carolBid.amount, carolBid.salt)
+    ).rejects.toThrow(/BidAlreadyRevealed/);
+
+    const auction = await escrow.program.account.auction.fetch(auctionAddress);
+    expect(auction.highestBidder).toEqual(bob.publicKey);
+    expect(auction.highestBid).toEqual(new BN(120 * USDC));
+  });
+
+  test("Highest revealed bid wins once the reveal phase is over", async () => {
+    await expect(settleAuction(bob.publicKey)).rejects.toThrow(
+      /WrongAuctionPhase/
+    );
+
+    await setUnixTimestamp(escrow, createdAt + 2 * HOUR);
+    await expect(settleAuction(carol.publicKey)).rejects.toThrow(
+      /InvalidLotReceiver/
+    );
+    await settleAuction(bob.publicKey);
+
+    expect(
+      await getTokenBalance(escrow, ata(apeMint.publicKey, bob.publicKey))
+    ).toEqual(new BN(1));
+    expect(await usdcBalance(alice.publicKey)).toEqual(new BN(120 * USDC));
+  });
+
+  test("Deposits are refunded or forfeited, then the auction closes", async () => {
+    for (const bidder of [bob, carol, dave]) {
+      await settleBid(bidder);
+    }
+
+    // Bob gets back what his deposit held above his bid, Carol all of hers.
+    expect(await usdcBalance(bob.publicKey)).toEqual(new BN(880 * USDC));
+    expect(await usdcBalance(carol.publicKey)).toEqual(new BN(1_000 * USDC));
+    expect(await usdcBalance(dave.publicKey)).toEqual(new BN(850 * USDC));
+    expect(await usdcBalance(alice.publicKey)).toEqual(new BN(270 * USDC));
+
+    const connection = escrow.provider.connection;
+    expect(await connection.getAccountInfo(auctionAddress)).toBeNull();
+    expect(
+      await connection.getAccountInfo(ata(usdcMint.publicKey, auctionAddress))
+    ).toBeNull();
+  });
+});
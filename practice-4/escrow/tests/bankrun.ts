@@ -0,0 +1,336 @@
+// Shared setup for tests that run the escrow program inside bankrun rather
+// than against the local validator.  Bankrun lets a test write arbitrary
+// account data and move the clock, which the validator does not.
+
+import { Program, BN } from "@coral-xyz/anchor";
+import { BankrunProvider, startAnchor } from "anchor-bankrun";
+import { AddedProgram, Clock, ProgramTestContext } from "solana-bankrun";
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { MINT_SIZE, getAccount } from "@solana/spl-token";
+
+import { Escrow } from "../target/types/escrow";
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintToIxs,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+
+const IDL = require("../target/idl/escrow.json");
+
+export type EscrowBankrun = {
+  context: ProgramTestContext;
+  provider: BankrunProvider;
+  program: Program<Escrow>;
+};
+
+// `extraPrograms` are loaded from `target/deploy` next to the escrow.
+export const startEscrow = async (
+  extraPrograms: Array<AddedProgram> = []
+): Promise<EscrowBankrun> => {
+  const context = await startAnchor("", extraPrograms, []);
+  const provider = new BankrunProvider(context);
+  const program = new Program<Escrow>(IDL as Escrow, provider);
+  return { context, provider, program };
+};
+
+// The escrow client for another deployment of the program, such as the
+// native build.
+export const escrowProgramAt = (
+  { provider }: EscrowBankrun,
+  programId: PublicKey
+): Program<Escrow> =>
+  new Program<Escrow>({ ...IDL, address: programId.toBase58() }, provider);
+
+// Accounts of `take_offer` and `close_offer` with every optional account
+// left out, so suites only name the accounts they use and a new optional
+// account only touches these helpers.
+export const takeOfferAccounts = (
+  accounts: Record<string, PublicKey | null>
+): any => ({
+  takerFill: null,
+  vesting: null,
+  vestingVault: null,
+  referrer: null,
+  referrerTokenAccount: null,
+  tokenAMetadata: null,
+  priceFeed: null,
+  makerReceiptMint: null,
+  makerReceiptAccount: null,
+  takerReceiptMint: null,
+  takerReceiptAccount: null,
+  receiptTokenProgram: null,
+  rentReceiver: null,
+  tokenProgram: TOKEN_PROGRAM,
+  ...accounts,
+});
+
+export const closeOfferAccounts = (
+  accounts: Record<string, PublicKey | null>
+): any => ({
+  rentReceiver: null,
+  tokenProgram: TOKEN_PROGRAM,
+  ...accounts,
+});
+
+export const offerAddressFor = (
+  program: Program<Escrow>,
+  maker: PublicKey,
+  offerId: BN
+): PublicKey =>
+  PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("offer"),
+      maker.toBuffer(),
+      offerId.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  )[0];
+
+// Makes an offer with a fresh id on index page 0, with `terms` over the
+// default terms.
+export const makeOffer = async (
+  program: Program<Escrow>,
+  {
+    maker,
+    tokenMintA,
+    tokenMintB,
+    offeredAmount,
+    wantedAmount,
+    terms = {},
+  }: {
+    maker: Keypair;
+    tokenMintA: PublicKey;
+    tokenMintB: PublicKey;
+    offeredAmount: BN;
+    wantedAmount: BN;
+    terms?: object;
+  }
+): Promise<{ offerId: BN; offerAddress: PublicKey }> => {
+  const offerId = getRandomBigNumber();
+  await program.methods
+    .makeOffer(
+      offerId,
+      offeredAmount,
+      wantedAmount,
+      { ...defaultOfferTerms(), ...terms } as any,
+      0
+    )
+    .accounts({
+      maker: maker.publicKey,
+      tokenMintA,
+      tokenMintB,
+      previousIndexPage: null,
+      tokenProgram: TOKEN_PROGRAM,
+    } as any)
+    .signers([maker])
+    .rpc();
+  return {
+    offerId,
+    offerAddress: offerAddressFor(program, maker.publicKey, offerId),
+  };
+};
+
+export const fundWallets = (
+  { context }: EscrowBankrun,
+  wallets: Array<Keypair>
+) => {
+  for (const wallet of wallets) {
+    context.setAccount(wallet.publicKey, {
+      lamports: 10 * LAMPORTS_PER_SOL,
+      data: Buffer.alloc(0),
+      owner: SystemProgram.programId,
+      executable: false,
+    });
+  }
+};
+
+export const rentFor = async (
+  { context }: EscrowBankrun,
+  space: number
+): Promise<number> => {
+  const rent = await context.banksClient.getRent();
+  return Number(rent.minimumBalance(BigInt(space)));
+};
+
+export const createTokenAndMintTo = async (
+  escrow: EscrowBankrun,
+  tokenMint: Keypair,
+  decimals: number,
+  mintAuthority: Keypair,
+  mintTo: Array<{ recepient: PublicKey; amount: number }>
+) => {
+  const tx = new Transaction();
+  tx.instructions = createTokenAndMintToIxs(
+    await rentFor(escrow, MINT_SIZE),
+    escrow.provider.publicKey,
+    tokenMint.publicKey,
+    decimals,
+    mintAuthority.publicKey,
+    mintTo
+  );
+  await escrow.provider.sendAndConfirm(tx, [tokenMint, mintAuthority]);
+};
+
+export const getUnixTimestamp = async ({
+  context,
+}: EscrowBankrun): Promise<number> =>
+  Number((await context.banksClient.getClock()).unixTimestamp);
+
+// Moves the clock seen by the program to `unixTimestamp`, leaving the slot
+// and epoch as they are.
+export const setUnixTimestamp = async (
+  { context }: EscrowBankrun,
+  unixTimestamp: number
+) => {
+  const clock = await context.banksClient.getClock();
+  context.setClock(
+    new Clock(
+      clock.slot,
+      clock.epochStartTimestamp,
+      clock.epoch,
+      clock.leaderScheduleEpoch,
+      BigInt(unixTimestamp)
+    )
+  );
+};
+
+export const getLamports = async (
+  { context }: EscrowBankrun,
+  address: PublicKey
+): Promise<number> => Number(await context.banksClient.getBalance(address));
+
+export const getTokenBalance = async (
+  { provider }: EscrowBankrun,
+  address: PublicKey
+): Promise<BN> => {
+  const account = await getAccount(
+    provider.connection,
+    address,
+    undefined,
+    TOKEN_PROGRAM
+  );
+  return new BN(account.amount.toString());
+};
+
+// Runs `instruction` on its own and returns the compute units it consumed,
+// for benchmarks.  The limit is raised so heavy instructions are measured
+// rather than cut off.
+export const computeUnitsFor = async (
+  { context }: EscrowBankrun,
+  instruction: TransactionInstruction,
+  signers: Array<Keypair>
+): Promise<number> => {
+  const tx = new Transaction().add(
+    ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+    instruction
+  );
+  tx.recentBlockhash = context.lastBlockhash;
+  tx.feePayer = context.payer.publicKey;
+  tx.sign(context.payer, ...signers);
+  const { computeUnitsConsumed } = await context.banksClient.processTransaction(
+    tx
+  );
+  return Number(computeUnitsConsumed);
+};
+
+export const PYTH_RECEIVER_PROGRAM_ID = new PublicKey(
+  "rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ"
+);
+
+// Writes a fully verified Pyth `PriceUpdateV2` account at `address`, standing
+// in for the pull oracle in price trigger tests.
+export const setPriceFeed = async (
+  escrow: EscrowBankrun,
+  address: PublicKey,
+  {
+    price,
+    exponent = -8,
+    publishTime,
+  }: { price: number; exponent?: number; publishTime: number }
+) => {
+  const data = Buffer.alloc(134);
+  Buffer.from([34, 241, 35, 99, 157, 126, 244, 205]).copy(data, 0);
+  // Write authority at 8, then the `Full` verification level.
+  data.writeUInt8(1, 40);
+  // Feed id at 41, then the price message.  Confidences and the posted slot
+  // stay zero.
+  data.writeBigInt64LE(BigInt(price), 73);
+  data.writeInt32LE(exponent, 89);
+  data.writeBigInt64LE(BigInt(publishTime), 93);
+  data.writeBigInt64LE(BigInt(publishTime), 101);
+  data.writeBigInt64LE(BigInt(price), 109);
+
+  escrow.context.setAccount(address, {
+    lamports: await rentFor(escrow, data.length),
+    data,
+    owner: PYTH_RECEIVER_PROGRAM_ID,
+    executable: false,
+  });
+};
+
+export const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
+const borshString = (value: string) => {
+  const bytes = Buffer.from(value);
+  const length = Buffer.alloc(4);
+  length.writeUInt32LE(bytes.length);
+  return Buffer.concat([length, bytes]);
+};
+
+// Writes the start of a Metaplex `MetadataV1` account for `mint`, up to the
+// creators, plus the two flags after them.  The escrow never reads past the
+// creators.  Returns the metadata address.
+export const setTokenMetadata = async (
+  escrow: EscrowBankrun,
+  mint: PublicKey,
+  sellerFeeBasisPoints: number,
+  creators: Array<{ address: PublicKey; verified: boolean; share: number }>
+): Promise<PublicKey> => {
+  const [address] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("metadata"),
+      TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+      mint.toBuffer(),
+    ],
+    TOKEN_METADATA_PROGRAM_ID
+  );
+
+  const fee = Buffer.alloc(2);
+  fee.writeUInt16LE(sellerFeeBasisPoints);
+  const creatorCount = Buffer.alloc(4);
+  creatorCount.writeUInt32LE(creators.length);
+  const data = Buffer.concat([
+    Buffer.from([4]),
+    PublicKey.default.toBuffer(),
+    mint.toBuffer(),
+    borshString("Escrow Ape #1"),
+    borshString("EAPE"),
+    borshString("https://example.com/ape/1.json"),
+    fee,
+    Buffer.from([1]),
+    creatorCount,
+    ...creators.map(({ address, verified, share }) =>
+      Buffer.concat([address.toBuffer(), Buffer.from([verified ? 1 : 0, share])])
+    ),
+    Buffer.from([0, 1]),
+  ]);
+
+  escrow.context.setAccount(address, {
+    lamports: await rentFor(escrow, data.length),
+    data,
+    owner: TOKEN_METADATA_PROGRAM_ID,
+    executable: false,
+  });
+  return address;
+};
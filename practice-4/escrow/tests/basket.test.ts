@@ -0,0 +1,278 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  AccountMeta,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  createAssociatedTokenAccountIdempotentInstruction,
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  getRandomBigNumber,
+  getTokenBalanceOn,
+} from "./helpers";
+
+describe("escrow basket offers", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+  const getTokenBalance = getTokenBalanceOn(connection);
+
+  // Carol makes offers without accounts for the mints she wants.
+  const [alice, bob, carol] = makeKeypairs(3);
+  // Alice offers three tokens and wants two others in return.
+  const offeredMints = makeKeypairs(3);
+  const wantedMints = makeKeypairs(2);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const basketAddress = (maker: PublicKey, id: BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("basket"),
+        maker.toBuffer(),
+        id.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const meta = (pubkey: PublicKey, isWritable: boolean): AccountMeta => ({
+    pubkey,
+    isWritable,
+    isSigner: false,
+  });
+
+  const offered = offeredMints.map((mint, i) => ({
+    mint: mint.publicKey,
+    amount: new BN((i + 1) * 1_000_000),
+  }));
+  const wanted = wantedMints.map((mint, i) => ({
+    mint: mint.publicKey,
+    amount: new BN((i + 1) * 5_000_000),
+  }));
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [alice, bob, carol].map((owner) =>
+      SystemProgram.transfer({
+        fromPubkey: provider.publicKey,
+        toPubkey: owner.publicKey,
+        lamports: 10 * LAMPORTS_PER_SOL,
+      })
+    );
+    for (const [mints, owner] of [
+      [offeredMints, alice],
+      [wantedMints, bob],
+    ] as const) {
+      for (const mint of mints) {
+        tx.instructions.push(
+          ...(await createTokenAndMintTo(
+            connection,
+            provider.publicKey,
+            mint.publicKey,
+            6,
+            owner.publicKey,
+            [{ recepient: owner.publicKey, amount: 100_000_000 }]
+          ))
+        );
+      }
+    }
+    await provider.sendAndConfirm(tx, [...offeredMints, ...wantedMints]);
+
+    // Makers have to hold an account for every wanted mint before their
+    // baskets can be taken, so Alice opens hers up front.  Carol gets the
+    // offered tokens only.
+    const accountsTx = new Transaction();
+    for (const { publicKey: mint } of offeredMints) {
+      const carolAccount = ata(mint, carol.publicKey);
+      accountsTx.add(
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          carolAccount,
+          carol.publicKey,
+          mint,
+          TOKEN_PROGRAM
+        ),
+        createMintToInstruction(
+          mint,
+          carolAccount,
+          alice.publicKey,
+          100_000_000,
+          [],
+          TOKEN_PROGRAM
+        )
+      );
+    }
+    for (const { publicKey: mint } of wantedMints) {
+      accountsTx.add(
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          ata(mint, alice.publicKey),
+          alice.publicKey,
+          mint,
+          TOKEN_PROGRAM
+        )
+      );
+    }
+    await provider.sendAndConfirm(accountsTx, [alice]);
+  });
+
+  const makeBasketOffer = async (id: BN, maker = alice) => {
+    const basket = basketAddress(maker.publicKey, id);
+    await program.methods
+      .makeBasketOffer(id, offered, wanted)
+      .accounts({
+        maker: maker.publicKey,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .remainingAccounts(
+        offered.flatMap(({ mint }) => [
+          meta(mint, false),
+          meta(ata(mint, maker.publicKey), true),
+          meta(ata(mint, basket), true),
+        ])
+      )
+      .signers([maker])
+      .rpc();
+    return basket;
+  };
+
+  const takeBasketAccounts = (
+    basket: PublicKey,
+    vaultOverride = new Map<string, PublicKey>(),
+    maker = alice
+  ) => [
+    ...offered.flatMap(({ mint }) => [
+      meta(mint, false),
+      meta(vaultOverride.get(mint.toBase58()) ?? ata(mint, basket), true),
+      meta(ata(mint, bob.publicKey), true),
+    ]),
+    ...wanted.flatMap(({ mint }) => [
+      meta(mint, false),
+      meta(ata(mint, bob.publicKey), true),
+      meta(ata(mint, maker.publicKey), true),
+    ]),
+  ];
+
+  test("Basket is swapped atomically and all vaults are closed", async () => {
+    const basket = await makeBasketOffer(getRandomBigNumber());
+
+    for (const { mint, amount } of offered) {
+      expect(await getTokenBalance(ata(mint, basket))).toEqual(amount);
+    }
+
+    await program.methods
+      .takeBasketOffer()
+      .accounts({
+        taker: bob.publicKey,
+        basketOffer: basket,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .remainingAccounts(takeBasketAccounts(basket))
+      .signers([bob])
+      .rpc();
+
+    for (const { mint, amount } of offered) {
+      expect(await getTokenBalance(ata(mint, bob.publicKey))).toEqual(amount);
+      expect(await connection.getAccountInfo(ata(mint, basket))).toBeNull();
+    }
+    for (const { mint, amount } of wanted) {
+      expect(await getTokenBalance(ata(mint, alice.publicKey))).toEqual(amount);
+    }
+    expect(await connection.getAccountInfo(basket)).toBeNull();
+  });
+
+  test("Substituted vault is rejected", async () => {
+    const basket = await makeBasketOffer(getRandomBigNumber());
+    const otherBasket = await makeBasketOffer(getRandomBigNumber());
+
+    // Point the first leg at the vault of Alice's other basket.
+    const firstMint = offered[0].mint;
+    const vaultOverride = new Map([
+      [firstMint.toBase58(), ata(firstMint, otherBasket)],
+    ]);
+
+    await expect(
+      program.methods
+        .takeBasketOffer()
+        .accounts({
+          taker: bob.publicKey,
+          basketOffer: basket,
+          tokenProgram: TOKEN_PROGRAM,
+        } as any)
+        .remainingAccounts(takeBasketAccounts(basket, vaultOverride))
+        .signers([bob])
+        .rpc()
+    ).rejects.toThrow(/BasketAccountMismatch/);
+  });
+
+  test("Taker does not open the maker's accounts for wanted mints", async () => {
+    const basket = await makeBasketOffer(getRandomBigNumber(), carol);
+
+    await expect(
+      program.methods
+        .takeBasketOffer()
+        .accounts({
+          taker: bob.publicKey,
+          maker: carol.publicKey,
+          basketOffer: basket,
+          tokenProgram: TOKEN_PROGRAM,
+        } as any)
+        .remainingAccounts(takeBasketAccounts(basket, new Map(), carol))
+        .signers([bob])
+        .rpc()
+    ).rejects.toThrow(/AccountNotInitialized/);
+    for (const { mint } of wanted) {
+      expect(
+        await connection.getAccountInfo(ata(mint, carol.publicKey))
+      ).toBeNull();
+    }
+  });
+
+  test("Maker closes a basket and gets every leg back", async () => {
+    const before = await Promise.all(
+      offered.map(({ mint }) => getTokenBalance(ata(mint, alice.publicKey)))
+    );
+    const basket = await makeBasketOffer(getRandomBigNumber());
+
+    await program.methods
+      .closeBasketOffer()
+      .accounts({
+        maker: alice.publicKey,
+        basketOffer: basket,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .remainingAccounts(
+        offered.flatMap(({ mint }) => [
+          meta(mint, false),
+          meta(ata(mint, basket), true),
+          meta(ata(mint, alice.publicKey), true),
+        ])
+      )
+      .signers([alice])
+      .rpc();
+
+    for (const [i, { mint }] of offered.entries()) {
+      expect(await getTokenBalance(ata(mint, alice.publicKey))).toEqual(
+        before[i]
+      );
+    }
+    expect(await connection.getAccountInfo(basket)).toBeNull();
+  });
+});
@@ -0,0 +1,216 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  AccountMeta,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+  getTokenBalanceOn,
+} from "./helpers";
+
+describe("escrow bulk close", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+
+  const [alice, carol, usdcMint, wifMint, bonkMint] = makeKeypairs(5);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const pda = (...seeds: Array<Buffer>) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const marketStatsAddressFor = (mintA: PublicKey, mintB: PublicKey) =>
+    pda(Buffer.from("market_stats"), mintA.toBuffer(), mintB.toBuffer());
+
+  type Offer = {
+    address: PublicKey;
+    tokenMintA: PublicKey;
+    tokenMintB: PublicKey;
+  };
+
+  const makeOffer = async (
+    tokenMintA: PublicKey,
+    tokenMintB: PublicKey,
+    rentReceiver: PublicKey | null = null
+  ): Promise<Offer> => {
+    const offerId = getRandomBigNumber();
+    await program.methods
+      .makeOffer(
+        offerId,
+        new BN(10_000_000),
+        new BN(25_000_000),
+        { ...defaultOfferTerms(), rentReceiver },
+        0
+      )
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA,
+        tokenMintB,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+    const address = pda(
+      Buffer.from("offer"),
+      alice.publicKey.toBuffer(),
+      offerId.toArrayLike(Buffer, "le", 8)
+    );
+    return { address, tokenMintA, tokenMintB };
+  };
+
+  // Every offer is passed as `[offer, vault, token mint A, maker ATA for
+  // mint A, market stats, market index page]`.
+  const groupFor = ({
+    address,
+    tokenMintA,
+    tokenMintB,
+  }: Offer): Array<AccountMeta> =>
+    [
+      address,
+      ata(tokenMintA, address),
+      tokenMintA,
+      ata(tokenMintA, alice.publicKey),
+      marketStatsAddressFor(tokenMintA, tokenMintB),
+      pda(
+        Buffer.from("market_index"),
+        tokenMintA.toBuffer(),
+        tokenMintB.toBuffer(),
+        Buffer.alloc(4)
+      ),
+    ].map((pubkey, i) => ({ pubkey, isSigner: false, isWritable: i !== 2 }));
+
+  const closeOffers = (offers: Array<Offer>) =>
+    program.methods
+      .closeOffers()
+      .accounts({
+        maker: alice.publicKey,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .remainingAccounts(offers.flatMap(groupFor))
+      .signers([alice])
+      .rpc({ commitment: "confirmed" });
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      SystemProgram.transfer({
+        fromPubkey: provider.publicKey,
+        toPubkey: alice.publicKey,
+        lamports: 10 * LAMPORTS_PER_SOL,
+      }),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [{ recepient: alice.publicKey, amount: 100_000_000 }]
+      )),
+    ];
+    for (const mint of [wifMint, bonkMint]) {
+      tx.instructions.push(
+        ...(await createTokenAndMintTo(
+          connection,
+          provider.publicKey,
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          []
+        ))
+      );
+    }
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint, bonkMint]);
+  });
+
+  test("All offers are refunded and closed in one instruction", async () => {
+    // Two offers in one market and one in another.
+    const offers = [
+      await makeOffer(usdcMint.publicKey, wifMint.publicKey),
+      await makeOffer(usdcMint.publicKey, wifMint.publicKey),
+      await makeOffer(usdcMint.publicKey, bonkMint.publicKey),
+    ];
+    const aliceUsdc = ata(usdcMint.publicKey, alice.publicKey);
+    expect(await getTokenBalanceOn(connection)(aliceUsdc)).toEqual(
+      new BN(70_000_000)
+    );
+
+    const signature = await closeOffers(offers);
+
+    const transaction = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const returnLog = transaction.meta.logMessages.find((log) =>
+      log.startsWith(`Program return: ${program.programId} `)
+    );
+    const closed = new BN(
+      Buffer.from(returnLog.split(" ").pop(), "base64"),
+      "le"
+    );
+    expect(closed).toEqual(new BN(3));
+
+    expect(await getTokenBalanceOn(connection)(aliceUsdc)).toEqual(
+      new BN(100_000_000)
+    );
+    for (const { address, tokenMintA } of offers) {
+      expect(await connection.getAccountInfo(address)).toBeNull();
+      expect(
+        await connection.getAccountInfo(ata(tokenMintA, address))
+      ).toBeNull();
+    }
+
+    const wifStats = await program.account.marketStats.fetch(
+      marketStatsAddressFor(usdcMint.publicKey, wifMint.publicKey)
+    );
+    expect(wifStats.openOffers).toEqual(new BN(0));
+
+    const reputation = await program.account.reputation.fetch(
+      pda(Buffer.from("reputation"), alice.publicKey.toBuffer())
+    );
+    expect(reputation.cancellations).toEqual(new BN(3));
+  });
+
+  test("Offers with a rent receiver are left to close_offer", async () => {
+    const offer = await makeOffer(
+      usdcMint.publicKey,
+      wifMint.publicKey,
+      carol.publicKey
+    );
+
+    await expect(closeOffers([offer])).rejects.toThrow(/InvalidRentReceiver/);
+  });
+
+  test("Groups must be complete", async () => {
+    const offer = await makeOffer(usdcMint.publicKey, wifMint.publicKey);
+
+    await expect(
+      program.methods
+        .closeOffers()
+        .accounts({
+          maker: alice.publicKey,
+          tokenProgram: TOKEN_PROGRAM,
+        })
+        .remainingAccounts(groupFor(offer).slice(0, 4))
+        .signers([alice])
+        .rpc()
+    ).rejects.toThrow(/CloseAccountMismatch/);
+  });
+});
@@ -0,0 +1,234 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+import {
+  EscrowBankrun,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  startEscrow,
+} from "./bankrun";
+
+const TOKEN = 1_000_000;
+
+describe("escrow offer delegates", () => {
+  let escrow: EscrowBankrun;
+  let firstOffer: PublicKey;
+  let secondOffer: PublicKey;
+  let aliceMakerDelegate: PublicKey;
+
+  // Carol's bot manages Alice's first offer, Dave's all of her offers.
+  const [alice, carol, dave, usdcMint, wifMint, bonkMint] = makeKeypairs(6);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const makeOffer = async (terms = defaultOfferTerms()) => {
+    const offerId = getRandomBigNumber();
+    await escrow.program.methods
+      .makeOffer(offerId, new BN(100 * TOKEN), new BN(200 * TOKEN), terms, 0)
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        alice.publicKey.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      escrow.program.programId
+    )[0];
+  };
+
+  const amendOffer = (
+    delegate: Keypair,
+    offer: PublicKey,
+    tokenBWantedAmount: number,
+    makerDelegate: PublicKey | null = null
+  ) =>
+    escrow.program.methods
+      .delegateAmendOffer(new BN(tokenBWantedAmount))
+      .accounts({
+        delegate: delegate.publicKey,
+        maker: alice.publicKey,
+        makerDelegate,
+        offer,
+      } as any)
+      .signers([delegate])
+      .rpc();
+
+  const closeOffer = (
+    delegate: Keypair,
+    offer: PublicKey,
+    makerDelegate: PublicKey | null,
+    makerTokenAccountA: PublicKey
+  ) =>
+    escrow.program.methods
+      .delegateCloseOffer()
+      .accounts({
+        delegate: delegate.publicKey,
+        maker: alice.publicKey,
+        makerDelegate,
+        offer,
+        vault: ata(usdcMint.publicKey, offer),
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        makerTokenAccountA,
+        rentReceiver: null,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .signers([delegate])
+      .rpc();
+
+  const setOfferDelegate = (offer: PublicKey, delegate: PublicKey | null) =>
+    escrow.program.methods
+      .setOfferDelegate(delegate)
+      .accounts({ maker: alice.publicKey, offer } as any)
+      .signers([alice])
+      .rpc();
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, carol, dave]);
+
+    await createTokenAndMintTo(escrow, usdcMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 1_000 * TOKEN },
+      { recepient: carol.publicKey, amount: 0 },
+    ]);
+    await createTokenAndMintTo(escrow, wifMint, 6, alice, []);
+
+    [aliceMakerDelegate] = PublicKey.findProgramAddressSync(
+      [Buffer.from("maker_delegate"), alice.publicKey.toBuffer()],
+      escrow.program.programId
+    );
+    firstOffer = await makeOffer();
+    secondOffer = await makeOffer();
+    await setOfferDelegate(firstOffer, carol.publicKey);
+  });
+
+  test("An offer delegate can reprice only that offer", async () => {
+    await amendOffer(carol, firstOffer, 250 * TOKEN);
+    const offer = await escrow.program.account.offer.fetch(firstOffer);
+    expect(offer.tokenBWantedAmount).toEqual(new BN(250 * TOKEN));
+
+    await expect(amendOffer(carol, secondOffer, 250 * TOKEN)).rejects.toThrow(
+      /NotOfferDelegate/
+    );
+    await expect(amendOffer(dave, firstOffer, 300 * TOKEN)).rejects.toThrow(
+      /NotOfferDelegate/
+    );
+  });
+
+  test("Delegates cannot lower the price", async () => {
+    await expect(amendOffer(carol, firstOffer, 1)).rejects.toThrow(
+      /DelegateCannotLowerPrice/
+    );
+    await expect(amendOffer(carol, firstOffer, 249 * TOKEN)).rejects.toThrow(
+      /DelegateCannotLowerPrice/
+    );
+
+    const offer = await escrow.program.account.offer.fetch(firstOffer);
+    expect(offer.tokenBWantedAmount).toEqual(new BN(250 * TOKEN));
+  });
+
+  test("Repricing moves payment options by the same factor", async () => {
+    // 100 USDC for 200 WIF or 201 BONK.
+    const offer = await makeOffer({
+      ...defaultOfferTerms(),
+      paymentOptions: [
+        { mint: bonkMint.publicKey, wantedAmount: new BN(201 * TOKEN) },
+      ],
+    });
+    await setOfferDelegate(offer, carol.publicKey);
+
+    await amendOffer(carol, offer, 300 * TOKEN);
+
+    const repriced = await escrow.program.account.offer.fetch(offer);
+    expect(repriced.tokenBWantedAmount).toEqual(new BN(300 * TOKEN));
+    expect(repriced.paymentOptions[0].wantedAmount).toEqual(
+      new BN(301_500_000)
+    );
+  });
+
+  test("A revoked offer delegate loses access", async () => {
+    await setOfferDelegate(firstOffer, null);
+    await expect(amendOffer(carol, firstOffer, 120 * TOKEN)).rejects.toThrow(
+      /NotOfferDelegate/
+    );
+  });
+
+  test("A maker delegate can close any of the maker's offers", async () => {
+    await escrow.program.methods
+      .setMakerDelegate(dave.publicKey)
+      .accounts({ maker: alice.publicKey })
+      .signers([alice])
+      .rpc();
+
+    await expect(
+      closeOffer(
+        dave,
+        firstOffer,
+        null,
+        ata(usdcMint.publicKey, alice.publicKey)
+      )
+    ).rejects.toThrow(/NotOfferDelegate/);
+
+    await amendOffer(dave, firstOffer, 300 * TOKEN, aliceMakerDelegate);
+    await closeOffer(
+      dave,
+      secondOffer,
+      aliceMakerDelegate,
+      ata(usdcMint.publicKey, alice.publicKey)
+    );
+
+    expect(
+      await escrow.provider.connection.getAccountInfo(secondOffer)
+    ).toBeNull();
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, alice.publicKey))
+    ).toEqual(new BN(900 * TOKEN));
+  });
+
+  test("Delegates cannot send the tokens anywhere but the maker's ATA", async () => {
+    await expect(
+      closeOffer(
+        dave,
+        firstOffer,
+        aliceMakerDelegate,
+        ata(usdcMint.publicKey, carol.publicKey)
+      )
+    ).rejects.toThrow(/ConstraintTokenOwner|ConstraintAssociated/);
+  });
+
+  test("A revoked maker delegate loses access", async () => {
+    await escrow.program.methods
+      .setMakerDelegate(null)
+      .accounts({ maker: alice.publicKey })
+      .signers([alice])
+      .rpc();
+
+    await expect(
+      closeOffer(
+        dave,
+        firstOffer,
+        aliceMakerDelegate,
+        ata(usdcMint.publicKey, alice.publicKey)
+      )
+    ).rejects.toThrow(/NotOfferDelegate/);
+  });
+});
@@ -3,7 +3,6 @@ import * as anchor from "@coral-xyz/anchor";
 import { type Program, BN } from "@coral-xyz/anchor";
 import { Escrow } from "../target/types/escrow";
 import {
-  Connection,
   Keypair,
   LAMPORTS_PER_SOL,
   PublicKey,
@@ -11,106 +10,18 @@ import {
   Transaction,
   TransactionInstruction,
 } from "@solana/web3.js";
-import {
-  MINT_SIZE,
-  TOKEN_2022_PROGRAM_ID,
-  type TOKEN_PROGRAM_ID,
-  createAssociatedTokenAccountIdempotentInstruction,
-  createInitializeMint2Instruction,
-  createMintToInstruction,
-  getAssociatedTokenAddressSync,
-  getMinimumBalanceForRentExemptMint,
-} from "@solana/spl-token";
-import { randomBytes } from "crypto";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
 
 import { confirmTransaction, makeKeypairs } from "@solana-developers/helpers";
 
-const TOKEN_PROGRAM: typeof TOKEN_2022_PROGRAM_ID | typeof TOKEN_PROGRAM_ID =
-  TOKEN_2022_PROGRAM_ID;
-
-export const getRandomBigNumber = (size: number = 8) => {
-  return new BN(randomBytes(size));
-};
-
-function areBnEqual(a: unknown, b: unknown): boolean | undefined {
-  const isABn = a instanceof BN;
-  const isBBn = b instanceof BN;
-
-  if (isABn && isBBn) {
-    return a.eq(b);
-  } else if (isABn === isBBn) {
-    return undefined;
-  } else {
-    return false;
-  }
-}
-expect.addEqualityTesters([areBnEqual]);
-
-const createTokenAndMintTo = async (
-  connection: Connection,
-  payer: PublicKey,
-  tokenMint: PublicKey,
-  decimals: number,
-  mintAuthority: PublicKey,
-  mintTo: Array<{ recepient: PublicKey; amount: number }>
-): Promise<Array<TransactionInstruction>> => {
-  let minimumLamports = await getMinimumBalanceForRentExemptMint(connection);
-
-  let createTokeIxs = [
-    SystemProgram.createAccount({
-      fromPubkey: payer,
-      newAccountPubkey: tokenMint,
-      lamports: minimumLamports,
-      space: MINT_SIZE,
-      programId: TOKEN_PROGRAM,
-    }),
-    createInitializeMint2Instruction(
-      tokenMint,
-      decimals,
-      mintAuthority,
-      null,
-      TOKEN_PROGRAM
-    ),
-  ];
-
-  let mintToIxs = mintTo.flatMap(({ recepient, amount }) => {
-    const ataAddress = getAssociatedTokenAddressSync(
-      tokenMint,
-      recepient,
-      false,
-      TOKEN_PROGRAM
-    );
-
-    return [
-      createAssociatedTokenAccountIdempotentInstruction(
-        payer,
-        ataAddress,
-        recepient,
-        tokenMint,
-        TOKEN_PROGRAM
-      ),
-      createMintToInstruction(
-        tokenMint,
-        ataAddress,
-        mintAuthority,
-        amount,
-        [],
-        TOKEN_PROGRAM
-      ),
-    ];
-  });
-
-  return [...createTokeIxs, ...mintToIxs];
-};
-
-const getTokenBalanceOn = (
-  connection: Connection,
-) => async (
-  tokenAccountAddress: PublicKey,
-): Promise<BN> => {
-    const tokenBalance = await connection.getTokenAccountBalance(tokenAccountAddress);
-    return new BN(tokenBalance.value.amount);
-  };
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+  getTokenBalanceOn,
+} from "./helpers";
+import { closeOfferAccounts, takeOfferAccounts } from "./bankrun";
 
 // Jest debug console it too verbose.
 // const jestConsole = console;
@@ -220,11 +131,20 @@ describe("escrow", () => {
     vaultAddress: PublicKey;
   }> => {
     const transactionSignature = await program.methods
-      .makeOffer(offerId, offeredAmount, wantedAmount)
+      .makeOffer(
+        offerId,
+        offeredAmount,
+        wantedAmount,
+        defaultOfferTerms(),
+        0
+      )
       .accounts({
         maker: maker.publicKey,
         tokenMintA: offeredTokenMint,
         tokenMintB: wantedTokenMint,
+        // Page 0 of the market index already exists or is created here,
+        // so there is no previous page to link.
+        previousIndexPage: null,
         // As the `token_program` account is specified as
         //
         //   pub token_program: Interface<'info, TokenInterface>,
@@ -270,6 +190,8 @@ describe("escrow", () => {
   const takeOfferTx = async (
     offerAddress: PublicKey,
     taker: Keypair,
+    tokenAAmount: BN,
+    paymentMint: PublicKey,
   ): Promise<void> => {
 
     // `accounts` argument debugging tool.  Should be part of Anchor really.
@@ -287,14 +209,16 @@ describe("escrow", () => {
     // >;
 
     const transactionSignature = await program.methods
-      .takeOffer()
-      .accounts({
-        taker: taker.publicKey,
-        offer: offerAddress,
-        // See note in the `makeOfferTx` on why this program address is provided
-        // and the rest are not.
-        tokenProgram: TOKEN_PROGRAM,
-      })
+      .takeOffer(tokenAAmount, [])
+      .accounts(
+        takeOfferAccounts({
+          taker: taker.publicKey,
+          offer: offerAddress,
+          // The offer may accept several token B mints, so the taker names the
+          // one they pay with.
+          tokenMintB: paymentMint,
+        })
+      )
       .signers([taker])
       .rpc();
 
@@ -311,16 +235,17 @@ describe("escrow", () => {
   ): Promise<string> => {
     const transactionSignature = await program.methods
       .closeOffer()
-      .accounts({
-        maker: maker.publicKey,
-        offer: offerAddress,
-        vault: vaultAddress,
-        tokenMintA: tokenMintA,
-        tokenMintB: tokenMintB,
-        makerTokenAccountA: makerTokenAccountA,
-        tokenProgram: TOKEN_PROGRAM,
-        systemProgram: SystemProgram.programId,
-      } as any)
+      .accounts(
+        closeOfferAccounts({
+          maker: maker.publicKey,
+          offer: offerAddress,
+          vault: vaultAddress,
+          tokenMintA: tokenMintA,
+          tokenMintB: tokenMintB,
+          makerTokenAccountA: makerTokenAccountA,
+          systemProgram: SystemProgram.programId,
+        })
+      )
       .signers([maker])
       .rpc();
 
@@ -377,7 +302,12 @@ describe("escrow", () => {
     expect(await getTokenBalance(bobUsdcAccount)).toEqual(new BN(20_000_000));
     expect(await getTokenBalance(bobWifAccount)).toEqual(new BN(300_000_000));
 
-    await takeOfferTx(offerAddress, bob);
+    await takeOfferTx(
+      offerAddress,
+      bob,
+      new BN(10_000_000),
+      wifMint.publicKey
+    );
 
     expect(await getTokenBalance(aliceUsdcAccount)).toEqual(new BN(90_000_000));
     expect(await getTokenBalance(aliceWifAccount)).toEqual(new BN(105_000_000));
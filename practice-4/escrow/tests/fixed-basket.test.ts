@@ -0,0 +1,230 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { AccountMeta, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { TOKEN_PROGRAM, getRandomBigNumber } from "./helpers";
+import {
+  EscrowBankrun,
+  computeUnitsFor,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  startEscrow,
+} from "./bankrun";
+
+const TOKEN = 1_000_000;
+const DEFAULT_COMPUTE_UNITS = 200_000;
+
+// Zero-copy `FixedBasketOffer`s against Borsh `BasketOffer`s holding the same
+// legs.  Three legs a side is as much as fits in a legacy transaction.
+describe("escrow fixed basket offers", () => {
+  let escrow: EscrowBankrun;
+
+  const [alice, bob] = makeKeypairs(2);
+  const offeredMints = makeKeypairs(3);
+  const wantedMints = makeKeypairs(3);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const meta = (pubkey: PublicKey, isWritable: boolean): AccountMeta => ({
+    pubkey,
+    isWritable,
+    isSigner: false,
+  });
+
+  const offered = offeredMints.map((mint, i) => ({
+    mint: mint.publicKey,
+    amount: new BN((i + 1) * TOKEN),
+  }));
+  const wanted = wantedMints.map((mint, i) => ({
+    mint: mint.publicKey,
+    amount: new BN((i + 1) * 5 * TOKEN),
+  }));
+
+  const basketAddress = (seed: string, id: BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(seed),
+        alice.publicKey.toBuffer(),
+        id.toArrayLike(Buffer, "le", 8),
+      ],
+      escrow.program.programId
+    )[0];
+
+  const makeAccounts = (basket: PublicKey) =>
+    offered.flatMap(({ mint }) => [
+      meta(mint, false),
+      meta(ata(mint, alice.publicKey), true),
+      meta(ata(mint, basket), true),
+    ]);
+
+  const takeAccounts = (basket: PublicKey) => [
+    ...offered.flatMap(({ mint }) => [
+      meta(mint, false),
+      meta(ata(mint, basket), true),
+      meta(ata(mint, bob.publicKey), true),
+    ]),
+    ...wanted.flatMap(({ mint }) => [
+      meta(mint, false),
+      meta(ata(mint, bob.publicKey), true),
+      meta(ata(mint, alice.publicKey), true),
+    ]),
+  ];
+
+  const makeFixedBasketOffer = async (id: BN) => {
+    const basket = basketAddress("fixed_basket", id);
+    const instruction = await escrow.program.methods
+      .makeFixedBasketOffer(id, offered, wanted)
+      .accounts({ maker: alice.publicKey, tokenProgram: TOKEN_PROGRAM })
+      .remainingAccounts(makeAccounts(basket))
+      .instruction();
+    return { basket, instruction };
+  };
+
+  const makeBasketOffer = async (id: BN) => {
+    const basket = basketAddress("basket", id);
+    const instruction = await escrow.program.methods
+      .makeBasketOffer(id, offered, wanted)
+      .accounts({ maker: alice.publicKey, tokenProgram: TOKEN_PROGRAM })
+      .remainingAccounts(makeAccounts(basket))
+      .instruction();
+    return { basket, instruction };
+  };
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob]);
+
+    // Both sides get an ATA for every mint up front, so no take below pays
+    // for creating one and the benchmarks compare like with like.
+    for (const [mints, owner, other] of [
+      [offeredMints, alice, bob],
+      [wantedMints, bob, alice],
+    ] as const) {
+      for (const mint of mints) {
+        await createTokenAndMintTo(escrow, mint, 6, owner, [
+          { recepient: owner.publicKey, amount: 100 * TOKEN },
+          { recepient: other.publicKey, amount: 0 },
+        ]);
+      }
+    }
+  });
+
+  test("Fixed basket is swapped atomically and closed", async () => {
+    const { basket, instruction } = await makeFixedBasketOffer(
+      getRandomBigNumber()
+    );
+    await computeUnitsFor(escrow, instruction, [alice]);
+
+    const stored = await escrow.program.account.fixedBasketOffer.fetch(basket);
+    expect(stored.maker).toEqual(alice.publicKey);
+    expect(stored.offeredCount).toEqual(offered.length);
+    expect(stored.wantedCount).toEqual(wanted.length);
+
+    await escrow.program.methods
+      .takeFixedBasketOffer()
+      .accounts({
+        taker: bob.publicKey,
+        maker: alice.publicKey,
+        basketOffer: basket,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .remainingAccounts(takeAccounts(basket))
+      .signers([bob])
+      .rpc();
+
+    for (const { mint, amount } of offered) {
+      expect(
+        await getTokenBalance(escrow, ata(mint, bob.publicKey))
+      ).toEqual(amount);
+    }
+    for (const { mint, amount } of wanted) {
+      expect(
+        await getTokenBalance(escrow, ata(mint, alice.publicKey))
+      ).toEqual(amount);
+    }
+    expect(
+      await escrow.provider.connection.getAccountInfo(basket)
+    ).toBeNull();
+  });
+
+  test("Maker closes a fixed basket and gets every leg back", async () => {
+    const { basket, instruction } = await makeFixedBasketOffer(
+      getRandomBigNumber()
+    );
+    await computeUnitsFor(escrow, instruction, [alice]);
+
+    await escrow.program.methods
+      .closeFixedBasketOffer()
+      .accounts({
+        maker: alice.publicKey,
+        basketOffer: basket,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .remainingAccounts(
+        offered.flatMap(({ mint }) => [
+          meta(mint, false),
+          meta(ata(mint, basket), true),
+          meta(ata(mint, alice.publicKey), true),
+        ])
+      )
+      .signers([alice])
+      .rpc();
+
+    for (const { mint } of offered) {
+      expect(
+        await escrow.provider.connection.getAccountInfo(ata(mint, basket))
+      ).toBeNull();
+    }
+    expect(
+      await escrow.provider.connection.getAccountInfo(basket)
+    ).toBeNull();
+  });
+
+  test("Zero-copy layout takes less compute than Borsh", async () => {
+    const id = getRandomBigNumber();
+    const borsh = await makeBasketOffer(id);
+    const fixed = await makeFixedBasketOffer(id);
+
+    const makeUnits = {
+      borsh: await computeUnitsFor(escrow, borsh.instruction, [alice]),
+      fixed: await computeUnitsFor(escrow, fixed.instruction, [alice]),
+    };
+
+    const takeInstruction = (basket: PublicKey, fixedLayout: boolean) =>
+      (fixedLayout
+        ? escrow.program.methods.takeFixedBasketOffer()
+        : escrow.program.methods.takeBasketOffer()
+      )
+        .accounts({
+          taker: bob.publicKey,
+          maker: alice.publicKey,
+          basketOffer: basket,
+          tokenProgram: TOKEN_PROGRAM,
+        } as any)
+        .remainingAccounts(takeAccounts(basket))
+        .instruction();
+    const takeUnits = {
+      borsh: await computeUnitsFor(
+        escrow,
+        await takeInstruction(borsh.basket, false),
+        [bob]
+      ),
+      fixed: await computeUnitsFor(
+        escrow,
+        await takeInstruction(fixed.basket, true),
+        [bob]
+      ),
+    };
+
+    // Both fit the default budget of 200_000 units an instruction gets, so
+    // neither side needs a compute budget instruction for a 3 + 3 basket.
+    expect(makeUnits.fixed).toBeLessThan(DEFAULT_COMPUTE_UNITS);
+    expect(takeUnits.fixed).toBeLessThan(DEFAULT_COMPUTE_UNITS);
+    expect(takeUnits.fixed).toBeLessThan(takeUnits.borsh);
+  });
+});
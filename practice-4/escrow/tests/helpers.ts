@@ -0,0 +1,138 @@
+import { expect } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import {
+  Connection,
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_2022_PROGRAM_ID,
+  type TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { randomBytes } from "crypto";
+
+export const TOKEN_PROGRAM: typeof TOKEN_2022_PROGRAM_ID | typeof TOKEN_PROGRAM_ID =
+  TOKEN_2022_PROGRAM_ID;
+
+export const getRandomBigNumber = (size: number = 8) => {
+  return new BN(randomBytes(size));
+};
+
+function areBnEqual(a: unknown, b: unknown): boolean | undefined {
+  const isABn = a instanceof BN;
+  const isBBn = b instanceof BN;
+
+  if (isABn && isBBn) {
+    return a.eq(b);
+  } else if (isABn === isBBn) {
+    return undefined;
+  } else {
+    return false;
+  }
+}
+expect.addEqualityTesters([areBnEqual]);
+
+export const createTokenAndMintTo = async (
+  connection: Connection,
+  payer: PublicKey,
+  tokenMint: PublicKey,
+  decimals: number,
+  mintAuthority: PublicKey,
+  mintTo: Array<{ recepient: PublicKey; amount: number }>
+): Promise<Array<TransactionInstruction>> =>
+  createTokenAndMintToIxs(
+    await getMinimumBalanceForRentExemptMint(connection),
+    payer,
+    tokenMint,
+    decimals,
+    mintAuthority,
+    mintTo
+  );
+
+// Same as `createTokenAndMintTo`, for callers that already know the mint rent
+// and may not have a full `Connection`, like the bankrun tests.
+export const createTokenAndMintToIxs = (
+  minimumLamports: number,
+  payer: PublicKey,
+  tokenMint: PublicKey,
+  decimals: number,
+  mintAuthority: PublicKey,
+  mintTo: Array<{ recepient: PublicKey; amount: number }>
+): Array<TransactionInstruction> => {
+  let createTokeIxs = [
+    SystemProgram.createAccount({
+      fromPubkey: payer,
+      newAccountPubkey: tokenMint,
+      lamports: minimumLamports,
+      space: MINT_SIZE,
+      programId: TOKEN_PROGRAM,
+    }),
+    createInitializeMint2Instruction(
+      tokenMint,
+      decimals,
+      mintAuthority,
+      null,
+      TOKEN_PROGRAM
+    ),
+  ];
+
+  let mintToIxs = mintTo.flatMap(({ recepient, amount }) => {
+    const ataAddress = getAssociatedTokenAddressSync(
+      tokenMint,
+      recepient,
+      true,
+      TOKEN_PROGRAM
+    );
+
+    return [
+      createAssociatedTokenAccountIdempotentInstruction(
+        payer,
+        ataAddress,
+        recepient,
+        tokenMint,
+        TOKEN_PROGRAM
+      ),
+      createMintToInstruction(
+        tokenMint,
+        ataAddress,
+        mintAuthority,
+        amount,
+        [],
+        TOKEN_PROGRAM
+      ),
+    ];
+  });
+
+  return [...createTokeIxs, ...mintToIxs];
+};
+
+export const getTokenBalanceOn = (
+  connection: Connection,
+) => async (
+  tokenAccountAddress: PublicKey,
+): Promise<BN> => {
+    const tokenBalance = await connection.getTokenAccountBalance(tokenAccountAddress);
+    return new BN(tokenBalance.value.amount);
+  };
+
+// `OfferTerms` with every optional condition switched off.  Tests override
+// just the fields they exercise.
+export const defaultOfferTerms = () => ({
+  allowlist: null,
+  paymentOptions: [],
+  rentReceiver: null,
+  minTakerReputation: null,
+  tranches: null,
+  vesting: null,
+  maxReferralBps: 0,
+  payRoyalties: false,
+  priceTrigger: null,
+  issueReceipts: false,
+});
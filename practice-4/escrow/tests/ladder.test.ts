@@ -0,0 +1,231 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getTokenBalanceOn,
+} from "./helpers";
+import { takeOfferAccounts } from "./bankrun";
+
+describe("escrow ladder offers", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+
+  const [alice, bob, carol, usdcMint, wifMint] = makeKeypairs(5);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const nextOfferId = async (maker: PublicKey) => {
+    const [makerCounterAddress] = PublicKey.findProgramAddressSync(
+      [Buffer.from("maker_counter"), maker.toBuffer()],
+      program.programId
+    );
+    const counter = await program.account.makerCounter.fetchNullable(
+      makerCounterAddress
+    );
+    return counter === null ? new BN(0) : counter.nextOfferId;
+  };
+
+  // Offers for the ids after the counter's, past those the maker already
+  // made offers with, along with their bumps.
+  const unusedOffers = async (maker: PublicKey, count: number) => {
+    const offers: Array<{ id: BN; address: PublicKey; bump: number }> = [];
+    for (
+      let id = await nextOfferId(maker);
+      offers.length < count;
+      id = id.addn(1)
+    ) {
+      const [address, bump] = PublicKey.findProgramAddressSync(
+        [Buffer.from("offer"), maker.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      if ((await connection.getAccountInfo(address)) === null) {
+        offers.push({ id, address, bump });
+      }
+    }
+    return offers;
+  };
+
+  // Selling USDC at 2.0, 2.5 and 3.0 WIF.
+  const levels = [
+    {
+      tokenAAmount: new BN(10_000_000),
+      tokenBWantedAmount: new BN(20_000_000),
+    },
+    {
+      tokenAAmount: new BN(10_000_000),
+      tokenBWantedAmount: new BN(25_000_000),
+    },
+    {
+      tokenAAmount: new BN(5_000_000),
+      tokenBWantedAmount: new BN(15_000_000),
+    },
+  ];
+
+  const makeLadder = async (
+    totalAmount: BN,
+    maker: Keypair = alice,
+    bumpOffset = 0
+  ) => {
+    const unused = await unusedOffers(maker.publicKey, levels.length);
+    const offers = unused.map(({ address }) => address);
+    await program.methods
+      .makeOfferLadder(
+        totalAmount,
+        levels.map((level, i) => ({
+          ...level,
+          offerId: unused[i].id,
+          offerBump: unused[i].bump - bumpOffset,
+        })),
+        defaultOfferTerms(),
+        0
+      )
+      .accounts({
+        maker: maker.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .remainingAccounts(
+        offers.flatMap((offer) =>
+          [offer, ata(usdcMint.publicKey, offer)].map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+      )
+      .signers([maker])
+      .rpc();
+    return offers;
+  };
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob, carol].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [
+          { recepient: alice.publicKey, amount: 100_000_000 },
+          { recepient: carol.publicKey, amount: 100_000_000 },
+        ]
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [{ recepient: bob.publicKey, amount: 100_000_000 }]
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+  });
+
+  test("Ladder splits the deposit across one offer per level", async () => {
+    const offers = await makeLadder(new BN(25_000_000));
+
+    expect(await nextOfferId(alice.publicKey)).toEqual(new BN(3));
+    for (const [i, offerAddress] of offers.entries()) {
+      const offer = await program.account.offer.fetch(offerAddress);
+      expect(offer.id).toEqual(new BN(i));
+      expect(offer.tokenAOfferedAmount).toEqual(levels[i].tokenAAmount);
+      expect(offer.tokenBWantedAmount).toEqual(levels[i].tokenBWantedAmount);
+      expect(
+        await getTokenBalanceOn(connection)(
+          ata(usdcMint.publicKey, offerAddress)
+        )
+      ).toEqual(levels[i].tokenAAmount);
+    }
+
+    // Levels are plain offers, taken one at a time.
+    await program.methods
+      .takeOffer(new BN(10_000_000), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offers[0],
+          tokenMintB: wifMint.publicKey,
+        })
+      )
+      .signers([bob])
+      .rpc();
+    const aliceWif = ata(wifMint.publicKey, alice.publicKey);
+    expect(await getTokenBalanceOn(connection)(aliceWif)).toEqual(
+      new BN(20_000_000)
+    );
+  });
+
+  test("Ladder ids skip those the maker already used", async () => {
+    // Carol made offer 1 by hand before her first ladder.
+    await program.methods
+      .makeOffer(
+        new BN(1),
+        new BN(1_000_000),
+        new BN(1_000_000),
+        defaultOfferTerms(),
+        0
+      )
+      .accounts({
+        maker: carol.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([carol])
+      .rpc();
+
+    const offers = await makeLadder(new BN(25_000_000), carol);
+
+    const ids: Array<BN> = [];
+    for (const offerAddress of offers) {
+      ids.push((await program.account.offer.fetch(offerAddress)).id);
+    }
+    expect(ids).toEqual([new BN(0), new BN(2), new BN(3)]);
+    expect(await nextOfferId(carol.publicKey)).toEqual(new BN(4));
+  });
+
+  test("Levels must carry the bump of their offer's address", async () => {
+    // Any other bump derives another address, or none at all.
+    await expect(
+      makeLadder(new BN(25_000_000), alice, 1)
+    ).rejects.toThrow(/LadderAccountMismatch/);
+  });
+
+  test("Levels must add up to the total amount", async () => {
+    await expect(makeLadder(new BN(30_000_000))).rejects.toThrow(
+      /LadderAmountMismatch/
+    );
+  });
+});
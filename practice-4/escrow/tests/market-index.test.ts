@@ -0,0 +1,182 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+import { closeOfferAccounts, takeOfferAccounts } from "./bankrun";
+
+describe("escrow market index", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+
+  const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+
+  const indexPageAddressFor = (page: number) => {
+    const pageSeed = Buffer.alloc(4);
+    pageSeed.writeUInt32LE(page);
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("market_index"),
+        usdcMint.publicKey.toBuffer(),
+        wifMint.publicKey.toBuffer(),
+        pageSeed,
+      ],
+      program.programId
+    )[0];
+  };
+
+  // Lists the market with plain `getAccountInfo` calls, following the links
+  // between pages.
+  const listOpenOffers = async (): Promise<Array<PublicKey>> => {
+    const offers: Array<PublicKey> = [];
+    let page: number | null = 0;
+    while (page !== null) {
+      const account = await connection.getAccountInfo(
+        indexPageAddressFor(page)
+      );
+      const indexPage = program.coder.accounts.decode(
+        "marketIndexPage",
+        account.data
+      );
+      offers.push(...indexPage.offers);
+      page = indexPage.nextPage;
+    }
+    return offers;
+  };
+
+  const makeOffer = async (page: number, previousPage: number | null) => {
+    const offerId = getRandomBigNumber();
+    // 10 USDC for 25 WIF.
+    await program.methods
+      .makeOffer(
+        offerId,
+        new BN(10_000_000),
+        new BN(25_000_000),
+        defaultOfferTerms(),
+        page
+      )
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage:
+          previousPage === null ? null : indexPageAddressFor(previousPage),
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        alice.publicKey.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+  };
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [{ recepient: alice.publicKey, amount: 100_000_000 }]
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [{ recepient: bob.publicKey, amount: 100_000_000 }]
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+  });
+
+  test("Offers are listed across linked pages until taken or closed", async () => {
+    const firstOffer = await makeOffer(0, null);
+    const secondOffer = await makeOffer(0, null);
+    const thirdOffer = await makeOffer(1, 0);
+
+    expect(await listOpenOffers()).toEqual([
+      firstOffer,
+      secondOffer,
+      thirdOffer,
+    ]);
+
+    // A partial fill leaves the offer listed.
+    const takeOffer = (tokenAAmount: BN) =>
+      program.methods
+        .takeOffer(tokenAAmount, [])
+        .accounts(
+          takeOfferAccounts({
+            taker: bob.publicKey,
+            offer: firstOffer,
+            tokenMintB: wifMint.publicKey,
+          })
+        )
+        .signers([bob])
+        .rpc();
+    await takeOffer(new BN(4_000_000));
+    expect(await listOpenOffers()).toHaveLength(3);
+
+    // Swap-remove moves the last offer of the page into the freed slot.
+    await takeOffer(new BN(6_000_000));
+    expect(await listOpenOffers()).toEqual([secondOffer, thirdOffer]);
+
+    await program.methods
+      .closeOffer()
+      .accounts(
+        closeOfferAccounts({
+          maker: alice.publicKey,
+          offer: thirdOffer,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: wifMint.publicKey,
+          makerTokenAccountA: getAssociatedTokenAddressSync(
+            usdcMint.publicKey,
+            alice.publicKey,
+            false,
+            TOKEN_PROGRAM
+          ),
+        })
+      )
+      .signers([alice])
+      .rpc();
+
+    expect(await listOpenOffers()).toEqual([secondOffer]);
+  });
+
+  test("Pages must be created in order", async () => {
+    await expect(makeOffer(3, null)).rejects.toThrow(/InvalidIndexPage/);
+  });
+});
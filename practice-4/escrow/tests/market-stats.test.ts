@@ -0,0 +1,166 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+import { closeOfferAccounts, takeOfferAccounts } from "./bankrun";
+
+describe("escrow market stats", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+
+  const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+
+  const [marketStatsAddress] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("market_stats"),
+      usdcMint.publicKey.toBuffer(),
+      wifMint.publicKey.toBuffer(),
+    ],
+    program.programId
+  );
+
+  const offerAddressFor = (offerId: BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        alice.publicKey.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const getMarketStats = () =>
+    program.methods
+      .getMarketStats()
+      .accounts({ marketStats: marketStatsAddress } as any)
+      .view();
+
+  const makeOffer = async (offerId: BN) => {
+    // 10 USDC for 25 WIF.
+    await program.methods
+      .makeOffer(
+        offerId,
+        new BN(10_000_000),
+        new BN(25_000_000),
+        defaultOfferTerms(),
+        0
+      )
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+    return offerAddressFor(offerId);
+  };
+
+  const takeOffer = (offerAddress: PublicKey, tokenAAmount: BN) =>
+    program.methods
+      .takeOffer(tokenAAmount, [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: wifMint.publicKey,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [{ recepient: alice.publicKey, amount: 100_000_000 }]
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [{ recepient: bob.publicKey, amount: 100_000_000 }]
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+  });
+
+  test("Stats follow offers being made, filled and closed", async () => {
+    const firstOffer = await makeOffer(getRandomBigNumber());
+    const secondOffer = await makeOffer(getRandomBigNumber());
+
+    expect((await getMarketStats()).openOffers).toEqual(new BN(2));
+
+    await takeOffer(firstOffer, new BN(4_000_000));
+
+    let stats = await getMarketStats();
+    expect(stats.openOffers).toEqual(new BN(2));
+    expect(stats.volumeA).toEqual(new BN(4_000_000));
+    expect(stats.volumeB).toEqual(new BN(10_000_000));
+    // 2.5 WIF per USDC, scaled by `PRICE_SCALE`.
+    expect(stats.lastPrice).toEqual(new BN(2_500_000_000));
+    expect(stats.lastTradeTimestamp.toNumber()).toBeGreaterThan(0);
+
+    await takeOffer(firstOffer, new BN(6_000_000));
+
+    stats = await getMarketStats();
+    expect(stats.openOffers).toEqual(new BN(1));
+    expect(stats.volumeA).toEqual(new BN(10_000_000));
+    expect(stats.volumeB).toEqual(new BN(25_000_000));
+
+    await program.methods
+      .closeOffer()
+      .accounts(
+        closeOfferAccounts({
+          maker: alice.publicKey,
+          offer: secondOffer,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: wifMint.publicKey,
+          makerTokenAccountA: getAssociatedTokenAddressSync(
+            usdcMint.publicKey,
+            alice.publicKey,
+            false,
+            TOKEN_PROGRAM
+          ),
+        })
+      )
+      .signers([alice])
+      .rpc();
+
+    expect((await getMarketStats()).openOffers).toEqual(new BN(0));
+  });
+});
@@ -0,0 +1,55 @@
+// Mirror of `programs/escrow/src/merkle.rs` and the `tools/allowlist` tree
+// builder, so tests can produce allowlist roots and proofs.
+
+import { PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
+
+const sha256 = (...parts: Array<Buffer>): Buffer =>
+  parts
+    .reduce((hash, part) => hash.update(part), createHash("sha256"))
+    .digest();
+
+export const leafHash = (wallet: PublicKey): Buffer =>
+  sha256(Buffer.from([0]), wallet.toBuffer());
+
+export const nodeHash = (a: Buffer, b: Buffer): Buffer => {
+  const [left, right] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return sha256(Buffer.from([1]), left, right);
+};
+
+export const buildAllowlist = (
+  wallets: Array<PublicKey>
+): { root: Buffer; proofFor: (wallet: PublicKey) => Array<Array<number>> } => {
+  const leaves = wallets.map(leafHash);
+  const levels: Array<Array<Buffer>> = [leaves];
+
+  while (levels[levels.length - 1].length > 1) {
+    const level = levels[levels.length - 1];
+    const next: Array<Buffer> = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(
+        i + 1 < level.length ? nodeHash(level[i], level[i + 1]) : level[i]
+      );
+    }
+    levels.push(next);
+  }
+
+  const proofFor = (wallet: PublicKey) => {
+    let index = wallets.findIndex((w) => w.equals(wallet));
+    if (index < 0) {
+      return [];
+    }
+
+    const proof: Array<Array<number>> = [];
+    for (const level of levels.slice(0, -1)) {
+      const sibling = level[index ^ 1];
+      if (sibling !== undefined) {
+        proof.push(Array.from(sibling));
+      }
+      index = Math.floor(index / 2);
+    }
+    return proof;
+  };
+
+  return { root: levels[levels.length - 1][0], proofFor };
+};
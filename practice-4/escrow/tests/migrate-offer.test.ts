@@ -0,0 +1,491 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  PublicKey,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  createAssociatedTokenAccountIdempotentInstruction,
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { TOKEN_PROGRAM, getRandomBigNumber } from "./helpers";
+import {
+  EscrowBankrun,
+  closeOfferAccounts,
+  createTokenAndMintTo,
+  fundWallets,
+  getLamports,
+  getTokenBalance,
+  rentFor,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+// Size of an offer in the layout used before versioning: discriminator, id,
+// maker, both mints, wanted amount and bump.
+const LEGACY_OFFER_SPACE = 8 + 8 + 32 * 3 + 8 + 1;
+
+// Size of an offer at version 1: the legacy fields, version, offered amount,
+// the allowlist, up to four payment options, the rent receiver and 64
+// reserved bytes.
+const VERSION_ONE_OFFER_SPACE =
+  LEGACY_OFFER_SPACE + 1 + 8 + 42 + 164 + 33 + 64;
+
+describe("escrow offer migration", () => {
+  let escrow: EscrowBankrun;
+
+  const [alice, bob, usdcMint, wifMint, bonkMint] = makeKeypairs(5);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const offerAddressFor = (maker: Keypair, offerId: BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        maker.publicKey.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      escrow.program.programId
+    );
+
+  // The fields every offer layout starts with.
+  const offerHeader = (
+    maker: Keypair,
+    offerId: BN,
+    wantedAmount: BN,
+    tokenMintB: PublicKey = wifMint.publicKey
+  ) => {
+    const { program } = escrow;
+    const [, bump] = offerAddressFor(maker, offerId);
+    const discriminator = program.idl.accounts.find(
+      (account) => account.name === "offer"
+    ).discriminator;
+    return Buffer.concat([
+      Buffer.from(discriminator),
+      offerId.toArrayLike(Buffer, "le", 8),
+      maker.publicKey.toBuffer(),
+      usdcMint.publicKey.toBuffer(),
+      tokenMintB.toBuffer(),
+      wantedAmount.toArrayLike(Buffer, "le", 8),
+      Buffer.from([bump]),
+    ]);
+  };
+
+  // Writes `data` as an offer straight into bank state, together with a
+  // funded vault, as a previous program version would have left it.
+  const writeOffer = async (
+    maker: Keypair,
+    offerId: BN,
+    data: Buffer,
+    offeredAmount: number
+  ): Promise<{ offerAddress: PublicKey; vaultAddress: PublicKey }> => {
+    const { context, program, provider } = escrow;
+    const [offerAddress] = offerAddressFor(maker, offerId);
+
+    context.setAccount(offerAddress, {
+      lamports: await rentFor(escrow, data.length),
+      data,
+      owner: program.programId,
+      executable: false,
+    });
+
+    const vaultAddress = ata(usdcMint.publicKey, offerAddress);
+    const tx = new Transaction();
+    tx.instructions = [
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        vaultAddress,
+        offerAddress,
+        usdcMint.publicKey,
+        TOKEN_PROGRAM
+      ),
+      createMintToInstruction(
+        usdcMint.publicKey,
+        vaultAddress,
+        alice.publicKey,
+        offeredAmount,
+        [],
+        TOKEN_PROGRAM
+      ),
+    ];
+    await provider.sendAndConfirm(tx, [alice]);
+
+    return { offerAddress, vaultAddress };
+  };
+
+  const writeLegacyOffer = (
+    maker: Keypair,
+    offeredAmount: number,
+    wantedAmount: BN
+  ) => {
+    const offerId = getRandomBigNumber();
+    const data = offerHeader(maker, offerId, wantedAmount);
+    expect(data.length).toEqual(LEGACY_OFFER_SPACE);
+    return writeOffer(maker, offerId, data, offeredAmount);
+  };
+
+  // An offer without any terms at `version`, zeroed past the offered amount.
+  const writeVersionedOffer = (
+    version: number,
+    space: number,
+    maker: Keypair,
+    offeredAmount: number,
+    wantedAmount: BN,
+    tokenMintB?: PublicKey
+  ) => {
+    const offerId = getRandomBigNumber();
+    const data = Buffer.alloc(space);
+    Buffer.concat([
+      offerHeader(maker, offerId, wantedAmount, tokenMintB),
+      Buffer.from([version]),
+      new BN(offeredAmount).toArrayLike(Buffer, "le", 8),
+    ]).copy(data);
+    return writeOffer(maker, offerId, data, offeredAmount);
+  };
+
+  const writeVersionOneOffer = (
+    maker: Keypair,
+    offeredAmount: number,
+    wantedAmount: BN
+  ) =>
+    writeVersionedOffer(
+      1,
+      VERSION_ONE_OFFER_SPACE,
+      maker,
+      offeredAmount,
+      wantedAmount
+    );
+
+  const takeOffer = (
+    offerAddress: PublicKey,
+    tokenAAmount: number,
+    accounts: Record<string, PublicKey | null> = {}
+  ) =>
+    escrow.program.methods
+      .takeOffer(new BN(tokenAAmount), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: wifMint.publicKey,
+          ...accounts,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  const migrateOffer = (
+    maker: Keypair,
+    offerAddress: PublicKey,
+    vaultAddress: PublicKey,
+    tokenMintB: PublicKey = wifMint.publicKey
+  ) =>
+    escrow.program.methods
+      .migrateOffer(0)
+      .accounts({
+        maker: maker.publicKey,
+        offer: offerAddress,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB,
+        vault: vaultAddress,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .signers([maker])
+      .rpc();
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob]);
+
+    await createTokenAndMintTo(escrow, usdcMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 0 },
+    ]);
+    await createTokenAndMintTo(escrow, wifMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 100_000_000 },
+    ]);
+    await createTokenAndMintTo(escrow, bonkMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 100_000_000 },
+    ]);
+  });
+
+  test("Legacy offer is migrated at the maker's expense", async () => {
+    const { offerAddress, vaultAddress } = await writeLegacyOffer(
+      alice,
+      10_000_000,
+      new BN(100_000_000)
+    );
+
+    const aliceBefore = await getLamports(escrow, alice.publicKey);
+    const offerBefore = await getLamports(escrow, offerAddress);
+
+    await migrateOffer(alice, offerAddress, vaultAddress);
+
+    const offerAfter = await getLamports(escrow, offerAddress);
+    expect(offerAfter).toBeGreaterThan(offerBefore);
+
+    // Legacy offers predate the market's stats, its index and the maker's
+    // reputation, and left the maker's token B ATA to takers, so the first
+    // migration creates them as well.
+    const pda = (...seeds: Array<Buffer>) =>
+      PublicKey.findProgramAddressSync(seeds, escrow.program.programId)[0];
+    const market = [
+      usdcMint.publicKey.toBuffer(),
+      wifMint.publicKey.toBuffer(),
+    ];
+    let createdRent = 0;
+    for (const address of [
+      pda(Buffer.from("market_stats"), ...market),
+      pda(Buffer.from("market_index"), ...market, Buffer.alloc(4)),
+      pda(Buffer.from("reputation"), alice.publicKey.toBuffer()),
+      ata(wifMint.publicKey, alice.publicKey),
+    ]) {
+      createdRent += await getLamports(escrow, address);
+    }
+    expect(await getLamports(escrow, alice.publicKey)).toEqual(
+      aliceBefore - (offerAfter - offerBefore) - createdRent
+    );
+
+    const offerAccount = await escrow.provider.connection.getAccountInfo(
+      offerAddress
+    );
+    expect(offerAfter).toEqual(
+      await rentFor(escrow, offerAccount.data.length)
+    );
+
+    const offer = await escrow.program.account.offer.fetch(offerAddress);
+    expect(offer.version).toEqual(2);
+    expect(offer.maker).toEqual(alice.publicKey);
+    expect(offer.tokenBWantedAmount).toEqual(new BN(100_000_000));
+    expect(offer.tokenAOfferedAmount).toEqual(new BN(10_000_000));
+    expect(offer.paymentOptions).toEqual([]);
+  });
+
+  test("Migrated offer can be taken", async () => {
+    const { offerAddress, vaultAddress } = await writeLegacyOffer(
+      alice,
+      10_000_000,
+      new BN(100_000_000)
+    );
+    await migrateOffer(alice, offerAddress, vaultAddress);
+
+    await takeOffer(offerAddress, 10_000_000);
+
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, bob.publicKey))
+    ).toEqual(new BN(10_000_000));
+    expect(
+      await getTokenBalance(escrow, ata(wifMint.publicKey, alice.publicKey))
+    ).toEqual(new BN(100_000_000));
+  });
+
+  test("Offers in an older version cannot be taken", async () => {
+    const { offerAddress } = await writeVersionOneOffer(
+      alice,
+      1_000_000,
+      new BN(1_000_000)
+    );
+
+    await expect(takeOffer(offerAddress, 1_000_000)).rejects.toThrow(
+      /OutdatedOffer/
+    );
+  });
+
+  test("Version 1 offer is grown to the current version and can be closed", async () => {
+    const { offerAddress, vaultAddress } = await writeVersionOneOffer(
+      alice,
+      1_000_000,
+      new BN(2_000_000)
+    );
+
+    await migrateOffer(alice, offerAddress, vaultAddress);
+
+    const offerAccount = await escrow.provider.connection.getAccountInfo(
+      offerAddress
+    );
+    expect(offerAccount.data.length).toBeGreaterThan(VERSION_ONE_OFFER_SPACE);
+    expect(await getLamports(escrow, offerAddress)).toEqual(
+      await rentFor(escrow, offerAccount.data.length)
+    );
+
+    const offer = await escrow.program.account.offer.fetch(offerAddress);
+    expect(offer.version).toEqual(2);
+    expect(offer.maker).toEqual(alice.publicKey);
+    expect(offer.tokenBWantedAmount).toEqual(new BN(2_000_000));
+    expect(offer.tokenAOfferedAmount).toEqual(new BN(1_000_000));
+
+    await escrow.program.methods
+      .closeOffer()
+      .accounts(
+        closeOfferAccounts({
+          maker: alice.publicKey,
+          offer: offerAddress,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: wifMint.publicKey,
+          makerTokenAccountA: ata(usdcMint.publicKey, alice.publicKey),
+        })
+      )
+      .signers([alice])
+      .rpc();
+
+    expect(
+      await escrow.provider.connection.getAccountInfo(offerAddress)
+    ).toBeNull();
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, alice.publicKey))
+    ).toEqual(new BN(1_000_000));
+  });
+
+  // The USDC / BONK market has never had an offer made through the program,
+  // so none of its bookkeeping accounts exist until the first migration.
+  const bonkMarket = () => {
+    const pda = (...seeds: Array<Buffer>) =>
+      PublicKey.findProgramAddressSync(seeds, escrow.program.programId)[0];
+    const market = [
+      usdcMint.publicKey.toBuffer(),
+      bonkMint.publicKey.toBuffer(),
+    ];
+    return {
+      marketStats: pda(Buffer.from("market_stats"), ...market),
+      marketIndexPage: pda(
+        Buffer.from("market_index"),
+        ...market,
+        Buffer.alloc(4)
+      ),
+    };
+  };
+
+  test("Version 1 offers made before the market's stats and index are counted and listed on migration", async () => {
+    const { marketStats, marketIndexPage } = bonkMarket();
+    const { offerAddress, vaultAddress } = await writeVersionedOffer(
+      1,
+      VERSION_ONE_OFFER_SPACE,
+      alice,
+      1_000_000,
+      new BN(1_000_000),
+      bonkMint.publicKey
+    );
+
+    await migrateOffer(alice, offerAddress, vaultAddress, bonkMint.publicKey);
+
+    const { program } = escrow;
+    const openOffers = async () =>
+      (await program.account.marketStats.fetch(marketStats)).openOffers;
+    const listed = async () =>
+      (await program.account.marketIndexPage.fetch(marketIndexPage)).offers;
+    expect(await openOffers()).toEqual(new BN(1));
+    expect(await listed()).toContainEqual(offerAddress);
+
+    await takeOffer(offerAddress, 400_000, {
+      tokenMintB: bonkMint.publicKey,
+    });
+    const stats = await program.account.marketStats.fetch(marketStats);
+    expect(stats.volumeA).toEqual(new BN(400_000));
+    expect(stats.volumeB).toEqual(new BN(400_000));
+
+    await program.methods
+      .closeOffer()
+      .accounts(
+        closeOfferAccounts({
+          maker: alice.publicKey,
+          offer: offerAddress,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: bonkMint.publicKey,
+          makerTokenAccountA: ata(usdcMint.publicKey, alice.publicKey),
+        })
+      )
+      .signers([alice])
+      .rpc();
+
+    expect(await openOffers()).toEqual(new BN(0));
+    expect(await listed()).not.toContainEqual(offerAddress);
+  });
+
+  test("Version 1 offers already listed are not counted again", async () => {
+    const { marketStats, marketIndexPage } = bonkMarket();
+    const { program, context } = escrow;
+    const { offerAddress, vaultAddress } = await writeVersionedOffer(
+      1,
+      VERSION_ONE_OFFER_SPACE,
+      alice,
+      2_000_000,
+      new BN(2_000_000),
+      bonkMint.publicKey
+    );
+
+    // List the offer, as `make_offer` did for offers made after the index.
+    const pageAccount = await context.banksClient.getAccount(marketIndexPage);
+    const page = await program.account.marketIndexPage.fetch(marketIndexPage);
+    const data = Buffer.alloc(pageAccount.data.length);
+    (
+      await program.coder.accounts.encode("marketIndexPage", {
+        ...page,
+        offers: [...page.offers, offerAddress],
+      })
+    ).copy(data);
+    context.setAccount(marketIndexPage, { ...pageAccount, data });
+    const openBefore = (await program.account.marketStats.fetch(marketStats))
+      .openOffers;
+
+    await migrateOffer(alice, offerAddress, vaultAddress, bonkMint.publicKey);
+
+    const pageAfter = await program.account.marketIndexPage.fetch(
+      marketIndexPage
+    );
+    expect(
+      pageAfter.offers.filter((offer) => offer.equals(offerAddress))
+    ).toHaveLength(1);
+    expect(
+      (await program.account.marketStats.fetch(marketStats)).openOffers
+    ).toEqual(openBefore);
+  });
+
+  test("Only the maker can migrate an offer", async () => {
+    const { offerAddress, vaultAddress } = await writeLegacyOffer(
+      alice,
+      1_000_000,
+      new BN(1_000_000)
+    );
+
+    await expect(migrateOffer(bob, offerAddress, vaultAddress)).rejects.toThrow(
+      /NotLegacyOffer/
+    );
+  });
+
+  test("Offer cannot be migrated twice", async () => {
+    const { offerAddress, vaultAddress } = await writeLegacyOffer(
+      alice,
+      1_000_000,
+      new BN(1_000_000)
+    );
+    await migrateOffer(alice, offerAddress, vaultAddress);
+
+    // Bankrun does not advance the blockhash between transactions, so the
+    // retry needs a different instruction list to not be deduplicated.
+    await expect(
+      escrow.program.methods
+        .migrateOffer(0)
+        .accounts({
+          maker: alice.publicKey,
+          offer: offerAddress,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: wifMint.publicKey,
+          vault: vaultAddress,
+          previousIndexPage: null,
+          tokenProgram: TOKEN_PROGRAM,
+        } as any)
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 }),
+        ])
+        .signers([alice])
+        .rpc()
+    ).rejects.toThrow(/NotLegacyOffer/);
+  });
+});
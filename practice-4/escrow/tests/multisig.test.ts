@@ -0,0 +1,269 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+import {
+  EscrowBankrun,
+  closeOfferAccounts,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  startEscrow,
+} from "./bankrun";
+
+const TOKEN = 1_000_000;
+
+describe("escrow multisig makers", () => {
+  let escrow: EscrowBankrun;
+  let signerSet: PublicKey;
+  let authority: PublicKey;
+
+  // Alice, Bob and Carol run a 2-of-3 treasury.  Dave is not part of it.
+  const [alice, bob, carol, dave, usdcMint, wifMint] = makeKeypairs(6);
+  const offerId = getRandomBigNumber();
+  let offerAddress: PublicKey;
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const proposalAddress = (id: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("proposal"),
+        signerSet.toBuffer(),
+        new BN(id).toArrayLike(Buffer, "le", 8),
+      ],
+      escrow.program.programId
+    )[0];
+
+  const propose = (proposer: Keypair, action: any) =>
+    escrow.program.methods
+      .propose(action)
+      .accounts({ proposer: proposer.publicKey, signerSet } as any)
+      .signers([proposer])
+      .rpc();
+
+  const approve = (member: Keypair, proposalId: number) =>
+    escrow.program.methods
+      .approveProposal()
+      .accounts({
+        member: member.publicKey,
+        signerSet,
+        proposal: proposalAddress(proposalId),
+      } as any)
+      .signers([member])
+      .rpc();
+
+  // The escrow instruction the proposal runs, with the authority's signature
+  // left to the escrow.  Bankrun drops a transaction identical to one it has
+  // already seen, so every attempt asks for a different compute limit.
+  let executions = 0;
+  const execute = (
+    proposalId: number,
+    proposer: Keypair,
+    instruction: TransactionInstruction
+  ) =>
+    escrow.program.methods
+      .executeProposal()
+      .accounts({
+        signerSet,
+        proposal: proposalAddress(proposalId),
+        proposer: proposer.publicKey,
+        escrowProgram: escrow.program.programId,
+      } as any)
+      .remainingAccounts(
+        instruction.keys.map((key) => ({ ...key, isSigner: false }))
+      )
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({
+          units: 400_000 + ++executions,
+        }),
+      ])
+      .rpc();
+
+  const makeOfferInstruction = (tokenMintA: PublicKey, tokenMintB: PublicKey) =>
+    escrow.program.methods
+      .makeOffer(
+        offerId,
+        new BN(1_000 * TOKEN),
+        new BN(2_000 * TOKEN),
+        defaultOfferTerms(),
+        0
+      )
+      .accounts({
+        maker: authority,
+        tokenMintA,
+        tokenMintB,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .instruction();
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob, carol, dave]);
+
+    const signerSetId = getRandomBigNumber();
+    await escrow.program.methods
+      .createSignerSet(
+        signerSetId,
+        [alice.publicKey, bob.publicKey, carol.publicKey],
+        2
+      )
+      .accounts({ creator: alice.publicKey })
+      .signers([alice])
+      .rpc();
+    [signerSet] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("signer_set"),
+        alice.publicKey.toBuffer(),
+        signerSetId.toArrayLike(Buffer, "le", 8),
+      ],
+      escrow.program.programId
+    );
+    [authority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("authority"), signerSet.toBuffer()],
+      escrow.program.programId
+    );
+    [offerAddress] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        authority.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      escrow.program.programId
+    );
+
+    // The treasury holds the SOL for rent and the USDC it sells.
+    escrow.context.setAccount(authority, {
+      lamports: 10 * LAMPORTS_PER_SOL,
+      data: Buffer.alloc(0),
+      owner: SystemProgram.programId,
+      executable: false,
+    });
+    await createTokenAndMintTo(escrow, usdcMint, 6, alice, [
+      { recepient: authority, amount: 1_000 * TOKEN },
+    ]);
+    await createTokenAndMintTo(escrow, wifMint, 6, dave, [
+      { recepient: dave.publicKey, amount: 0 },
+    ]);
+  });
+
+  test("Only members can propose", async () => {
+    await expect(
+      propose(dave, { closeOffer: { offer: offerAddress } })
+    ).rejects.toThrow(/NotSignerSetMember/);
+  });
+
+  test("An offer is made only once the threshold approves it", async () => {
+    await propose(alice, {
+      makeOffer: {
+        id: offerId,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        tokenAOfferedAmount: new BN(1_000 * TOKEN),
+        tokenBWantedAmount: new BN(2_000 * TOKEN),
+        terms: defaultOfferTerms(),
+        indexPage: 0,
+      },
+    });
+    const instruction = await makeOfferInstruction(
+      usdcMint.publicKey,
+      wifMint.publicKey
+    );
+
+    await expect(execute(0, alice, instruction)).rejects.toThrow(
+      /ThresholdNotMet/
+    );
+    await expect(approve(alice, 0)).rejects.toThrow(/AlreadyApproved/);
+    await expect(approve(dave, 0)).rejects.toThrow(/NotSignerSetMember/);
+
+    await approve(bob, 0);
+    await expect(
+      execute(
+        0,
+        alice,
+        await makeOfferInstruction(wifMint.publicKey, usdcMint.publicKey)
+      )
+    ).rejects.toThrow(/ProposalAccountMismatch/);
+    await execute(0, alice, instruction);
+
+    const offer = await escrow.program.account.offer.fetch(offerAddress);
+    expect(offer.maker).toEqual(authority);
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, offerAddress))
+    ).toEqual(new BN(1_000 * TOKEN));
+    expect(
+      await escrow.provider.connection.getAccountInfo(proposalAddress(0))
+    ).toBeNull();
+  });
+
+  test("Members cannot change the offer on their own", async () => {
+    await expect(
+      escrow.program.methods
+        .amendOffer(new BN(1))
+        .accounts({ maker: alice.publicKey, offer: offerAddress } as any)
+        .signers([alice])
+        .rpc()
+    ).rejects.toThrow();
+  });
+
+  test("Amending and closing go through proposals too", async () => {
+    await propose(bob, {
+      amendOffer: { offer: offerAddress, tokenBWantedAmount: new BN(2_500) },
+    });
+    await approve(carol, 1);
+    await execute(
+      1,
+      bob,
+      await escrow.program.methods
+        .amendOffer(new BN(2_500))
+        .accounts({ maker: authority, offer: offerAddress } as any)
+        .instruction()
+    );
+    const offer = await escrow.program.account.offer.fetch(offerAddress);
+    expect(offer.tokenBWantedAmount).toEqual(new BN(2_500));
+
+    await propose(alice, { closeOffer: { offer: offerAddress } });
+    await approve(carol, 2);
+    await execute(
+      2,
+      alice,
+      await escrow.program.methods
+        .closeOffer()
+        .accounts(
+          closeOfferAccounts({
+            maker: authority,
+            offer: offerAddress,
+            vault: ata(usdcMint.publicKey, offerAddress),
+            tokenMintA: usdcMint.publicKey,
+            tokenMintB: wifMint.publicKey,
+            makerTokenAccountA: ata(usdcMint.publicKey, authority),
+          })
+        )
+        .instruction()
+    );
+
+    expect(
+      await escrow.provider.connection.getAccountInfo(offerAddress)
+    ).toBeNull();
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, authority))
+    ).toEqual(new BN(1_000 * TOKEN));
+  });
+});
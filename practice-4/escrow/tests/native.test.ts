@@ -0,0 +1,255 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { statSync } from "fs";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { Escrow } from "../target/types/escrow";
+import {
+  TOKEN_PROGRAM,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+import {
+  EscrowBankrun,
+  closeOfferAccounts,
+  computeUnitsFor,
+  createTokenAndMintTo,
+  escrowProgramAt,
+  fundWallets,
+  getTokenBalance,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+const TOKEN = 1_000_000;
+
+// Offset of `Offer::bump`, the one byte that differs between the builds'
+// copies of the same offer, as their PDAs differ.
+const OFFER_BUMP_OFFSET = 8 + 8 + 32 * 3 + 8;
+
+// The same IDL client drives the Anchor build and `programs/escrow-native`,
+// built with `yarn build:native`.
+describe("escrow native build", () => {
+  let escrow: EscrowBankrun;
+  let builds: { anchor: Program<Escrow>; native: Program<Escrow> };
+
+  const nativeProgramId = Keypair.generate().publicKey;
+  const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const offerAddress = (program: Program<Escrow>, offerId: BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        alice.publicKey.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const makeOffer = (
+    program: Program<Escrow>,
+    offerId: BN,
+    rentReceiver: PublicKey | null = null
+  ) =>
+    program.methods
+      .makeOffer(
+        offerId,
+        new BN(100 * TOKEN),
+        new BN(250 * TOKEN),
+        { ...defaultOfferTerms(), rentReceiver },
+        0
+      )
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      });
+
+  const takeOffer = (
+    program: Program<Escrow>,
+    offerId: BN,
+    tokenAAmount: number
+  ) =>
+    program.methods.takeOffer(new BN(tokenAAmount), []).accounts(
+      takeOfferAccounts({
+        taker: bob.publicKey,
+        offer: offerAddress(program, offerId),
+        tokenMintB: wifMint.publicKey,
+      })
+    );
+
+  const closeOffer = (program: Program<Escrow>, offerId: BN) => {
+    const offer = offerAddress(program, offerId);
+    return program.methods.closeOffer().accounts(
+      closeOfferAccounts({
+        maker: alice.publicKey,
+        offer,
+        vault: ata(usdcMint.publicKey, offer),
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        makerTokenAccountA: ata(usdcMint.publicKey, alice.publicKey),
+      })
+    );
+  };
+
+  beforeAll(async () => {
+    escrow = await startEscrow([
+      { name: "escrow_native", programId: nativeProgramId },
+    ]);
+    builds = {
+      anchor: escrow.program,
+      native: escrowProgramAt(escrow, nativeProgramId),
+    };
+    fundWallets(escrow, [alice, bob]);
+
+    // Every token account exists up front, so the benchmarks below measure
+    // the escrow rather than ATA creation.
+    await createTokenAndMintTo(escrow, usdcMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 10_000 * TOKEN },
+      { recepient: bob.publicKey, amount: 0 },
+    ]);
+    await createTokenAndMintTo(escrow, wifMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 10_000 * TOKEN },
+      { recepient: alice.publicKey, amount: 0 },
+    ]);
+  });
+
+  test("Native offers are taken in parts and closed like Anchor ones", async () => {
+    const { native } = builds;
+    const takenId = getRandomBigNumber();
+    const closedId = getRandomBigNumber();
+    await makeOffer(native, takenId).signers([alice]).rpc();
+    await makeOffer(native, closedId).signers([alice]).rpc();
+
+    const offer = await native.account.offer.fetch(
+      offerAddress(native, takenId)
+    );
+    expect(offer.maker).toEqual(alice.publicKey);
+    expect(offer.tokenAOfferedAmount).toEqual(new BN(100 * TOKEN));
+    expect(offer.version).toEqual(2);
+
+    await takeOffer(native, takenId, 40 * TOKEN).signers([bob]).rpc();
+    await takeOffer(native, takenId, 60 * TOKEN).signers([bob]).rpc();
+    await closeOffer(native, closedId).signers([alice]).rpc();
+
+    expect(
+      await getTokenBalance(escrow, ata(wifMint.publicKey, alice.publicKey))
+    ).toEqual(new BN(250 * TOKEN));
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, bob.publicKey))
+    ).toEqual(new BN(100 * TOKEN));
+    for (const offerId of [takenId, closedId]) {
+      expect(
+        await escrow.provider.connection.getAccountInfo(
+          offerAddress(native, offerId)
+        )
+      ).toBeNull();
+    }
+
+    const [marketStats] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("market_stats"),
+        usdcMint.publicKey.toBuffer(),
+        wifMint.publicKey.toBuffer(),
+      ],
+      native.programId
+    );
+    const stats = await native.account.marketStats.fetch(marketStats);
+    expect(stats.openOffers).toEqual(new BN(0));
+    expect(stats.volumeA).toEqual(new BN(100 * TOKEN));
+    expect(stats.volumeB).toEqual(new BN(250 * TOKEN));
+
+    const [makerReputation] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reputation"), alice.publicKey.toBuffer()],
+      native.programId
+    );
+    const reputation = await native.account.reputation.fetch(makerReputation);
+    expect(reputation.sales).toEqual(new BN(2));
+    expect(reputation.cancellations).toEqual(new BN(1));
+  });
+
+  test("Both builds write the same offer bytes", async () => {
+    const offerId = getRandomBigNumber();
+    const data: Array<Buffer> = [];
+    for (const program of [builds.anchor, builds.native]) {
+      await makeOffer(program, offerId).signers([alice]).rpc();
+      const account = await escrow.provider.connection.getAccountInfo(
+        offerAddress(program, offerId)
+      );
+      data.push(
+        Buffer.from(account.data).fill(
+          0,
+          OFFER_BUMP_OFFSET,
+          OFFER_BUMP_OFFSET + 1
+        )
+      );
+    }
+    expect(data[1].length).toEqual(data[0].length);
+    expect(data[1].equals(data[0])).toBe(true);
+  });
+
+  // `UnsupportedByNativeBuild`, which only the native build has, so the IDL
+  // does not name it.
+  test("Offers with terms are left to the Anchor build", async () => {
+    await expect(
+      makeOffer(builds.native, getRandomBigNumber(), bob.publicKey)
+        .signers([alice])
+        .rpc()
+    ).rejects.toThrow(/0x2328/);
+  });
+
+  // Only measures what the native build supports: plain offers, with every
+  // `OfferTerms` option off apart from the referral cap, taken without a
+  // referrer, vesting or an allowlist fill record.  Offers with any other
+  // term only run on the Anchor build, at its cost.
+  test("Native build uses less compute and binary size", async () => {
+    const units: Record<string, Record<string, number>> = {};
+    for (const [name, program] of [
+      ["anchor", builds.anchor],
+      ["native", builds.native],
+    ] as const) {
+      const takenId = getRandomBigNumber();
+      const closedId = getRandomBigNumber();
+      units[name] = {
+        makeOffer: await computeUnitsFor(
+          escrow,
+          await makeOffer(program, takenId).instruction(),
+          [alice]
+        ),
+      };
+      await makeOffer(program, closedId).signers([alice]).rpc();
+      units[name].takeOffer = await computeUnitsFor(
+        escrow,
+        await takeOffer(program, takenId, 100 * TOKEN).instruction(),
+        [bob]
+      );
+      units[name].closeOffer = await computeUnitsFor(
+        escrow,
+        await closeOffer(program, closedId).instruction(),
+        [alice]
+      );
+    }
+
+    const binarySize = (name: string) =>
+      statSync(`${__dirname}/../target/deploy/${name}.so`).size;
+    const sizes = {
+      anchor: binarySize("escrow"),
+      native: binarySize("escrow_native"),
+    };
+
+    for (const instruction of ["makeOffer", "takeOffer", "closeOffer"]) {
+      expect(units.native[instruction]).toBeLessThan(
+        units.anchor[instruction]
+      );
+    }
+    expect(sizes.native).toBeLessThan(sizes.anchor);
+  });
+});
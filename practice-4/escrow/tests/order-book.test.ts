@@ -0,0 +1,322 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  getTokenBalanceOn,
+} from "./helpers";
+
+// WIF (token A) quoted in USDC (token B).  Prices are USDC per WIF, scaled by
+// `PRICE_SCALE`.
+describe("escrow order book", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+
+  const [alice, bob, carol, wifMint, usdcMint] = makeKeypairs(5);
+
+  const PRICE_SCALE = 1_000_000_000;
+  const price = (usdcPerWif: number) => new BN(usdcPerWif * PRICE_SCALE);
+
+  const ata = (mint: Keypair, owner: Keypair) =>
+    getAssociatedTokenAddressSync(
+      mint.publicKey,
+      owner.publicKey,
+      false,
+      TOKEN_PROGRAM
+    );
+
+  const pda = (...seeds: Array<Buffer>) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const orderBook = pda(
+    Buffer.from("order_book"),
+    wifMint.publicKey.toBuffer(),
+    usdcMint.publicKey.toBuffer()
+  );
+  const bookSideAddressFor = (side: "bid" | "ask") =>
+    pda(Buffer.from(side === "bid" ? "bids" : "asks"), orderBook.toBuffer());
+  const balanceAddressFor = (owner: Keypair) =>
+    pda(
+      Buffer.from("balance"),
+      orderBook.toBuffer(),
+      owner.publicKey.toBuffer()
+    );
+
+  const placeOrder = (
+    owner: Keypair,
+    side: "bid" | "ask",
+    orderPrice: BN,
+    wifAmount: number,
+    evictedBalance: PublicKey | null = null
+  ) => {
+    const depositMint = side === "ask" ? wifMint : usdcMint;
+    return program.methods
+      .placeOrder(
+        side === "ask" ? { ask: {} } : { bid: {} },
+        orderPrice,
+        new BN(wifAmount)
+      )
+      .accounts({
+        owner: owner.publicKey,
+        orderBook,
+        // Seeded by the `side` argument, which the client cannot resolve.
+        bookSide: bookSideAddressFor(side),
+        depositMint: depositMint.publicKey,
+        ownerTokenAccount: ata(depositMint, owner),
+        vault: getAssociatedTokenAddressSync(
+          depositMint.publicKey,
+          orderBook,
+          true,
+          TOKEN_PROGRAM
+        ),
+        evictedBalance,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .signers([owner])
+      .rpc();
+  };
+
+  const getBalance = (owner: Keypair) =>
+    program.account.userBalance.fetch(balanceAddressFor(owner));
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob, carol].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        alice.publicKey,
+        [alice, carol].map((owner) => ({
+          recepient: owner.publicKey,
+          amount: 100_000_000,
+        }))
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        bob.publicKey,
+        [{ recepient: bob.publicKey, amount: 100_000_000 }]
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [wifMint, usdcMint]);
+
+    await program.methods
+      .createOrderBook()
+      .accounts({
+        payer: provider.publicKey,
+        tokenMintA: wifMint.publicKey,
+        tokenMintB: usdcMint.publicKey,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .rpc();
+  });
+
+  test("Crossing orders fill in price-time priority", async () => {
+    // Same price, Alice first.
+    await placeOrder(alice, "ask", price(2), 10_000_000);
+    await placeOrder(carol, "ask", price(2), 5_000_000);
+    // Bob deposits 12 * 2.5 = 30 USDC.
+    await placeOrder(bob, "bid", price(2.5), 12_000_000);
+
+    const bobUsdc = await getTokenBalanceOn(connection)(ata(usdcMint, bob));
+    expect(bobUsdc).toEqual(new BN(70_000_000));
+
+    await program.methods
+      .matchOrders(10)
+      .accounts({ orderBook })
+      .remainingAccounts(
+        [alice, bob, carol].map((owner) => ({
+          pubkey: balanceAddressFor(owner),
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
+      .rpc();
+
+    // The asks rested first, so both fills trade at their price of 2 USDC.
+    expect((await getBalance(alice)).tokenBFree).toEqual(new BN(20_000_000));
+    expect((await getBalance(carol)).tokenBFree).toEqual(new BN(4_000_000));
+    const bobBalance = await getBalance(bob);
+    expect(bobBalance.tokenAFree).toEqual(new BN(12_000_000));
+    // 30 USDC deposited, 24 spent.
+    expect(bobBalance.tokenBFree).toEqual(new BN(6_000_000));
+
+    const asks = await program.account.bookSide.fetch(
+      bookSideAddressFor("ask")
+    );
+    expect(asks.orders).toHaveLength(1);
+    expect(asks.orders[0].owner).toEqual(carol.publicKey);
+    expect(asks.orders[0].tokenAAmount).toEqual(new BN(3_000_000));
+    const bids = await program.account.bookSide.fetch(
+      bookSideAddressFor("bid")
+    );
+    expect(bids.orders).toHaveLength(0);
+
+    await program.methods
+      .withdrawBalance()
+      .accounts({
+        owner: bob.publicKey,
+        tokenMintA: wifMint.publicKey,
+        tokenMintB: usdcMint.publicKey,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .signers([bob])
+      .rpc();
+
+    expect(await getTokenBalanceOn(connection)(ata(wifMint, bob))).toEqual(
+      new BN(12_000_000)
+    );
+    expect(await getTokenBalanceOn(connection)(ata(usdcMint, bob))).toEqual(
+      new BN(76_000_000)
+    );
+  });
+
+  test("Cancelled orders are credited back to their owner", async () => {
+    const asks = await program.account.bookSide.fetch(
+      bookSideAddressFor("ask")
+    );
+
+    await program.methods
+      .cancelOrder({ ask: {} }, asks.orders[0].orderId)
+      .accounts({
+        owner: carol.publicKey,
+        orderBook,
+        bookSide: bookSideAddressFor("ask"),
+      } as any)
+      .signers([carol])
+      .rpc();
+
+    expect((await getBalance(carol)).tokenAFree).toEqual(new BN(3_000_000));
+  });
+
+  test("Matching needs the balance of every owner it settles", async () => {
+    await placeOrder(alice, "ask", price(1), 1_000_000);
+    await placeOrder(bob, "bid", price(1), 1_000_000);
+
+    await expect(
+      program.methods
+        .matchOrders(10)
+        .accounts({ orderBook })
+        .remainingAccounts([
+          {
+            pubkey: balanceAddressFor(alice),
+            isSigner: false,
+            isWritable: true,
+          },
+        ])
+        .rpc()
+    ).rejects.toThrow(/UserBalanceMismatch/);
+  });
+
+  const matchOrders = () =>
+    program.methods
+      .matchOrders(10)
+      .accounts({ orderBook })
+      .remainingAccounts(
+        [alice, bob, carol].map((owner) => ({
+          pubkey: balanceAddressFor(owner),
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
+      .rpc();
+
+  const fetchSide = async (side: "bid" | "ask") =>
+    (await program.account.bookSide.fetch(bookSideAddressFor(side))).orders;
+
+  test("Orders too small to be worth any token B are rejected", async () => {
+    // 1 / 10^6 WIF at 0.5 USDC is worth less than 1 / 10^6 USDC.
+    await expect(placeOrder(carol, "ask", price(0.5), 1)).rejects.toThrow(
+      /InvalidAmount/
+    );
+  });
+
+  test("Fills worth no token B drop the smaller order instead", async () => {
+    // Clear the orders left by the test before.
+    await matchOrders();
+
+    // Carol's ask is left with 1 / 10^6 WIF after Bob's first bid, worth
+    // nothing at 0.5 USDC.
+    await placeOrder(carol, "ask", price(0.5), 1_000_001);
+    await placeOrder(bob, "bid", price(0.5), 1_000_000);
+    await matchOrders();
+    expect((await fetchSide("ask"))[0].tokenAAmount).toEqual(new BN(1));
+
+    const carolBefore = await getBalance(carol);
+    const bobBefore = await getBalance(bob);
+    await placeOrder(bob, "bid", price(0.5), 2_000_000);
+    await matchOrders();
+
+    // Carol gets her WIF back rather than selling it for nothing, and Bob's
+    // bid keeps its whole deposit.
+    expect(await fetchSide("ask")).toHaveLength(0);
+    const carolAfter = await getBalance(carol);
+    expect(carolAfter.tokenAFree).toEqual(carolBefore.tokenAFree.addn(1));
+    expect(carolAfter.tokenBFree).toEqual(carolBefore.tokenBFree);
+    expect((await getBalance(bob)).tokenAFree).toEqual(bobBefore.tokenAFree);
+    const bids = await fetchSide("bid");
+    expect(bids).toHaveLength(1);
+    expect(bids[0].tokenAAmount).toEqual(new BN(2_000_000));
+    expect(bids[0].tokenBLocked).toEqual(new BN(1_000_000));
+  });
+
+  test("A full side makes way for a better priced order", async () => {
+    // Carol fills the asks at prices nobody bids, each one apart so no two
+    // transactions are the same.
+    for (let i = 0; i < 32; i++) {
+      await placeOrder(carol, "ask", price(100 + i), 1_000_000);
+    }
+
+    await expect(placeOrder(alice, "ask", price(200), 1_000_000)).rejects.toThrow(
+      /BookSideFull/
+    );
+    // Evicting needs the balance the worst order is refunded to.
+    await expect(
+      placeOrder(alice, "ask", price(50), 1_000_000)
+    ).rejects.toThrow(/BookSideFull/);
+
+    const carolBefore = await getBalance(carol);
+    await placeOrder(
+      alice,
+      "ask",
+      price(50),
+      2_000_000,
+      balanceAddressFor(carol)
+    );
+
+    const asks = await fetchSide("ask");
+    expect(asks).toHaveLength(32);
+    expect(asks[0].owner).toEqual(alice.publicKey);
+    expect(asks[31].price).toEqual(price(130));
+    expect((await getBalance(carol)).tokenAFree).toEqual(
+      carolBefore.tokenAFree.addn(1_000_000)
+    );
+  });
+});
@@ -0,0 +1,169 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  createAssociatedTokenAccountIdempotentInstruction,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+  getTokenBalanceOn,
+} from "./helpers";
+import { takeOfferAccounts } from "./bankrun";
+
+describe("escrow payment options", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+  const getTokenBalance = getTokenBalanceOn(connection);
+
+  const [alice, bob, wifMint, usdcMint, usdtMint, bonkMint] = makeKeypairs(6);
+
+  const offerId = getRandomBigNumber();
+
+  const [offerAddress] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("offer"),
+      alice.publicKey.toBuffer(),
+      offerId.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+
+  const ata = (mint: Keypair, owner: Keypair) =>
+    getAssociatedTokenAddressSync(
+      mint.publicKey,
+      owner.publicKey,
+      false,
+      TOKEN_PROGRAM
+    );
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        alice.publicKey,
+        [{ recepient: alice.publicKey, amount: 100_000_000 }]
+      )),
+    ];
+    for (const stableMint of [usdcMint, usdtMint, bonkMint]) {
+      tx.instructions.push(
+        ...(await createTokenAndMintTo(
+          connection,
+          provider.publicKey,
+          stableMint.publicKey,
+          6,
+          bob.publicKey,
+          [{ recepient: bob.publicKey, amount: 1_000_000_000 }]
+        ))
+      );
+    }
+    await provider.sendAndConfirm(tx, [wifMint, usdcMint, usdtMint, bonkMint]);
+
+    // 100 WIF for either 150 USDC or 151 USDT.
+    await program.methods
+      .makeOffer(
+        offerId,
+        new BN(100_000_000),
+        new BN(150_000_000),
+        {
+          ...defaultOfferTerms(),
+          paymentOptions: [
+            { mint: usdtMint.publicKey, wantedAmount: new BN(151_000_000) },
+          ],
+        },
+        0
+      )
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA: wifMint.publicKey,
+        tokenMintB: usdcMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+  });
+
+  const takeOffer = (paymentMint: Keypair, tokenAAmount: BN) =>
+    program.methods
+      .takeOffer(tokenAAmount, [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: paymentMint.publicKey,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  test("Mints the maker did not list are rejected", async () => {
+    await expect(takeOffer(bonkMint, new BN(1_000_000))).rejects.toThrow(
+      /PaymentMintNotAccepted/
+    );
+  });
+
+  test("Taker pays with an alternative mint at its own price", async () => {
+    // Takers never pay for the maker's ATA, so Alice needs one for USDT.
+    await expect(takeOffer(usdtMint, new BN(50_000_000))).rejects.toThrow(
+      /AccountNotInitialized/
+    );
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          ata(usdtMint, alice),
+          alice.publicKey,
+          usdtMint.publicKey,
+          TOKEN_PROGRAM
+        )
+      )
+    );
+
+    await takeOffer(usdtMint, new BN(50_000_000));
+
+    expect(await getTokenBalance(ata(wifMint, bob))).toEqual(
+      new BN(50_000_000)
+    );
+    expect(await getTokenBalance(ata(usdtMint, alice))).toEqual(
+      new BN(75_500_000)
+    );
+  });
+
+  test("Taker pays the rest with the primary mint", async () => {
+    await takeOffer(usdcMint, new BN(50_000_000));
+
+    expect(await getTokenBalance(ata(usdcMint, alice))).toEqual(
+      new BN(75_000_000)
+    );
+    expect(await connection.getAccountInfo(offerAddress)).toBeNull();
+  });
+});
@@ -0,0 +1,116 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { TOKEN_PROGRAM } from "./helpers";
+import {
+  EscrowBankrun,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  getUnixTimestamp,
+  makeOffer,
+  setPriceFeed,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+const TOKEN = 1_000_000;
+// SOL/USD with the feed's exponent of -8.
+const usd = (dollars: number) => dollars * 100_000_000;
+
+describe("escrow price triggered offers", () => {
+  let escrow: EscrowBankrun;
+  let offerAddress: PublicKey;
+
+  const [alice, bob, solMint, usdcMint] = makeKeypairs(4);
+  const solUsdFeed = Keypair.generate().publicKey;
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const takeOffer = (solAmount: number, priceFeed: PublicKey | null) =>
+    escrow.program.methods
+      .takeOffer(new BN(solAmount * TOKEN), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: usdcMint.publicKey,
+          priceFeed,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob]);
+
+    await createTokenAndMintTo(escrow, solMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 100 * TOKEN },
+    ]);
+    await createTokenAndMintTo(escrow, usdcMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 20_000 * TOKEN },
+    ]);
+
+    // Stop-loss: sell 100 SOL for 14,000 USDC once SOL drops to $150.
+    ({ offerAddress } = await makeOffer(escrow.program, {
+      maker: alice,
+      tokenMintA: solMint.publicKey,
+      tokenMintB: usdcMint.publicKey,
+      offeredAmount: new BN(100 * TOKEN),
+      wantedAmount: new BN(14_000 * TOKEN),
+      terms: {
+        priceTrigger: {
+          priceFeed: solUsdFeed,
+          direction: { below: {} },
+          threshold: new BN(usd(150)),
+          maxStalenessSeconds: new BN(60),
+        },
+      },
+    }));
+  });
+
+  test("Offer cannot be taken while the price is above the threshold", async () => {
+    await setPriceFeed(escrow, solUsdFeed, {
+      price: usd(160),
+      publishTime: await getUnixTimestamp(escrow),
+    });
+
+    await expect(takeOffer(10, solUsdFeed)).rejects.toThrow(/TriggerNotMet/);
+  });
+
+  test("Only the offer's own, fresh feed is accepted", async () => {
+    const now = await getUnixTimestamp(escrow);
+    const otherFeed = Keypair.generate().publicKey;
+    await setPriceFeed(escrow, otherFeed, { price: usd(140), publishTime: now });
+
+    await expect(takeOffer(11, null)).rejects.toThrow(/InvalidPriceFeed/);
+    await expect(takeOffer(12, otherFeed)).rejects.toThrow(/InvalidPriceFeed/);
+
+    await setPriceFeed(escrow, solUsdFeed, {
+      price: usd(140),
+      publishTime: now - 120,
+    });
+    await expect(takeOffer(13, solUsdFeed)).rejects.toThrow(/StalePrice/);
+  });
+
+  test("Offer can be taken once the price crosses the threshold", async () => {
+    await setPriceFeed(escrow, solUsdFeed, {
+      price: usd(150),
+      publishTime: await getUnixTimestamp(escrow),
+    });
+
+    await takeOffer(40, solUsdFeed);
+    expect(
+      await getTokenBalance(escrow, ata(solMint.publicKey, bob.publicKey))
+    ).toEqual(new BN(40 * TOKEN));
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, alice.publicKey))
+    ).toEqual(new BN(5_600 * TOKEN));
+  });
+});
@@ -0,0 +1,164 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { TOKEN_PROGRAM } from "./helpers";
+import {
+  EscrowBankrun,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  makeOffer,
+  setTokenMetadata,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+const TOKEN = 1_000_000;
+
+describe("escrow quotes", () => {
+  let escrow: EscrowBankrun;
+  let offerAddress: PublicKey;
+  let metadataAddress: PublicKey;
+  let erinReferrer: PublicKey;
+
+  // Carol and Dave are token A's creators, Erin runs the frontend Bob
+  // trades through.
+  const [alice, bob, carol, dave, erin, apeMint, usdcMint] = makeKeypairs(7);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const quoteOffer = (
+    tokenAAmount: number,
+    tokenAMetadata: PublicKey | null = metadataAddress
+  ) =>
+    escrow.program.methods
+      .quoteOffer(new BN(tokenAAmount))
+      .accounts({
+        offer: offerAddress,
+        vault: ata(apeMint.publicKey, offerAddress),
+        tokenMintB: usdcMint.publicKey,
+        referrer: erinReferrer,
+        tokenAMetadata,
+        priceFeed: null,
+      } as any)
+      .view();
+
+  const takeOffer = (tokenAAmount: number) =>
+    escrow.program.methods
+      .takeOffer(new BN(tokenAAmount), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: usdcMint.publicKey,
+          referrer: erinReferrer,
+          referrerTokenAccount: ata(usdcMint.publicKey, erin.publicKey),
+          tokenAMetadata: metadataAddress,
+        })
+      )
+      .remainingAccounts(
+        [carol, dave].map(({ publicKey }) => ({
+          pubkey: ata(usdcMint.publicKey, publicKey),
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
+      .signers([bob])
+      .rpc();
+
+  const usdcBalance = (owner: PublicKey) =>
+    getTokenBalance(escrow, ata(usdcMint.publicKey, owner));
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob, erin]);
+
+    await createTokenAndMintTo(escrow, apeMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 10 * TOKEN },
+    ]);
+    await createTokenAndMintTo(escrow, usdcMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 1_000 * TOKEN },
+      { recepient: carol.publicKey, amount: 0 },
+      { recepient: dave.publicKey, amount: 0 },
+      { recepient: erin.publicKey, amount: 0 },
+    ]);
+
+    // 5% royalties, split 75 / 25 between Carol and Dave.
+    metadataAddress = await setTokenMetadata(escrow, apeMint.publicKey, 500, [
+      { address: carol.publicKey, verified: true, share: 75 },
+      { address: dave.publicKey, verified: true, share: 25 },
+    ]);
+
+    // Erin asks for 1%, of which the offer allows 0.5%.
+    await escrow.program.methods
+      .registerReferrer(100)
+      .accounts({ wallet: erin.publicKey })
+      .signers([erin])
+      .rpc();
+    [erinReferrer] = PublicKey.findProgramAddressSync(
+      [Buffer.from("referrer"), erin.publicKey.toBuffer()],
+      escrow.program.programId
+    );
+
+    // 10 APE for 25 USDC.
+    ({ offerAddress } = await makeOffer(escrow.program, {
+      maker: alice,
+      tokenMintA: apeMint.publicKey,
+      tokenMintB: usdcMint.publicKey,
+      offeredAmount: new BN(10 * TOKEN),
+      wantedAmount: new BN(25 * TOKEN),
+      terms: { payRoyalties: true, maxReferralBps: 50 },
+    }));
+  });
+
+  test("Quotes split the payment between every leg", async () => {
+    const quote = await quoteOffer(4 * TOKEN);
+
+    expect(quote.tokenAAmount).toEqual(new BN(4 * TOKEN));
+    expect(quote.tokenBAmount).toEqual(new BN(10 * TOKEN));
+    expect(quote.royalties.map(({ creator }) => creator)).toEqual([
+      carol.publicKey,
+      dave.publicKey,
+    ]);
+    expect(quote.royalties.map(({ amount }) => amount)).toEqual([
+      new BN(375_000),
+      new BN(125_000),
+    ]);
+    expect(quote.referralAmount).toEqual(new BN(50_000));
+    expect(quote.makerAmount).toEqual(new BN(9_450_000));
+    expect(quote.vests).toBe(false);
+    expect(quote.closesOffer).toBe(false);
+  });
+
+  test("Quotes fail where the take would", async () => {
+    await expect(quoteOffer(11 * TOKEN)).rejects.toThrow();
+    await expect(quoteOffer(4 * TOKEN, null)).rejects.toThrow();
+  });
+
+  test("Takes transfer exactly what was quoted", async () => {
+    const quote = await quoteOffer(10 * TOKEN);
+    expect(quote.closesOffer).toBe(true);
+
+    await takeOffer(10 * TOKEN);
+
+    expect(await usdcBalance(alice.publicKey)).toEqual(quote.makerAmount);
+    expect(await usdcBalance(erin.publicKey)).toEqual(quote.referralAmount);
+    expect(await usdcBalance(carol.publicKey)).toEqual(
+      quote.royalties[0].amount
+    );
+    expect(await usdcBalance(dave.publicKey)).toEqual(
+      quote.royalties[1].amount
+    );
+    expect(await usdcBalance(bob.publicKey)).toEqual(
+      new BN(1_000 * TOKEN).sub(quote.tokenBAmount)
+    );
+    expect(
+      await getTokenBalance(escrow, ata(apeMint.publicKey, bob.publicKey))
+    ).toEqual(quote.tokenAAmount);
+  });
+});
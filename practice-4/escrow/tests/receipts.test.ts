@@ -0,0 +1,144 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
+  getExtensionTypes,
+  getMint,
+  getTokenMetadata,
+} from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  EscrowBankrun,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  getUnixTimestamp,
+  makeOffer,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+const TOKEN = 1_000_000;
+
+type Receipts = { makerReceiptMint: Keypair; takerReceiptMint: Keypair };
+
+describe("escrow settlement receipts", () => {
+  let escrow: EscrowBankrun;
+  let offerId: BN;
+  let offerAddress: PublicKey;
+
+  const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+  const [makerReceiptMint, takerReceiptMint] = makeKeypairs(2);
+
+  const receiptAccount = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, false, TOKEN_2022_PROGRAM_ID);
+
+  const receiptAccounts = (receipts: Receipts | null) =>
+    receipts
+      ? {
+          makerReceiptMint: receipts.makerReceiptMint.publicKey,
+          makerReceiptAccount: receiptAccount(
+            receipts.makerReceiptMint.publicKey,
+            alice.publicKey
+          ),
+          takerReceiptMint: receipts.takerReceiptMint.publicKey,
+          takerReceiptAccount: receiptAccount(
+            receipts.takerReceiptMint.publicKey,
+            bob.publicKey
+          ),
+          receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+        }
+      : {};
+
+  const takeOffer = (receipts: Receipts | null) =>
+    escrow.program.methods
+      .takeOffer(new BN(40 * TOKEN), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: wifMint.publicKey,
+          ...receiptAccounts(receipts),
+        })
+      )
+      .signers(
+        receipts
+          ? [bob, receipts.makerReceiptMint, receipts.takerReceiptMint]
+          : [bob]
+      )
+      .rpc();
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob]);
+
+    await createTokenAndMintTo(escrow, usdcMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 100 * TOKEN },
+    ]);
+    await createTokenAndMintTo(escrow, wifMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 1_000 * TOKEN },
+    ]);
+
+    // 100 USDC for 250 WIF, with receipts for every take.
+    ({ offerId, offerAddress } = await makeOffer(escrow.program, {
+      maker: alice,
+      tokenMintA: usdcMint.publicKey,
+      tokenMintB: wifMint.publicKey,
+      offeredAmount: new BN(100 * TOKEN),
+      wantedAmount: new BN(250 * TOKEN),
+      terms: { issueReceipts: true },
+    }));
+  });
+
+  test("Receipt offers cannot be taken without the receipt accounts", async () => {
+    await expect(takeOffer(null)).rejects.toThrow(/ReceiptAccountsMismatch/);
+  });
+
+  test("Maker and taker each get a receipt recording the take", async () => {
+    const takenAt = await getUnixTimestamp(escrow);
+    await takeOffer({ makerReceiptMint, takerReceiptMint });
+
+    const connection = escrow.provider.connection;
+    for (const [mint, owner, role] of [
+      [makerReceiptMint, alice, "maker"],
+      [takerReceiptMint, bob, "taker"],
+    ] as const) {
+      expect(
+        await getTokenBalance(
+          escrow,
+          receiptAccount(mint.publicKey, owner.publicKey)
+        )
+      ).toEqual(new BN(1));
+
+      const mintAccount = await getMint(
+        connection,
+        mint.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(mintAccount.supply).toEqual(BigInt(1));
+      expect(mintAccount.mintAuthority).toBeNull();
+      expect(getExtensionTypes(mintAccount.tlvData)).toContain(
+        ExtensionType.NonTransferable
+      );
+
+      const metadata = await getTokenMetadata(connection, mint.publicKey);
+      expect(metadata.updateAuthority).toBeUndefined();
+      expect(metadata.additionalMetadata).toEqual([
+        ["role", role],
+        ["offer", offerAddress.toBase58()],
+        ["offer_id", offerId.toString()],
+        ["token_mint_a", usdcMint.publicKey.toBase58()],
+        ["token_mint_b", wifMint.publicKey.toBase58()],
+        ["token_a_amount", String(40 * TOKEN)],
+        ["token_b_amount", String(100 * TOKEN)],
+        ["timestamp", String(takenAt)],
+      ]);
+    }
+  });
+});
@@ -0,0 +1,173 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  getTokenBalanceOn,
+} from "./helpers";
+import { makeOffer, takeOfferAccounts } from "./bankrun";
+
+describe("escrow referrals", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+  const getTokenBalance = getTokenBalanceOn(connection);
+
+  // Carol runs the frontend Bob trades through.
+  const [alice, bob, carol, usdcMint, wifMint] = makeKeypairs(5);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, false, TOKEN_PROGRAM);
+
+  const [carolReferrer] = PublicKey.findProgramAddressSync(
+    [Buffer.from("referrer"), carol.publicKey.toBuffer()],
+    program.programId
+  );
+
+  let offerAddress: PublicKey;
+
+  const registerReferrer = (feeBps: number, wallet = carol) =>
+    program.methods
+      .registerReferrer(feeBps)
+      .accounts({ wallet: wallet.publicKey })
+      .signers([wallet])
+      .rpc();
+
+  const takeOffer = (
+    tokenAAmount: BN,
+    referrer: PublicKey | null,
+    referrerTokenAccount: PublicKey | null
+  ) =>
+    program.methods
+      .takeOffer(tokenAAmount, [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: wifMint.publicKey,
+          referrer,
+          referrerTokenAccount,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob, carol].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [{ recepient: alice.publicKey, amount: 100_000_000 }]
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [
+          { recepient: bob.publicKey, amount: 100_000_000 },
+          { recepient: carol.publicKey, amount: 0 },
+        ]
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+
+    // 10 USDC for 25 WIF, letting referrers keep up to 0.5% of the WIF.
+    ({ offerAddress } = await makeOffer(program, {
+      maker: alice,
+      tokenMintA: usdcMint.publicKey,
+      tokenMintB: wifMint.publicKey,
+      offeredAmount: new BN(10_000_000),
+      wantedAmount: new BN(25_000_000),
+      terms: { maxReferralBps: 50 },
+    }));
+  });
+
+  test("Referrer fees above MAX_BPS are rejected", async () => {
+    await expect(registerReferrer(10_001)).rejects.toThrow(
+      /InvalidReferralFee/
+    );
+  });
+
+  test("Referrer and its token account must come together", async () => {
+    await registerReferrer(100);
+
+    await expect(
+      takeOffer(new BN(1_000_000), carolReferrer, null)
+    ).rejects.toThrow(/ReferralAccountsMismatch/);
+    await expect(
+      takeOffer(
+        new BN(1_000_000),
+        carolReferrer,
+        ata(wifMint.publicKey, bob.publicKey)
+      )
+    ).rejects.toThrow(/ReferralAccountsMismatch/);
+  });
+
+  test("Takers cannot refer themselves", async () => {
+    await registerReferrer(100, bob);
+    const [bobReferrer] = PublicKey.findProgramAddressSync(
+      [Buffer.from("referrer"), bob.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await expect(
+      takeOffer(
+        new BN(2_000_000),
+        bobReferrer,
+        ata(wifMint.publicKey, bob.publicKey)
+      )
+    ).rejects.toThrow(/SelfReferral/);
+  });
+
+  test("Referrer share is capped by the offer and paid out of the maker's proceeds", async () => {
+    const carolWif = ata(wifMint.publicKey, carol.publicKey);
+    const aliceWif = ata(wifMint.publicKey, alice.publicKey);
+
+    // Carol asks for 1%, the offer allows 0.5% of the 10 WIF paid.
+    await takeOffer(new BN(4_000_000), carolReferrer, carolWif);
+    expect(await getTokenBalance(carolWif)).toEqual(new BN(50_000));
+    expect(await getTokenBalance(aliceWif)).toEqual(new BN(9_950_000));
+    expect(
+      await getTokenBalance(ata(wifMint.publicKey, bob.publicKey))
+    ).toEqual(new BN(90_000_000));
+
+    // Below the offer's cap, Carol's own fee applies: 0.2% of 15 WIF.
+    await registerReferrer(20);
+    await takeOffer(new BN(6_000_000), carolReferrer, carolWif);
+    expect(await getTokenBalance(carolWif)).toEqual(new BN(80_000));
+    expect(await getTokenBalance(aliceWif)).toEqual(new BN(24_920_000));
+
+    const referrer = await program.account.referrer.fetch(carolReferrer);
+    expect(referrer.wallet).toEqual(carol.publicKey);
+    expect(referrer.feeBps).toEqual(20);
+    expect(referrer.referredTakes).toEqual(new BN(2));
+    expect(referrer.totalEarned).toEqual(new BN(80_000));
+  });
+});
@@ -0,0 +1,260 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+import { closeOfferAccounts, takeOfferAccounts } from "./bankrun";
+
+// The provider wallet pays every transaction fee, so the lamport deltas
+// below are rent only.
+describe("escrow rent accounting", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+
+  const [alice, bob, carol, usdcMint, wifMint] = makeKeypairs(5);
+
+  const ata = (mint: Keypair, owner: Keypair) =>
+    getAssociatedTokenAddressSync(
+      mint.publicKey,
+      owner.publicKey,
+      false,
+      TOKEN_PROGRAM
+    );
+
+  const lamportsOf = async (...addresses: Array<PublicKey>) =>
+    Promise.all(addresses.map((address) => connection.getBalance(address)));
+
+  // Runs `action` and returns how many lamports each of `addresses` gained.
+  const lamportDeltas = async (
+    addresses: Array<PublicKey>,
+    action: () => Promise<unknown>
+  ) => {
+    const before = await lamportsOf(...addresses);
+    await action();
+    const after = await lamportsOf(...addresses);
+    return after.map((balance, i) => balance - before[i]);
+  };
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob, carol].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      // Bob already holds both tokens, so taking creates no accounts for him.
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [
+          { recepient: alice.publicKey, amount: 100_000_000 },
+          { recepient: bob.publicKey, amount: 0 },
+        ]
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [{ recepient: bob.publicKey, amount: 100_000_000 }]
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+  });
+
+  const offerAddressFor = (offerId: BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        alice.publicKey.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const vaultAddressFor = (offerAddress: PublicKey) =>
+    getAssociatedTokenAddressSync(
+      usdcMint.publicKey,
+      offerAddress,
+      true,
+      TOKEN_PROGRAM
+    );
+
+  const pda = (...seeds: Array<Buffer>) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const reputationAddressFor = (wallet: Keypair) =>
+    pda(Buffer.from("reputation"), wallet.publicKey.toBuffer());
+
+  const makeOffer = (offerId: BN, rentReceiver: PublicKey | null) =>
+    program.methods
+      .makeOffer(
+        offerId,
+        new BN(10_000_000),
+        new BN(10_000_000),
+        {
+          ...defaultOfferTerms(),
+          rentReceiver,
+        },
+        0
+      )
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+
+  const takeOffer = (
+    offerAddress: PublicKey,
+    rentReceiver: PublicKey | null
+  ) =>
+    program.methods
+      .takeOffer(new BN(10_000_000), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: wifMint.publicKey,
+          rentReceiver,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  test("Maker pays for the offer, the vault and their token B account", async () => {
+    const offerId = getRandomBigNumber();
+    const offerAddress = offerAddressFor(offerId);
+
+    const [aliceDelta] = await lamportDeltas([alice.publicKey], () =>
+      makeOffer(offerId, null)
+    );
+
+    // The first offer of the market also creates its stats, the first page
+    // of its index and Alice's reputation.
+    const market = [
+      usdcMint.publicKey.toBuffer(),
+      wifMint.publicKey.toBuffer(),
+    ];
+    const rents = await lamportsOf(
+      offerAddress,
+      vaultAddressFor(offerAddress),
+      ata(wifMint, alice),
+      pda(Buffer.from("market_stats"), ...market),
+      pda(Buffer.from("market_index"), ...market, Buffer.alloc(4)),
+      reputationAddressFor(alice)
+    );
+    expect(aliceDelta).toEqual(-rents.reduce((sum, rent) => sum + rent, 0));
+  });
+
+  test("Take refunds maker-paid rent to the maker, taker pays only for their reputation", async () => {
+    const offerId = getRandomBigNumber();
+    const offerAddress = offerAddressFor(offerId);
+    await makeOffer(offerId, null);
+
+    const [offerRent, vaultRent] = await lamportsOf(
+      offerAddress,
+      vaultAddressFor(offerAddress)
+    );
+
+    const [aliceDelta, bobDelta] = await lamportDeltas(
+      [alice.publicKey, bob.publicKey],
+      () => takeOffer(offerAddress, null)
+    );
+
+    expect(aliceDelta).toEqual(offerRent + vaultRent);
+    const [bobReputationRent] = await lamportsOf(reputationAddressFor(bob));
+    expect(bobDelta).toEqual(-bobReputationRent);
+  });
+
+  test("Take refunds rent to the offer's rent receiver", async () => {
+    const offerId = getRandomBigNumber();
+    const offerAddress = offerAddressFor(offerId);
+    await makeOffer(offerId, carol.publicKey);
+
+    const [offerRent, vaultRent] = await lamportsOf(
+      offerAddress,
+      vaultAddressFor(offerAddress)
+    );
+
+    const [aliceDelta, bobDelta, carolDelta] = await lamportDeltas(
+      [alice.publicKey, bob.publicKey, carol.publicKey],
+      () => takeOffer(offerAddress, carol.publicKey)
+    );
+
+    expect(aliceDelta).toEqual(0);
+    expect(bobDelta).toEqual(0);
+    expect(carolDelta).toEqual(offerRent + vaultRent);
+  });
+
+  test("Rent cannot be redirected away from the rent receiver", async () => {
+    const offerId = getRandomBigNumber();
+    const offerAddress = offerAddressFor(offerId);
+    await makeOffer(offerId, carol.publicKey);
+
+    await expect(takeOffer(offerAddress, bob.publicKey)).rejects.toThrow(
+      /InvalidRentReceiver/
+    );
+  });
+
+  test("Close refunds rent to the offer's rent receiver", async () => {
+    const offerId = getRandomBigNumber();
+    const offerAddress = offerAddressFor(offerId);
+    const vaultAddress = vaultAddressFor(offerAddress);
+    await makeOffer(offerId, carol.publicKey);
+
+    const [offerRent, vaultRent] = await lamportsOf(offerAddress, vaultAddress);
+
+    const [aliceDelta, carolDelta] = await lamportDeltas(
+      [alice.publicKey, carol.publicKey],
+      () =>
+        program.methods
+          .closeOffer()
+          .accounts(
+            closeOfferAccounts({
+              maker: alice.publicKey,
+              offer: offerAddress,
+              vault: vaultAddress,
+              tokenMintA: usdcMint.publicKey,
+              tokenMintB: wifMint.publicKey,
+              makerTokenAccountA: ata(usdcMint, alice),
+              rentReceiver: carol.publicKey,
+            })
+          )
+          .signers([alice])
+          .rpc()
+    );
+
+    expect(aliceDelta).toEqual(0);
+    expect(carolDelta).toEqual(offerRent + vaultRent);
+  });
+});
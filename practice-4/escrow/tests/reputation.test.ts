@@ -0,0 +1,210 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import * as anchor from "@coral-xyz/anchor";
+import { type Program, BN } from "@coral-xyz/anchor";
+import { Escrow } from "../target/types/escrow";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import {
+  TOKEN_PROGRAM,
+  createTokenAndMintTo,
+  defaultOfferTerms,
+  getRandomBigNumber,
+} from "./helpers";
+import { closeOfferAccounts, takeOfferAccounts } from "./bankrun";
+
+describe("escrow reputation", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider();
+  const connection = provider.connection;
+  const program = anchor.workspace.Escrow as Program<Escrow>;
+
+  const [alice, bob, carol, usdcMint, wifMint] = makeKeypairs(5);
+
+  const reputationAddressFor = (wallet: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("reputation"), wallet.toBuffer()],
+      program.programId
+    )[0];
+
+  const offerAddressFor = (offerId: BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        alice.publicKey.toBuffer(),
+        offerId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const makeOffer = async (terms = defaultOfferTerms()) => {
+    const offerId = getRandomBigNumber();
+    // 10 USDC for 25 WIF.
+    await program.methods
+      .makeOffer(
+        offerId,
+        new BN(10_000_000),
+        new BN(25_000_000),
+        terms,
+        0
+      )
+      .accounts({
+        maker: alice.publicKey,
+        tokenMintA: usdcMint.publicKey,
+        tokenMintB: wifMint.publicKey,
+        previousIndexPage: null,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+    return offerAddressFor(offerId);
+  };
+
+  const takeOffer = (
+    offerAddress: PublicKey,
+    taker: Keypair,
+    tokenAAmount: BN
+  ) =>
+    program.methods
+      .takeOffer(tokenAAmount, [])
+      .accounts(
+        takeOfferAccounts({
+          taker: taker.publicKey,
+          offer: offerAddress,
+          tokenMintB: wifMint.publicKey,
+        })
+      )
+      .signers([taker])
+      .rpc();
+
+  beforeAll(async () => {
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[alice, bob, carol].map((owner) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: owner.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        usdcMint.publicKey,
+        6,
+        alice.publicKey,
+        [{ recepient: alice.publicKey, amount: 100_000_000 }]
+      )),
+      ...(await createTokenAndMintTo(
+        connection,
+        provider.publicKey,
+        wifMint.publicKey,
+        6,
+        bob.publicKey,
+        [
+          { recepient: alice.publicKey, amount: 100_000_000 },
+          { recepient: bob.publicKey, amount: 100_000_000 },
+          { recepient: carol.publicKey, amount: 100_000_000 },
+        ]
+      )),
+    ];
+    await provider.sendAndConfirm(tx, [usdcMint, wifMint]);
+  });
+
+  test("Takes and cancellations are recorded for both sides", async () => {
+    const filledOffer = await makeOffer();
+    const cancelledOffer = await makeOffer();
+
+    await takeOffer(filledOffer, bob, new BN(4_000_000));
+    await takeOffer(filledOffer, bob, new BN(6_000_000));
+
+    await program.methods
+      .closeOffer()
+      .accounts(
+        closeOfferAccounts({
+          maker: alice.publicKey,
+          offer: cancelledOffer,
+          tokenMintA: usdcMint.publicKey,
+          tokenMintB: wifMint.publicKey,
+          makerTokenAccountA: getAssociatedTokenAddressSync(
+            usdcMint.publicKey,
+            alice.publicKey,
+            false,
+            TOKEN_PROGRAM
+          ),
+        })
+      )
+      .signers([alice])
+      .rpc();
+
+    const aliceReputation = await program.account.reputation.fetch(
+      reputationAddressFor(alice.publicKey)
+    );
+    expect(aliceReputation.wallet).toEqual(alice.publicKey);
+    expect(aliceReputation.takes).toEqual(new BN(0));
+    expect(aliceReputation.sales).toEqual(new BN(2));
+    expect(aliceReputation.cancellations).toEqual(new BN(1));
+    expect(aliceReputation.volume).toEqual(new BN(10_000_000));
+    expect(aliceReputation.firstSeen.toNumber()).toBeGreaterThan(0);
+
+    const bobReputation = await program.account.reputation.fetch(
+      reputationAddressFor(bob.publicKey)
+    );
+    expect(bobReputation.takes).toEqual(new BN(2));
+    expect(bobReputation.sales).toEqual(new BN(0));
+    expect(bobReputation.volume).toEqual(new BN(10_000_000));
+  });
+
+  test("Offers can require a minimum taker reputation", async () => {
+    const gatedOffer = await makeOffer({
+      ...defaultOfferTerms(),
+      minTakerReputation: { minTakes: new BN(2), minAgeSeconds: new BN(0) },
+    });
+
+    // Carol has never taken an offer.
+    await expect(
+      takeOffer(gatedOffer, carol, new BN(1_000_000))
+    ).rejects.toThrow(/InsufficientReputation/);
+
+    // Bob's two fills from the previous test are enough.
+    await takeOffer(gatedOffer, bob, new BN(1_000_000));
+  });
+
+  test("Self-takes and dust takes do not count as takes", async () => {
+    const offer = await makeOffer();
+    const gatedOffer = await makeOffer({
+      ...defaultOfferTerms(),
+      minTakerReputation: { minTakes: new BN(1), minAgeSeconds: new BN(0) },
+    });
+    const reputationOf = (wallet: Keypair) =>
+      program.account.reputation.fetch(reputationAddressFor(wallet.publicKey));
+
+    // Alice fills her own offer, as a wallet farming its record would.
+    const aliceBefore = await reputationOf(alice);
+    await takeOffer(offer, alice, new BN(2_000_000));
+    await takeOffer(offer, alice, new BN(3_000_000));
+    const aliceAfter = await reputationOf(alice);
+    expect(aliceAfter.takes).toEqual(aliceBefore.takes);
+    expect(aliceAfter.sales).toEqual(aliceBefore.sales);
+    expect(aliceAfter.volume).toEqual(aliceBefore.volume);
+
+    // Carol's fills of less than one USDC add volume but no takes.
+    await takeOffer(offer, carol, new BN(500_000));
+    await takeOffer(offer, carol, new BN(400_000));
+    const carolReputation = await reputationOf(carol);
+    expect(carolReputation.takes).toEqual(new BN(0));
+    expect(carolReputation.volume).toEqual(new BN(900_000));
+    await expect(
+      takeOffer(gatedOffer, carol, new BN(1_000_000))
+    ).rejects.toThrow(/InsufficientReputation/);
+  });
+});
@@ -0,0 +1,123 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { TOKEN_PROGRAM } from "./helpers";
+import {
+  EscrowBankrun,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  makeOffer,
+  setTokenMetadata,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+const USDC = 1_000_000;
+
+describe("escrow NFT royalties", () => {
+  let escrow: EscrowBankrun;
+  let offerAddress: PublicKey;
+  let metadataAddress: PublicKey;
+
+  // Dave listed himself as a creator without being verified.
+  const [alice, bob, carol, dave, erin, apeMint, usdcMint] = makeKeypairs(7);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const takeOffer = (
+    tokenAMetadata: PublicKey | null,
+    creatorTokenAccounts: Array<PublicKey>
+  ) =>
+    escrow.program.methods
+      .takeOffer(new BN(1), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: usdcMint.publicKey,
+          tokenAMetadata,
+        })
+      )
+      .remainingAccounts(
+        creatorTokenAccounts.map((pubkey) => ({
+          pubkey,
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
+      .signers([bob])
+      .rpc();
+
+  const usdcBalance = (owner: PublicKey) =>
+    getTokenBalance(escrow, ata(usdcMint.publicKey, owner));
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob]);
+
+    await createTokenAndMintTo(escrow, apeMint, 0, alice, [
+      { recepient: alice.publicKey, amount: 1 },
+    ]);
+    await createTokenAndMintTo(escrow, usdcMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 1_000 * USDC },
+      { recepient: carol.publicKey, amount: 0 },
+      { recepient: dave.publicKey, amount: 0 },
+      { recepient: erin.publicKey, amount: 0 },
+    ]);
+
+    // 5% royalties, split 60 / 20 / 20 between Carol, Dave and Erin.
+    metadataAddress = await setTokenMetadata(escrow, apeMint.publicKey, 500, [
+      { address: carol.publicKey, verified: true, share: 60 },
+      { address: dave.publicKey, verified: false, share: 20 },
+      { address: erin.publicKey, verified: true, share: 20 },
+    ]);
+
+    ({ offerAddress } = await makeOffer(escrow.program, {
+      maker: alice,
+      tokenMintA: apeMint.publicKey,
+      tokenMintB: usdcMint.publicKey,
+      offeredAmount: new BN(1),
+      wantedAmount: new BN(100 * USDC),
+      terms: { payRoyalties: true },
+    }));
+  });
+
+  test("Royalty offers cannot be taken without the metadata", async () => {
+    await expect(takeOffer(null, [])).rejects.toThrow(/InvalidMetadata/);
+  });
+
+  test("Every verified creator needs its token account, in order", async () => {
+    await expect(takeOffer(metadataAddress, [])).rejects.toThrow(
+      /CreatorAccountMismatch/
+    );
+    await expect(
+      takeOffer(metadataAddress, [
+        ata(usdcMint.publicKey, erin.publicKey),
+        ata(usdcMint.publicKey, carol.publicKey),
+      ])
+    ).rejects.toThrow(/CreatorAccountMismatch/);
+  });
+
+  test("Verified creators are paid their share and the maker the rest", async () => {
+    await takeOffer(metadataAddress, [
+      ata(usdcMint.publicKey, carol.publicKey),
+      ata(usdcMint.publicKey, erin.publicKey),
+    ]);
+
+    expect(await usdcBalance(carol.publicKey)).toEqual(new BN(3 * USDC));
+    expect(await usdcBalance(erin.publicKey)).toEqual(new BN(1 * USDC));
+    expect(await usdcBalance(dave.publicKey)).toEqual(new BN(0));
+    // Dave's unverified share stays with the maker.
+    expect(await usdcBalance(alice.publicKey)).toEqual(new BN(96 * USDC));
+    expect(await usdcBalance(bob.publicKey)).toEqual(new BN(900 * USDC));
+    expect(
+      await getTokenBalance(escrow, ata(apeMint.publicKey, bob.publicKey))
+    ).toEqual(new BN(1));
+  });
+});
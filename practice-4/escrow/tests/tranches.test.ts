@@ -0,0 +1,120 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { TOKEN_PROGRAM } from "./helpers";
+import {
+  EscrowBankrun,
+  closeOfferAccounts,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  getUnixTimestamp,
+  makeOffer,
+  setUnixTimestamp,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+const DAY = 24 * 60 * 60;
+const TOKEN = 1_000_000;
+
+describe("escrow tranche offers", () => {
+  let escrow: EscrowBankrun;
+  let start: number;
+  let offerAddress: PublicKey;
+
+  const [alice, bob, wifMint, usdcMint] = makeKeypairs(4);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  const takeOffer = (wifAmount: number) =>
+    escrow.program.methods
+      .takeOffer(new BN(wifAmount * TOKEN), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer: offerAddress,
+          tokenMintB: usdcMint.publicKey,
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  const bobWif = () =>
+    getTokenBalance(escrow, ata(wifMint.publicKey, bob.publicKey));
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob]);
+
+    await createTokenAndMintTo(escrow, wifMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 10_000 * TOKEN },
+    ]);
+    await createTokenAndMintTo(escrow, usdcMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 10_000 * TOKEN },
+    ]);
+
+    // 10,000 WIF for 10,000 USDC, released 1,000 WIF a day.
+    start = await getUnixTimestamp(escrow);
+    ({ offerAddress } = await makeOffer(escrow.program, {
+      maker: alice,
+      tokenMintA: wifMint.publicKey,
+      tokenMintB: usdcMint.publicKey,
+      offeredAmount: new BN(10_000 * TOKEN),
+      wantedAmount: new BN(10_000 * TOKEN),
+      terms: {
+        tranches: {
+          startTimestamp: new BN(start),
+          intervalSeconds: new BN(DAY),
+          trancheAmount: new BN(1_000 * TOKEN),
+        },
+      },
+    }));
+  });
+
+  test("Only the first tranche is available at the start", async () => {
+    await takeOffer(1_000);
+    await expect(takeOffer(1)).rejects.toThrow(/TrancheLocked/);
+  });
+
+  test("Each interval unlocks one more tranche", async () => {
+    await setUnixTimestamp(escrow, start + DAY);
+
+    await takeOffer(600);
+    await takeOffer(400);
+    await expect(takeOffer(2)).rejects.toThrow(/TrancheLocked/);
+    expect(await bobWif()).toEqual(new BN(2_000 * TOKEN));
+  });
+
+  test("Unlocked tranches stay available until filled", async () => {
+    await setUnixTimestamp(escrow, start + 4 * DAY);
+
+    await takeOffer(3_000);
+    expect(await bobWif()).toEqual(new BN(5_000 * TOKEN));
+  });
+
+  test("Maker can cancel the rest at any time", async () => {
+    await escrow.program.methods
+      .closeOffer()
+      .accounts(
+        closeOfferAccounts({
+          maker: alice.publicKey,
+          offer: offerAddress,
+          tokenMintA: wifMint.publicKey,
+          tokenMintB: usdcMint.publicKey,
+          makerTokenAccountA: ata(wifMint.publicKey, alice.publicKey),
+        })
+      )
+      .signers([alice])
+      .rpc();
+
+    expect(
+      await getTokenBalance(escrow, ata(wifMint.publicKey, alice.publicKey))
+    ).toEqual(new BN(5_000 * TOKEN));
+  });
+});
@@ -0,0 +1,216 @@
+import { expect, describe, beforeAll, test } from "@jest/globals";
+import { BN } from "@coral-xyz/anchor";
+import { ComputeBudgetProgram, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+
+import { makeKeypairs } from "@solana-developers/helpers";
+
+import { TOKEN_PROGRAM } from "./helpers";
+import {
+  EscrowBankrun,
+  createTokenAndMintTo,
+  fundWallets,
+  getTokenBalance,
+  getUnixTimestamp,
+  makeOffer,
+  setUnixTimestamp,
+  startEscrow,
+  takeOfferAccounts,
+} from "./bankrun";
+
+const DAY = 24 * 60 * 60;
+const TOKEN = 1_000_000;
+
+describe("escrow vesting offers", () => {
+  let escrow: EscrowBankrun;
+  let takenAt: number;
+  let offerAddress: PublicKey;
+  let vestingAddress: PublicKey;
+
+  const [alice, bob, wifMint, usdcMint] = makeKeypairs(4);
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true, TOKEN_PROGRAM);
+
+  // Bankrun drops a transaction identical to one it has already seen, so
+  // every claim asks for a different compute limit.
+  let claims = 0;
+  const claimVested = () =>
+    escrow.program.methods
+      .claimVested()
+      .accounts({
+        taker: bob.publicKey,
+        vesting: vestingAddress,
+        tokenMintA: wifMint.publicKey,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 200_000 + ++claims }),
+      ])
+      .signers([bob])
+      .rpc();
+
+  const takeVestingOffer = (
+    offer: PublicKey,
+    vesting: PublicKey,
+    tokenAAmount: number
+  ) =>
+    escrow.program.methods
+      .takeOffer(new BN(tokenAAmount), [])
+      .accounts(
+        takeOfferAccounts({
+          taker: bob.publicKey,
+          offer,
+          tokenMintB: usdcMint.publicKey,
+          vesting,
+          vestingVault: ata(wifMint.publicKey, vesting),
+        })
+      )
+      .signers([bob])
+      .rpc();
+
+  const bobWif = () =>
+    getTokenBalance(escrow, ata(wifMint.publicKey, bob.publicKey));
+
+  beforeAll(async () => {
+    escrow = await startEscrow();
+    fundWallets(escrow, [alice, bob]);
+
+    await createTokenAndMintTo(escrow, wifMint, 6, alice, [
+      { recepient: alice.publicKey, amount: 1_100 * TOKEN },
+    ]);
+    await createTokenAndMintTo(escrow, usdcMint, 6, bob, [
+      { recepient: bob.publicKey, amount: 1_000 * TOKEN },
+    ]);
+
+    // 1,000 WIF for 500 USDC, vesting over 10 days after a 2 day cliff.
+    ({ offerAddress } = await makeOffer(escrow.program, {
+      maker: alice,
+      tokenMintA: wifMint.publicKey,
+      tokenMintB: usdcMint.publicKey,
+      offeredAmount: new BN(1_000 * TOKEN),
+      wantedAmount: new BN(500 * TOKEN),
+      terms: {
+        vesting: {
+          cliffSeconds: new BN(2 * DAY),
+          durationSeconds: new BN(10 * DAY),
+        },
+      },
+    }));
+    [vestingAddress] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("vesting"),
+        offerAddress.toBuffer(),
+        bob.publicKey.toBuffer(),
+      ],
+      escrow.program.programId
+    );
+  });
+
+  test("Taking without the vesting accounts fails", async () => {
+    await expect(
+      escrow.program.methods
+        .takeOffer(new BN(1_000 * TOKEN), [])
+        .accounts(
+          takeOfferAccounts({
+            taker: bob.publicKey,
+            offer: offerAddress,
+            tokenMintB: usdcMint.publicKey,
+          })
+        )
+        .signers([bob])
+        .rpc()
+    ).rejects.toThrow(/VestingAccountsMismatch/);
+  });
+
+  test("Maker is paid at once while token A goes to the vesting vault", async () => {
+    takenAt = await getUnixTimestamp(escrow);
+    await takeVestingOffer(offerAddress, vestingAddress, 1_000 * TOKEN);
+
+    expect(
+      await getTokenBalance(escrow, ata(usdcMint.publicKey, alice.publicKey))
+    ).toEqual(new BN(500 * TOKEN));
+    expect(
+      await getTokenBalance(escrow, ata(wifMint.publicKey, vestingAddress))
+    ).toEqual(new BN(1_000 * TOKEN));
+    expect(await bobWif()).toEqual(new BN(0));
+  });
+
+  test("Nothing can be claimed before the cliff", async () => {
+    await setUnixTimestamp(escrow, takenAt + DAY);
+    await expect(claimVested()).rejects.toThrow(/NothingToClaim/);
+  });
+
+  test("Claims release the linearly vested amount", async () => {
+    await setUnixTimestamp(escrow, takenAt + 4 * DAY);
+    await claimVested();
+    expect(await bobWif()).toEqual(new BN(400 * TOKEN));
+
+    await expect(claimVested()).rejects.toThrow(/NothingToClaim/);
+
+    await setUnixTimestamp(escrow, takenAt + 5 * DAY);
+    await claimVested();
+    expect(await bobWif()).toEqual(new BN(500 * TOKEN));
+  });
+
+  test("The last claim empties and closes the vesting accounts", async () => {
+    await setUnixTimestamp(escrow, takenAt + 30 * DAY);
+    await claimVested();
+    expect(await bobWif()).toEqual(new BN(1_000 * TOKEN));
+
+    const connection = escrow.provider.connection;
+    expect(await connection.getAccountInfo(vestingAddress)).toBeNull();
+    expect(
+      await connection.getAccountInfo(ata(wifMint.publicKey, vestingAddress))
+    ).toBeNull();
+  });
+
+  test("A second take by the same taker restarts the schedule with both amounts", async () => {
+    // 100 WIF for 50 USDC on the same schedule.
+    const { offerAddress: offer } = await makeOffer(escrow.program, {
+      maker: alice,
+      tokenMintA: wifMint.publicKey,
+      tokenMintB: usdcMint.publicKey,
+      offeredAmount: new BN(100 * TOKEN),
+      wantedAmount: new BN(50 * TOKEN),
+      terms: {
+        vesting: {
+          cliffSeconds: new BN(2 * DAY),
+          durationSeconds: new BN(10 * DAY),
+        },
+      },
+    });
+    const [vesting] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), offer.toBuffer(), bob.publicKey.toBuffer()],
+      escrow.program.programId
+    );
+
+    await takeVestingOffer(offer, vesting, 40 * TOKEN);
+    const firstTakeAt = await getUnixTimestamp(escrow);
+    await setUnixTimestamp(escrow, firstTakeAt + 5 * DAY);
+    await takeVestingOffer(offer, vesting, 60 * TOKEN);
+
+    const account = await escrow.program.account.vesting.fetch(vesting);
+    expect(account.totalAmount).toEqual(new BN(100 * TOKEN));
+    expect(account.claimedAmount).toEqual(new BN(0));
+    expect(account.startTimestamp).toEqual(new BN(firstTakeAt + 5 * DAY));
+    expect(
+      await getTokenBalance(escrow, ata(wifMint.publicKey, vesting))
+    ).toEqual(new BN(100 * TOKEN));
+
+    // Half way through the restarted schedule.
+    const before = await bobWif();
+    await setUnixTimestamp(escrow, firstTakeAt + 10 * DAY);
+    await escrow.program.methods
+      .claimVested()
+      .accounts({
+        taker: bob.publicKey,
+        vesting,
+        tokenMintA: wifMint.publicKey,
+        tokenProgram: TOKEN_PROGRAM,
+      } as any)
+      .signers([bob])
+      .rpc();
+    expect(await bobWif()).toEqual(before.add(new BN(50 * TOKEN)));
+  });
+});
@@ -0,0 +1,195 @@
+//! Builds the allowlist merkle tree for an escrow offer.
+//!
+//! Usage: `allowlist <wallets.csv>`
+//!
+//! The CSV holds one wallet address in the first column of every line.
+//! Empty lines, `#` comments and a `wallet` header are skipped.  The root is
+//! printed first, followed by a `wallet,proof` line for every wallet, where
+//! the proof is the hex encoded sibling hashes joined with `:`.
+
+use std::{collections::BTreeSet, env, fs, process, str::FromStr};
+
+use anchor_lang::prelude::Pubkey;
+use escrow::merkle::{leaf_hash, node_hash};
+
+struct Tree {
+    // `levels[0]` holds the leaves, the last level holds just the root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl Tree {
+    fn new(leaves: Vec<[u8; 32]>) -> Self {
+        let mut levels = vec![leaves];
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    // An odd node is carried up to the next level unchanged.
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn proof(&self, mut index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
+
+fn read_wallets(path: &str) -> Result<Vec<Pubkey>, String> {
+    let csv = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    parse_wallets(path, &csv)
+}
+
+// Sorted and deduplicated, so the same set of wallets always gives the same
+// root.
+fn parse_wallets(path: &str, csv: &str) -> Result<Vec<Pubkey>, String> {
+    let mut wallets = BTreeSet::new();
+    for (number, line) in csv.lines().enumerate() {
+        let field = line.split(',').next().unwrap_or_default().trim();
+        if field.is_empty() || field.starts_with('#') || field.eq_ignore_ascii_case("wallet") {
+            continue;
+        }
+        let wallet = Pubkey::from_str(field)
+            .map_err(|err| format!("{path}:{}: {field}: {err}", number + 1))?;
+        wallets.insert(wallet);
+    }
+
+    if wallets.is_empty() {
+        return Err(format!("{path}: no wallets found"));
+    }
+    Ok(wallets.into_iter().collect())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn main() {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("Usage: allowlist <wallets.csv>");
+        process::exit(2);
+    };
+
+    let wallets = read_wallets(&path).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
+
+    let tree = Tree::new(wallets.iter().map(leaf_hash).collect());
+
+    println!("# root: {}", hex(&tree.root()));
+    println!("wallet,proof");
+    for (index, wallet) in wallets.iter().enumerate() {
+        let proof: Vec<String> = tree.proof(index).iter().map(|node| hex(node)).collect();
+        println!("{wallet},{}", proof.join(":"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use escrow::merkle::verify;
+
+    use super::*;
+
+    const WALLETS_CSV: &str = "\
+wallet,note
+# Programs stand in for wallets, any address will do.
+11111111111111111111111111111111,system
+TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA,token
+
+ATokenGPvbdGVxr1b2hqZbsiqW5xWH25efTNsLJA8knL,associated token
+TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb,token 2022
+Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo
+TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA,listed twice
+";
+
+    fn tree_for(wallets: &[Pubkey]) -> Tree {
+        Tree::new(wallets.iter().map(leaf_hash).collect())
+    }
+
+    #[test]
+    fn proofs_verify_on_chain_for_every_wallet() {
+        let wallets = parse_wallets("wallets.csv", WALLETS_CSV).unwrap();
+        assert_eq!(wallets.len(), 5);
+
+        let tree = tree_for(&wallets);
+        for (index, wallet) in wallets.iter().enumerate() {
+            assert!(verify(&tree.proof(index), &tree.root(), leaf_hash(wallet)));
+        }
+    }
+
+    #[test]
+    fn proofs_verify_for_any_tree_size() {
+        // Odd sizes carry a node up a level, powers of two do not.
+        for size in 1..=17u8 {
+            let wallets: Vec<Pubkey> = (1..=size)
+                .map(|byte| Pubkey::new_from_array([byte; 32]))
+                .collect();
+            let tree = tree_for(&wallets);
+            for (index, wallet) in wallets.iter().enumerate() {
+                assert!(
+                    verify(&tree.proof(index), &tree.root(), leaf_hash(wallet)),
+                    "wallet {index} of {size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn proofs_do_not_verify_for_other_wallets() {
+        let wallets = parse_wallets("wallets.csv", WALLETS_CSV).unwrap();
+        let tree = tree_for(&wallets);
+        let outsider = Pubkey::new_from_array([7; 32]);
+
+        assert!(!verify(&tree.proof(0), &tree.root(), leaf_hash(&outsider)));
+        // Nor does a listed wallet's proof for the wallet next to it.
+        assert!(!verify(
+            &tree.proof(0),
+            &tree.root(),
+            leaf_hash(&wallets[2])
+        ));
+    }
+
+    #[test]
+    fn single_wallet_root_is_its_leaf() {
+        let wallets = parse_wallets("wallets.csv", "11111111111111111111111111111111").unwrap();
+        let tree = tree_for(&wallets);
+
+        assert_eq!(tree.root(), leaf_hash(&wallets[0]));
+        assert!(tree.proof(0).is_empty());
+        assert!(verify(&[], &tree.root(), leaf_hash(&wallets[0])));
+    }
+
+    #[test]
+    fn rejects_invalid_and_empty_lists() {
+        let err = parse_wallets("wallets.csv", "wallet\nnot-a-wallet\n").unwrap_err();
+        assert!(err.starts_with("wallets.csv:2: not-a-wallet:"), "{err}");
+
+        assert_eq!(
+            parse_wallets("wallets.csv", "wallet\n# none yet\n").unwrap_err(),
+            "wallets.csv: no wallets found"
+        );
+    }
+}